@@ -0,0 +1,160 @@
+//! A library of the avatars on disk, to choose one from.
+//!
+//! The [`AvatarLibrary`] resource scans its directories for glTF, GLB and VRM files
+//! in the background, and reads what they say about themselves (title, author,
+//! license and thumbnail) without loading them into Bevy. An [`AvatarGrid`] shows
+//! them, and clicking one sends [`AvatarChosen`].
+
+mod metadata;
+mod scan;
+mod ui;
+
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use bevy::tasks::{block_on, IoTaskPool, Task};
+use bevy_egui::egui;
+use worldspace_ui::WorldspaceUi;
+
+pub use crate::metadata::AvatarMetadata;
+use crate::scan::{scan, ScannedAvatar};
+pub use crate::ui::AvatarGrid;
+
+/// Scans the [`AvatarLibrary`] when it is inserted, draws the [`AvatarGrid`]s on
+/// world-space panels, and remembers the avatar that was chosen. Nothing happens
+/// until the app inserts the [`AvatarLibrary`] resource.
+pub struct AvatarLibraryPlugin;
+
+impl Plugin for AvatarLibraryPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_event::<AvatarChosen>().add_systems(
+			Update,
+			(start_scan, finish_scan, panel_ui, choose_avatar)
+				.chain()
+				.run_if(resource_exists::<AvatarLibrary>()),
+		);
+	}
+}
+
+/// Sent when the user picks an avatar. Loading it is up to the app, since glTF and
+/// VRM avatars need different loaders.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct AvatarChosen {
+	pub path: PathBuf,
+}
+
+/// An avatar file found in one of the library's directories.
+#[derive(Debug, Clone)]
+pub struct Avatar {
+	pub path: PathBuf,
+	pub metadata: AvatarMetadata,
+	pub thumbnail: Option<Handle<Image>>,
+}
+
+#[derive(Resource)]
+pub struct AvatarLibrary {
+	/// Searched for avatars, including their subdirectories.
+	pub dirs: Vec<PathBuf>,
+	/// Where thumbnails are kept between runs.
+	pub cache_dir: PathBuf,
+	/// Sorted by path.
+	avatars: Vec<Avatar>,
+	chosen: Option<PathBuf>,
+	scan: Option<Task<Vec<ScannedAvatar>>>,
+}
+
+impl AvatarLibrary {
+	/// A library of the avatars in `dirs`, caching thumbnails in the system's
+	/// temporary directory.
+	pub fn new(dirs: Vec<PathBuf>) -> Self {
+		Self {
+			dirs,
+			cache_dir: std::env::temp_dir().join("avatar-thumbnails"),
+			avatars: Vec::new(),
+			chosen: None,
+			scan: None,
+		}
+	}
+
+	pub fn avatars(&self) -> &[Avatar] {
+		&self.avatars
+	}
+
+	pub fn chosen(&self) -> Option<&Path> {
+		self.chosen.as_deref()
+	}
+
+	pub fn is_scanning(&self) -> bool {
+		self.scan.is_some()
+	}
+
+	/// Looks for avatars again, such as after changing [`Self::dirs`]. The current
+	/// avatars stay until the scan is done.
+	pub fn rescan(&mut self) {
+		let dirs = self.dirs.clone();
+		let cache_dir = self.cache_dir.clone();
+		self.scan =
+			Some(IoTaskPool::get().spawn(async move { scan(&dirs, &cache_dir) }));
+	}
+}
+
+fn start_scan(mut library: ResMut<AvatarLibrary>) {
+	if library.is_added() {
+		library.rescan();
+	}
+}
+
+fn finish_scan(
+	mut library: ResMut<AvatarLibrary>,
+	mut images: ResMut<Assets<Image>>,
+	mut panels: Query<&mut WorldspaceUi, With<AvatarGrid>>,
+) {
+	let finished = library.scan.as_ref().is_some_and(|task| task.is_finished());
+	if !finished {
+		return;
+	}
+	let scanned = block_on(library.scan.take().unwrap());
+	info!("found {} avatars", scanned.len());
+	// Panels only hold weak handles to the thumbnails, so dropping the old avatars
+	// frees them, but the panels still need to forget their texture ids.
+	for thumbnail in library.avatars.iter().filter_map(|a| a.thumbnail.as_ref()) {
+		for mut panel in panels.iter_mut() {
+			panel.remove_image(thumbnail);
+		}
+	}
+	library.avatars = scanned
+		.into_iter()
+		.map(|avatar| Avatar {
+			path: avatar.path,
+			metadata: avatar.metadata,
+			thumbnail: avatar.thumbnail.map(|image| images.add(image)),
+		})
+		.collect();
+}
+
+fn panel_ui(
+	mut panels: Query<(&mut WorldspaceUi, &AvatarGrid)>,
+	library: Res<AvatarLibrary>,
+	mut chosen: EventWriter<AvatarChosen>,
+) {
+	for (mut panel, grid) in panels.iter_mut() {
+		let ctx = panel.ctx_mut().clone();
+		egui::CentralPanel::default().show(&ctx, |ui| {
+			let clicked = grid.ui(ui, &library, |image| {
+				Some(panel.add_image(image.clone_weak()))
+			});
+			if let Some(path) = clicked {
+				chosen.send(AvatarChosen { path });
+			}
+		});
+	}
+}
+
+fn choose_avatar(
+	mut library: ResMut<AvatarLibrary>,
+	mut chosen: EventReader<AvatarChosen>,
+) {
+	if let Some(chosen) = chosen.read().last() {
+		library.chosen = Some(chosen.path.clone());
+	}
+}
@@ -0,0 +1,81 @@
+use avatar_library::{AvatarChosen, AvatarGrid, AvatarLibrary, AvatarLibraryPlugin};
+use bevy::prelude::*;
+use bevy_flycam::FlyCam;
+use color_eyre::Result;
+use worldspace_ui::pointer::add_mouse_pointer;
+use worldspace_ui::{WorldspaceUi, WorldspaceUiPlugin};
+
+const ASSET_FOLDER: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../assets/");
+
+fn main() -> Result<()> {
+	color_eyre::install()?;
+
+	// Add more directories to the library with command line arguments.
+	let dirs = std::iter::once(ASSET_FOLDER.into())
+		.chain(std::env::args_os().skip(1).map(Into::into))
+		.collect();
+	App::new()
+		.add_plugins(DefaultPlugins.set(AssetPlugin {
+			file_path: ASSET_FOLDER.to_string(),
+			..default()
+		}))
+		.add_plugins(bevy_flycam::PlayerPlugin)
+		.add_plugins(bevy_egui::EguiPlugin)
+		.add_plugins((WorldspaceUiPlugin, AvatarLibraryPlugin))
+		.insert_resource(AvatarLibrary::new(dirs))
+		.insert_resource(AmbientLight {
+			color: Color::WHITE,
+			brightness: 1.,
+		})
+		.add_systems(Startup, setup)
+		.add_systems(Update, (spawn_avatar, add_mouse_pointer::<FlyCam>))
+		.run();
+
+	Ok(())
+}
+
+/// The avatar that was chosen last.
+#[derive(Component)]
+struct Avatar;
+
+fn setup(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+	let panel = WorldspaceUi::menu(&mut images, Vec2::new(0.6, 0.45));
+	commands.spawn((
+		SpatialBundle::from_transform(
+			Transform::from_xyz(-0.5, 0.0, -0.5)
+				.with_rotation(Quat::from_rotation_y(0.4)),
+		),
+		panel,
+		AvatarGrid::default(),
+	));
+}
+
+fn spawn_avatar(
+	mut commands: Commands,
+	mut chosen: EventReader<AvatarChosen>,
+	avatars: Query<Entity, With<Avatar>>,
+	assets: Res<AssetServer>,
+) {
+	let Some(chosen) = chosen.read().last() else {
+		return;
+	};
+	let is_gltf = chosen
+		.path
+		.extension()
+		.is_some_and(|extension| extension == "gltf" || extension == "glb");
+	if !is_gltf {
+		warn!("{} needs a VRM loader to be shown", chosen.path.display());
+		return;
+	}
+	for avatar in avatars.iter() {
+		commands.entity(avatar).despawn_recursive();
+	}
+	commands.spawn((
+		SceneBundle {
+			scene: assets.load(format!("{}#Scene0", chosen.path.display())),
+			transform: Transform::from_xyz(0.5, -1.0, -1.0),
+			..default()
+		},
+		Avatar,
+	));
+}
@@ -0,0 +1,426 @@
+//! Reads what an avatar file says about itself, without loading its meshes or
+//! textures. Only the JSON part of the file is parsed, plus the bytes of the
+//! thumbnail, if it has one.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+
+use eyre::{bail, ensure, Result, WrapErr};
+use serde_json::Value;
+
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const JSON_CHUNK: u32 = 0x4E4F534A;
+/// Length of the GLB header, and of each chunk's header.
+const GLB_HEADER_LEN: u64 = 12;
+const CHUNK_HEADER_LEN: u64 = 8;
+/// The most we read of a file's JSON, or of its thumbnail. Lengths come from the file,
+/// so a broken one could otherwise ask for gigabytes.
+const MAX_JSON_LEN: u64 = 64 * 1024 * 1024;
+const MAX_THUMBNAIL_LEN: u64 = 16 * 1024 * 1024;
+
+/// What an avatar file says about itself. VRM files have all of this in their meta;
+/// plain glTF files only have a copyright, so the title falls back to the file name.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AvatarMetadata {
+	pub title: String,
+	pub author: Option<String>,
+	/// A license name or URL, or a glTF copyright notice.
+	pub license: Option<String>,
+}
+
+/// The encoded thumbnail image from an avatar file.
+pub(crate) struct Thumbnail {
+	pub bytes: Vec<u8>,
+	/// Such as `image/png`.
+	pub mime_type: String,
+}
+
+/// The parsed JSON of a glTF, and where the binary chunk starts if it is a GLB.
+struct GltfJson {
+	json: Value,
+	bin_offset: Option<u64>,
+}
+
+pub(crate) fn read_metadata(path: &Path) -> Result<AvatarMetadata> {
+	let gltf = read_json(path)?;
+	Ok(metadata(&gltf.json, path))
+}
+
+pub(crate) fn read_thumbnail(path: &Path) -> Result<Option<Thumbnail>> {
+	let gltf = read_json(path)?;
+	let Some(image) = thumbnail_image(&gltf.json) else {
+		return Ok(None);
+	};
+	thumbnail(path, &gltf, image)
+}
+
+fn read_json(path: &Path) -> Result<GltfJson> {
+	let mut file = File::open(path)
+		.wrap_err_with(|| format!("failed to open {}", path.display()))?;
+	let mut magic = [0; 4];
+	file.read_exact(&mut magic)?;
+	file.rewind()?;
+	if &magic != GLB_MAGIC {
+		let json = serde_json::from_reader(std::io::BufReader::new(file))
+			.wrap_err_with(|| format!("failed to parse {}", path.display()))?;
+		return Ok(GltfJson {
+			json,
+			bin_offset: None,
+		});
+	}
+
+	// GLB (and VRM): a header, then a JSON chunk, then an optional binary chunk.
+	let mut header = [0; (GLB_HEADER_LEN + CHUNK_HEADER_LEN) as usize];
+	file.read_exact(&mut header)?;
+	let json_len = u32::from_le_bytes(header[12..16].try_into().unwrap());
+	let chunk_type = u32::from_le_bytes(header[16..20].try_into().unwrap());
+	ensure!(
+		chunk_type == JSON_CHUNK,
+		"{} has no JSON chunk",
+		path.display()
+	);
+	let json = read_bytes(
+		&mut file,
+		GLB_HEADER_LEN + CHUNK_HEADER_LEN,
+		u64::from(json_len),
+		MAX_JSON_LEN,
+	)
+	.wrap_err_with(|| format!("failed to read the JSON of {}", path.display()))?;
+	let json = serde_json::from_slice(&json)
+		.wrap_err_with(|| format!("failed to parse {}", path.display()))?;
+	Ok(GltfJson {
+		json,
+		bin_offset: Some(GLB_HEADER_LEN + CHUNK_HEADER_LEN * 2 + u64::from(json_len)),
+	})
+}
+
+fn metadata(json: &Value, path: &Path) -> AvatarMetadata {
+	let string =
+		|value: &Value| value.as_str().filter(|s| !s.is_empty()).map(str::to_owned);
+	let file_name = || {
+		path.file_stem()
+			.map(|stem| stem.to_string_lossy().into_owned())
+			.unwrap_or_default()
+	};
+	// VRM 1.0
+	let meta = &json["extensions"]["VRMC_vrm"]["meta"];
+	if meta.is_object() {
+		let authors: Vec<&str> = meta["authors"]
+			.as_array()
+			.into_iter()
+			.flatten()
+			.filter_map(Value::as_str)
+			.collect();
+		return AvatarMetadata {
+			title: string(&meta["name"]).unwrap_or_else(file_name),
+			author: (!authors.is_empty()).then(|| authors.join(", ")),
+			license: string(&meta["licenseUrl"]),
+		};
+	}
+	// VRM 0.x
+	let meta = &json["extensions"]["VRM"]["meta"];
+	if meta.is_object() {
+		return AvatarMetadata {
+			title: string(&meta["title"]).unwrap_or_else(file_name),
+			author: string(&meta["author"]),
+			license: string(&meta["licenseName"])
+				.filter(|name| name != "Other")
+				.or_else(|| string(&meta["otherLicenseUrl"])),
+		};
+	}
+	AvatarMetadata {
+		title: file_name(),
+		author: None,
+		license: string(&json["asset"]["copyright"]),
+	}
+}
+
+/// The index of the thumbnail in the glTF's images. Only VRMs have one.
+fn thumbnail_image(json: &Value) -> Option<usize> {
+	let vrm1 = &json["extensions"]["VRMC_vrm"]["meta"]["thumbnailImage"];
+	if let Some(image) = vrm1.as_u64() {
+		return Some(image as usize);
+	}
+	// VRM 0.x points at a texture rather than an image.
+	let texture = json["extensions"]["VRM"]["meta"]["texture"].as_u64()?;
+	json["textures"][texture as usize]["source"]
+		.as_u64()
+		.map(|image| image as usize)
+}
+
+fn thumbnail(path: &Path, gltf: &GltfJson, image: usize) -> Result<Option<Thumbnail>> {
+	let json = &gltf.json;
+	let image = &json["images"][image];
+	if let Some(uri) = image["uri"].as_str() {
+		if uri.starts_with("data:") {
+			return Ok(None);
+		}
+		let Some(mime_type) = mime_type_for(uri) else {
+			return Ok(None);
+		};
+		let mut file = File::open(sibling(path, uri)?)?;
+		let len = file.metadata()?.len();
+		let bytes = read_bytes(&mut file, 0, len, MAX_THUMBNAIL_LEN)
+			.wrap_err("failed to read the thumbnail")?;
+		return Ok(Some(Thumbnail { bytes, mime_type }));
+	}
+
+	let Some(view) = image["bufferView"].as_u64() else {
+		return Ok(None);
+	};
+	let Some(mime_type) = image["mimeType"].as_str() else {
+		return Ok(None);
+	};
+	let view = &json["bufferViews"][view as usize];
+	let offset = view["byteOffset"].as_u64().unwrap_or(0);
+	let Some(len) = view["byteLength"].as_u64() else {
+		bail!("buffer view of the thumbnail has no length");
+	};
+	let buffer = &json["buffers"][view["buffer"].as_u64().unwrap_or(0) as usize];
+	let (mut file, start) = match (buffer["uri"].as_str(), gltf.bin_offset) {
+		(Some(uri), _) if uri.starts_with("data:") => return Ok(None),
+		(Some(uri), _) => (File::open(sibling(path, uri)?)?, offset),
+		(None, Some(bin_offset)) => (File::open(path)?, bin_offset + offset),
+		(None, None) => bail!("thumbnail is in a buffer that doesn't exist"),
+	};
+	let bytes = read_bytes(&mut file, start, len, MAX_THUMBNAIL_LEN)
+		.wrap_err("failed to read the thumbnail")?;
+	Ok(Some(Thumbnail {
+		bytes,
+		mime_type: mime_type.to_owned(),
+	}))
+}
+
+/// Reads `len` bytes from `start`, once we know the file has that many and they are
+/// no more than `max`.
+fn read_bytes(file: &mut File, start: u64, len: u64, max: u64) -> Result<Vec<u8>> {
+	ensure!(len <= max, "{len} bytes is more than the limit of {max}");
+	let file_len = file.metadata()?.len();
+	ensure!(
+		start.checked_add(len).is_some_and(|end| end <= file_len),
+		"{len} bytes at {start} is past the end of the file"
+	);
+	file.seek(SeekFrom::Start(start))?;
+	let mut bytes = vec![0; len as usize];
+	file.read_exact(&mut bytes)?;
+	Ok(bytes)
+}
+
+/// A file referred to by a glTF, which is relative to the glTF. The URI can't leave
+/// the glTF's directory, since an avatar someone sent you shouldn't be able to read
+/// other files.
+fn sibling(path: &Path, uri: &str) -> Result<PathBuf> {
+	let relative = PathBuf::from(percent_decode(uri)?);
+	ensure!(
+		relative.components().all(|component| matches!(
+			component,
+			Component::Normal(_) | Component::CurDir
+		)),
+		"{uri} is outside of the avatar's directory"
+	);
+	Ok(path.parent().unwrap_or(Path::new("")).join(relative))
+}
+
+/// Decodes the `%XX` escapes in a URI.
+fn percent_decode(uri: &str) -> Result<String> {
+	let mut bytes = Vec::with_capacity(uri.len());
+	let mut rest = uri.as_bytes();
+	while let Some((&byte, tail)) = rest.split_first() {
+		if byte != b'%' {
+			bytes.push(byte);
+			rest = tail;
+			continue;
+		}
+		let hex = tail
+			.get(..2)
+			.and_then(|hex| std::str::from_utf8(hex).ok())
+			.and_then(|hex| u8::from_str_radix(hex, 16).ok());
+		let Some(decoded) = hex else {
+			bail!("{uri} has a broken escape");
+		};
+		bytes.push(decoded);
+		rest = &tail[2..];
+	}
+	String::from_utf8(bytes).wrap_err_with(|| format!("{uri} isn't UTF-8"))
+}
+
+fn mime_type_for(uri: &str) -> Option<String> {
+	let extension = Path::new(uri).extension()?.to_str()?.to_lowercase();
+	match extension.as_str() {
+		"png" => Some("image/png".to_owned()),
+		"jpg" | "jpeg" => Some("image/jpeg".to_owned()),
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+
+	use super::*;
+
+	/// An empty directory of its own for each test, since tests run in parallel.
+	fn temp_dir(name: &str) -> PathBuf {
+		let dir = std::env::temp_dir()
+			.join(format!("avatar-library-{}-{name}", std::process::id()));
+		let _ = std::fs::remove_dir_all(&dir);
+		std::fs::create_dir_all(&dir).unwrap();
+		dir
+	}
+
+	/// A GLB with `json` in its JSON chunk and `bin` in its binary chunk.
+	fn glb(json: &Value, bin: &[u8]) -> Vec<u8> {
+		let mut json = serde_json::to_vec(json).unwrap();
+		json.resize(json.len().next_multiple_of(4), b' ');
+		let mut bin = bin.to_vec();
+		bin.resize(bin.len().next_multiple_of(4), 0);
+		let len =
+			GLB_HEADER_LEN + CHUNK_HEADER_LEN * 2 + (json.len() + bin.len()) as u64;
+		let mut glb = Vec::new();
+		glb.extend(GLB_MAGIC);
+		glb.extend(2_u32.to_le_bytes());
+		glb.extend((len as u32).to_le_bytes());
+		glb.extend((json.len() as u32).to_le_bytes());
+		glb.extend(JSON_CHUNK.to_le_bytes());
+		glb.extend(json);
+		glb.extend((bin.len() as u32).to_le_bytes());
+		glb.extend(b"BIN\0");
+		glb.extend(bin);
+		glb
+	}
+
+	const PNG: &[u8] = b"\x89PNG not really";
+
+	#[test]
+	fn reads_vrm1_meta_and_embedded_thumbnail() {
+		let dir = temp_dir("vrm1");
+		let path = dir.join("alice.vrm");
+		let json = json!({
+			"asset": { "version": "2.0" },
+			"extensions": { "VRMC_vrm": { "meta": {
+				"name": "Alice",
+				"authors": ["Ann", "Bob"],
+				"licenseUrl": "https://vrm.dev/licenses/1.0/",
+				"thumbnailImage": 0,
+			} } },
+			"images": [{ "bufferView": 0, "mimeType": "image/png" }],
+			"bufferViews": [{ "buffer": 0, "byteOffset": 4, "byteLength": PNG.len() }],
+			"buffers": [{ "byteLength": 4 + PNG.len() }],
+		});
+		let mut bin = b"skip".to_vec();
+		bin.extend(PNG);
+		std::fs::write(&path, glb(&json, &bin)).unwrap();
+
+		assert_eq!(
+			read_metadata(&path).unwrap(),
+			AvatarMetadata {
+				title: "Alice".to_owned(),
+				author: Some("Ann, Bob".to_owned()),
+				license: Some("https://vrm.dev/licenses/1.0/".to_owned()),
+			}
+		);
+		let thumbnail = read_thumbnail(&path).unwrap().unwrap();
+		assert_eq!(thumbnail.bytes, PNG);
+		assert_eq!(thumbnail.mime_type, "image/png");
+	}
+
+	#[test]
+	fn reads_vrm0_meta_through_its_texture() {
+		let dir = temp_dir("vrm0");
+		let path = dir.join("bob.vrm");
+		let json = json!({
+			"extensions": { "VRM": { "meta": {
+				"title": "",
+				"author": "Bob",
+				"licenseName": "Other",
+				"otherLicenseUrl": "https://example.com/license",
+				"texture": 1,
+			} } },
+			"textures": [{ "source": 0 }, { "source": 1 }],
+			"images": [
+				{ "bufferView": 0, "mimeType": "image/jpeg" },
+				{ "bufferView": 1, "mimeType": "image/png" },
+			],
+			"bufferViews": [
+				{ "buffer": 0, "byteLength": 4 },
+				{ "buffer": 0, "byteOffset": 4, "byteLength": PNG.len() },
+			],
+			"buffers": [{ "byteLength": 4 + PNG.len() }],
+		});
+		let mut bin = b"jpeg".to_vec();
+		bin.extend(PNG);
+		std::fs::write(&path, glb(&json, &bin)).unwrap();
+
+		// An empty title falls back to the file name, and "Other" to the URL.
+		assert_eq!(
+			read_metadata(&path).unwrap(),
+			AvatarMetadata {
+				title: "bob".to_owned(),
+				author: Some("Bob".to_owned()),
+				license: Some("https://example.com/license".to_owned()),
+			}
+		);
+		let thumbnail = read_thumbnail(&path).unwrap().unwrap();
+		assert_eq!(thumbnail.bytes, PNG);
+		assert_eq!(thumbnail.mime_type, "image/png");
+	}
+
+	#[test]
+	fn reads_plain_gltf_with_an_external_thumbnail() {
+		let dir = temp_dir("external");
+		std::fs::create_dir(dir.join("my images")).unwrap();
+		std::fs::write(dir.join("my images").join("face.png"), PNG).unwrap();
+		let path = dir.join("carol.gltf");
+		let json = json!({
+			"asset": { "version": "2.0", "copyright": "CC0" },
+			"extensions": { "VRMC_vrm": { "meta": { "thumbnailImage": 0 } } },
+			"images": [{ "uri": "my%20images/face.png" }],
+		});
+		std::fs::write(&path, serde_json::to_vec(&json).unwrap()).unwrap();
+
+		let metadata = read_metadata(&path).unwrap();
+		assert_eq!(metadata.title, "carol");
+		let thumbnail = read_thumbnail(&path).unwrap().unwrap();
+		assert_eq!(thumbnail.bytes, PNG);
+		assert_eq!(thumbnail.mime_type, "image/png");
+
+		let json = json!({
+			"asset": { "version": "2.0", "copyright": "CC0" },
+		});
+		std::fs::write(&path, serde_json::to_vec(&json).unwrap()).unwrap();
+		assert_eq!(
+			read_metadata(&path).unwrap(),
+			AvatarMetadata {
+				title: "carol".to_owned(),
+				author: None,
+				license: Some("CC0".to_owned()),
+			}
+		);
+		assert!(read_thumbnail(&path).unwrap().is_none());
+	}
+
+	#[test]
+	fn external_uris_stay_in_the_directory() {
+		let path = Path::new("avatars/dave.gltf");
+		assert_eq!(
+			sibling(path, "a%2Fb.png").unwrap(),
+			Path::new("avatars/a/b.png")
+		);
+		assert!(sibling(path, "../secret.png").is_err());
+		assert!(sibling(path, "%2E%2E/secret.png").is_err());
+		assert!(sibling(path, "/etc/secret.png").is_err());
+		assert!(sibling(path, "broken%2").is_err());
+	}
+
+	#[test]
+	fn rejects_lengths_past_the_end() {
+		let dir = temp_dir("truncated");
+		let path = dir.join("eve.vrm");
+		let mut bytes = glb(&json!({}), &[]);
+		// Claim a JSON chunk longer than the file.
+		bytes[12..16].copy_from_slice(&1000_u32.to_le_bytes());
+		std::fs::write(&path, bytes).unwrap();
+		assert!(read_metadata(&path).is_err());
+	}
+}
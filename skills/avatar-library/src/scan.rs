@@ -0,0 +1,134 @@
+//! Finds the avatars in the library's directories, and keeps their thumbnails in a
+//! cache on disk, so that big avatar files only have to be read once.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use bevy::render::texture::{CompressedImageFormats, ImageSampler, ImageType};
+use eyre::Result;
+
+use crate::metadata::{read_metadata, read_thumbnail, Thumbnail};
+use crate::AvatarMetadata;
+
+/// File extensions of avatars, in lower case.
+const AVATAR_EXTENSIONS: [&str; 3] = ["gltf", "glb", "vrm"];
+
+pub(crate) struct ScannedAvatar {
+	pub path: PathBuf,
+	pub metadata: AvatarMetadata,
+	pub thumbnail: Option<Image>,
+}
+
+/// Reads every avatar in `dirs` and their subdirectories. Avatars that can't be read
+/// are left out.
+pub(crate) fn scan(dirs: &[PathBuf], cache_dir: &Path) -> Vec<ScannedAvatar> {
+	let mut paths = Vec::new();
+	for dir in dirs {
+		find_avatars(dir, &mut paths);
+	}
+	paths.sort();
+	if let Err(err) = std::fs::create_dir_all(cache_dir) {
+		warn!("failed to create {}: {err}", cache_dir.display());
+	}
+	paths
+		.into_iter()
+		.filter_map(|path| {
+			let metadata = read_metadata(&path)
+				.map_err(|err| warn!("skipping {}: {err:#}", path.display()))
+				.ok()?;
+			let thumbnail = cached_thumbnail(&path, cache_dir)
+				.map_err(|err| {
+					warn!("failed to read thumbnail of {}: {err:#}", path.display())
+				})
+				.ok()
+				.flatten()
+				.and_then(|thumbnail| decode(&path, thumbnail));
+			Some(ScannedAvatar {
+				path,
+				metadata,
+				thumbnail,
+			})
+		})
+		.collect()
+}
+
+fn find_avatars(dir: &Path, paths: &mut Vec<PathBuf>) {
+	let entries = match std::fs::read_dir(dir) {
+		Ok(entries) => entries,
+		Err(err) => {
+			warn!("failed to read {}: {err}", dir.display());
+			return;
+		}
+	};
+	for entry in entries.flatten() {
+		let path = entry.path();
+		if path.is_dir() {
+			find_avatars(&path, paths);
+			continue;
+		}
+		let is_avatar = path
+			.extension()
+			.and_then(|extension| extension.to_str())
+			.is_some_and(|extension| {
+				AVATAR_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+			});
+		if is_avatar {
+			paths.push(path);
+		}
+	}
+}
+
+/// The avatar's thumbnail from the cache, or from the avatar if it isn't cached yet.
+/// The cache is keyed by the avatar's path, size and modification time, so a
+/// changed avatar is read again.
+fn cached_thumbnail(path: &Path, cache_dir: &Path) -> Result<Option<Thumbnail>> {
+	let file = std::fs::metadata(path)?;
+	let mut hasher = DefaultHasher::new();
+	path.hash(&mut hasher);
+	file.len().hash(&mut hasher);
+	file.modified()?.hash(&mut hasher);
+	let key = format!("{:016x}", hasher.finish());
+
+	for (extension, mime_type) in [("png", "image/png"), ("jpg", "image/jpeg")] {
+		let cached = cache_dir.join(format!("{key}.{extension}"));
+		if let Ok(bytes) = std::fs::read(cached) {
+			return Ok(Some(Thumbnail {
+				bytes,
+				mime_type: mime_type.to_owned(),
+			}));
+		}
+	}
+	// Remembers that the avatar has no thumbnail, so it isn't read again.
+	let missing = cache_dir.join(format!("{key}.none"));
+	if missing.exists() {
+		return Ok(None);
+	}
+
+	let thumbnail = read_thumbnail(path)?;
+	let cached = match &thumbnail {
+		Some(thumbnail) if thumbnail.mime_type == "image/jpeg" => {
+			cache_dir.join(format!("{key}.jpg"))
+		}
+		Some(_) => cache_dir.join(format!("{key}.png")),
+		None => missing,
+	};
+	let bytes = thumbnail.as_ref().map_or(&[][..], |t| &t.bytes);
+	if let Err(err) = std::fs::write(&cached, bytes) {
+		warn!("failed to cache {}: {err}", cached.display());
+	}
+	Ok(thumbnail)
+}
+
+fn decode(path: &Path, thumbnail: Thumbnail) -> Option<Image> {
+	Image::from_buffer(
+		&thumbnail.bytes,
+		ImageType::MimeType(&thumbnail.mime_type),
+		CompressedImageFormats::NONE,
+		true,
+		ImageSampler::Default,
+	)
+	.map_err(|err| warn!("failed to decode thumbnail of {}: {err}", path.display()))
+	.ok()
+}
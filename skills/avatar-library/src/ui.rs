@@ -0,0 +1,127 @@
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::AvatarLibrary;
+
+/// A grid of cards for the avatars in the [`AvatarLibrary`], each with its thumbnail
+/// and title. A [`WorldspaceUi`](worldspace_ui::WorldspaceUi) panel with one shows the
+/// grid and turns clicks into [`AvatarChosen`](crate::AvatarChosen) events. To show it
+/// in a screen-space window, call [`Self::ui`] with a way to get the thumbnails'
+/// texture ids from that window's egui context.
+#[derive(Component, Debug, Clone)]
+pub struct AvatarGrid {
+	/// Width of each avatar's card, in points. The thumbnail is square.
+	pub card_width: f32,
+}
+
+impl Default for AvatarGrid {
+	fn default() -> Self {
+		Self { card_width: 96.0 }
+	}
+}
+
+impl AvatarGrid {
+	/// Draws the grid, returning the avatar that was clicked, if any. `image_id`
+	/// gives the egui texture of a thumbnail, which depends on where the grid is
+	/// drawn.
+	pub fn ui(
+		&self,
+		ui: &mut egui::Ui,
+		library: &AvatarLibrary,
+		mut image_id: impl FnMut(&Handle<Image>) -> Option<egui::TextureId>,
+	) -> Option<PathBuf> {
+		let mut clicked = None;
+		ui.horizontal(|ui| {
+			ui.heading("Avatars");
+			if library.is_scanning() {
+				ui.spinner();
+			} else {
+				ui.label(format!("{} found", library.avatars().len()));
+			}
+		});
+		ui.separator();
+
+		let text_height = ui.text_style_height(&egui::TextStyle::Body);
+		let card_size =
+			egui::vec2(self.card_width, self.card_width + text_height * 2.5);
+		egui::ScrollArea::vertical().show(ui, |ui| {
+			ui.horizontal_wrapped(|ui| {
+				for avatar in library.avatars() {
+					let chosen = library.chosen() == Some(avatar.path.as_path());
+					let (rect, response) =
+						ui.allocate_exact_size(card_size, egui::Sense::click());
+					let visuals = ui.style().interact_selectable(&response, chosen);
+					let painter = ui.painter().with_clip_rect(rect);
+					painter.rect(rect, 4.0, visuals.weak_bg_fill, visuals.bg_stroke);
+
+					let thumbnail = egui::Rect::from_min_size(
+						rect.min,
+						egui::Vec2::splat(self.card_width),
+					)
+					.shrink(4.0);
+					match avatar.thumbnail.as_ref().and_then(&mut image_id) {
+						Some(texture) => painter.image(
+							texture,
+							thumbnail,
+							egui::Rect::from_min_max(
+								egui::pos2(0.0, 0.0),
+								egui::pos2(1.0, 1.0),
+							),
+							egui::Color32::WHITE,
+						),
+						None => {
+							painter.rect_filled(
+								thumbnail,
+								2.0,
+								ui.visuals().extreme_bg_color,
+							);
+							let initial = avatar.metadata.title.chars().next();
+							painter.text(
+								thumbnail.center(),
+								egui::Align2::CENTER_CENTER,
+								initial.unwrap_or('?').to_uppercase(),
+								egui::FontId::proportional(self.card_width / 2.0),
+								visuals.text_color(),
+							);
+						}
+					}
+
+					let text_top = thumbnail.bottom() + 4.0;
+					painter.text(
+						egui::pos2(rect.left() + 4.0, text_top),
+						egui::Align2::LEFT_TOP,
+						&avatar.metadata.title,
+						egui::FontId::proportional(text_height),
+						visuals.text_color(),
+					);
+					if let Some(author) = &avatar.metadata.author {
+						painter.text(
+							egui::pos2(rect.left() + 4.0, text_top + text_height),
+							egui::Align2::LEFT_TOP,
+							author,
+							egui::FontId::proportional(text_height * 0.8),
+							ui.visuals().weak_text_color(),
+						);
+					}
+
+					let response = response.on_hover_ui(|ui| {
+						ui.strong(&avatar.metadata.title);
+						if let Some(author) = &avatar.metadata.author {
+							ui.label(format!("By {author}"));
+						}
+						if let Some(license) = &avatar.metadata.license {
+							ui.label(format!("License: {license}"));
+						}
+						ui.small(avatar.path.display().to_string());
+					});
+					if response.clicked() {
+						clicked = Some(avatar.path.clone());
+					}
+				}
+			});
+		});
+		clicked
+	}
+}
@@ -0,0 +1,844 @@
+//! A gizmo on the selected entities, that moves, rotates and scales them with the
+//! mouse in the [`InspectorCamera`]'s viewport.
+
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_egui::{egui, EguiContext};
+use worldspace_ui::pointer::WorldspacePointer;
+
+use crate::history::{Edit, History, HistoryEntry};
+use crate::{InspectorCamera, Selection};
+
+/// How close the mouse must be to a handle to grab it, as a fraction of the length
+/// of the gizmo's axes.
+const GRAB_DISTANCE: f32 = 0.08;
+
+/// Draws the [`TransformGizmo`] on the [`Selection`], and drags it with the left mouse
+/// button. Needs the [`InspectorPlugin`](crate::InspectorPlugin), and a camera with an
+/// [`InspectorCamera`] to drag it from.
+pub struct TransformGizmoPlugin;
+
+impl Plugin for TransformGizmoPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<GizmoSettings>()
+			.init_resource::<TransformGizmo>()
+			.add_systems(
+				Update,
+				(gizmo_shortcuts, drag_gizmo, draw_gizmo)
+					.chain()
+					.in_set(GizmoSet),
+			);
+	}
+}
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GizmoSet;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GizmoMode {
+	#[default]
+	Translate,
+	Rotate,
+	Scale,
+}
+
+/// Which axes the gizmo uses. Scaling is always along each entity's own axes, the
+/// ones closest to the dragged handle.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GizmoSpace {
+	#[default]
+	World,
+	/// The axes of the active entity, the last one selected.
+	Local,
+}
+
+/// What the selected entities rotate and scale around.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GizmoPivot {
+	/// The middle of the selected entities.
+	#[default]
+	Median,
+	/// Each entity's own origin, so they rotate and scale in place.
+	Individual,
+	/// The origin of the active entity, the last one selected.
+	Active,
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct GizmoSettings {
+	pub mode: GizmoMode,
+	pub space: GizmoSpace,
+	pub pivot: GizmoPivot,
+	/// Whether dragging snaps to steps. Holding control while dragging does the
+	/// opposite.
+	pub snap: bool,
+	/// In metres.
+	pub translate_step: f32,
+	/// In degrees.
+	pub rotate_step: f32,
+	/// Of the scale factor.
+	pub scale_step: f32,
+	/// Length of the gizmo's axes, as a fraction of its distance to the camera, so it
+	/// keeps the same size on screen.
+	pub size: f32,
+	/// Whether W, E and R pick the mode, and X switches the space.
+	pub shortcuts: bool,
+}
+
+impl Default for GizmoSettings {
+	fn default() -> Self {
+		Self {
+			mode: GizmoMode::default(),
+			space: GizmoSpace::default(),
+			pivot: GizmoPivot::default(),
+			snap: false,
+			translate_step: 0.25,
+			rotate_step: 15.0,
+			scale_step: 0.1,
+			size: 0.15,
+			shortcuts: true,
+		}
+	}
+}
+
+/// The state of the gizmo: which of its handles the mouse is over, or is dragging.
+#[derive(Resource, Debug, Default)]
+pub struct TransformGizmo {
+	hovered: Option<GizmoHandle>,
+	drag: Option<Drag>,
+}
+
+impl TransformGizmo {
+	/// Whether the mouse is over the gizmo or dragging it, so that clicks are the
+	/// gizmo's rather than the viewport's.
+	pub fn is_active(&self) -> bool {
+		self.hovered.is_some() || self.drag.is_some()
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GizmoHandle {
+	/// The arrow of an axis, which moves or scales along it.
+	Axis(usize),
+	/// The ring around an axis, which rotates around it.
+	Ring(usize),
+	/// The middle of the gizmo, which moves in the camera's plane or scales evenly.
+	Center,
+}
+
+/// Where the gizmo is, and which way it points.
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+	pivot: Vec3,
+	axes: [Vec3; 3],
+	length: f32,
+}
+
+#[derive(Debug)]
+struct Drag {
+	handle: GizmoHandle,
+	frame: Frame,
+	/// Where the handle was grabbed.
+	grabbed: Vec3,
+	/// The camera's right when the handle was grabbed, for scaling evenly.
+	right: Vec3,
+	/// The camera's forward when the handle was grabbed. The middle moves in the
+	/// plane facing it, which stays put while dragging.
+	forward: Vec3,
+	/// The global transform of each entity when the handle was grabbed, and the
+	/// inverse of its parent's global transform.
+	targets: Vec<(Entity, Transform, Option<Mat4>)>,
+	/// The transform of each entity when the handle was grabbed, for undoing.
+	before: Vec<(Entity, Transform)>,
+}
+
+const AXIS_COLORS: [Color; 3] = [Color::RED, Color::GREEN, Color::BLUE];
+const CENTER_COLOR: Color = Color::WHITE;
+const ACTIVE_COLOR: Color = Color::YELLOW;
+
+/// Draws the gizmo's settings.
+pub fn gizmo_ui(world: &mut World, ui: &mut egui::Ui) {
+	let Some(mut settings) = world.get_resource_mut::<GizmoSettings>() else {
+		return;
+	};
+	ui.horizontal(|ui| {
+		for (mode, label) in [
+			(GizmoMode::Translate, "Move"),
+			(GizmoMode::Rotate, "Rotate"),
+			(GizmoMode::Scale, "Scale"),
+		] {
+			ui.selectable_value(&mut settings.mode, mode, label);
+		}
+		ui.separator();
+		for (space, label) in
+			[(GizmoSpace::World, "World"), (GizmoSpace::Local, "Local")]
+		{
+			ui.selectable_value(&mut settings.space, space, label);
+		}
+	});
+	ui.horizontal(|ui| {
+		ui.label("Pivot:");
+		egui::ComboBox::from_id_source("gizmo pivot")
+			.selected_text(format!("{:?}", settings.pivot))
+			.show_ui(ui, |ui| {
+				for pivot in [
+					GizmoPivot::Median,
+					GizmoPivot::Individual,
+					GizmoPivot::Active,
+				] {
+					ui.selectable_value(
+						&mut settings.pivot,
+						pivot,
+						format!("{pivot:?}"),
+					);
+				}
+			});
+	});
+	ui.horizontal(|ui| {
+		ui.checkbox(&mut settings.snap, "Snap");
+		let (step, suffix) = match settings.mode {
+			GizmoMode::Translate => (&mut settings.translate_step, " m"),
+			GizmoMode::Rotate => (&mut settings.rotate_step, "°"),
+			GizmoMode::Scale => (&mut settings.scale_step, "×"),
+		};
+		ui.add(
+			egui::DragValue::new(step)
+				.speed(0.01)
+				.clamp_range(0.001..=f32::MAX)
+				.suffix(suffix),
+		);
+	});
+}
+
+fn gizmo_shortcuts(
+	keys: Res<Input<KeyCode>>,
+	mut settings: ResMut<GizmoSettings>,
+	mut contexts: Query<&mut EguiContext, With<PrimaryWindow>>,
+) {
+	if !settings.shortcuts {
+		return;
+	}
+	// Don't steal the keys from a text field.
+	if let Ok(mut context) = contexts.get_single_mut() {
+		if context.get_mut().wants_keyboard_input() {
+			return;
+		}
+	}
+	for (key, mode) in [
+		(KeyCode::W, GizmoMode::Translate),
+		(KeyCode::E, GizmoMode::Rotate),
+		(KeyCode::R, GizmoMode::Scale),
+	] {
+		if keys.just_pressed(key) {
+			settings.mode = mode;
+		}
+	}
+	if keys.just_pressed(KeyCode::X) {
+		settings.space = match settings.space {
+			GizmoSpace::World => GizmoSpace::Local,
+			GizmoSpace::Local => GizmoSpace::World,
+		};
+	}
+}
+
+#[allow(clippy::too_many_arguments)]
+fn drag_gizmo(
+	mut gizmo: ResMut<TransformGizmo>,
+	settings: Res<GizmoSettings>,
+	selection: Res<Selection>,
+	buttons: Res<Input<MouseButton>>,
+	keys: Res<Input<KeyCode>>,
+	windows: Query<&Window, With<PrimaryWindow>>,
+	mut contexts: Query<&mut EguiContext, With<PrimaryWindow>>,
+	cameras: Query<
+		(&Camera, &GlobalTransform, Option<&WorldspacePointer>),
+		With<InspectorCamera>,
+	>,
+	globals: Query<(&GlobalTransform, Option<&Parent>)>,
+	mut transforms: Query<&mut Transform>,
+	mut history: ResMut<History>,
+) {
+	if !buttons.pressed(MouseButton::Left) {
+		if let Some(drag) = gizmo.drag.take() {
+			record_drag(drag, settings.mode, &transforms, &mut history);
+		}
+	}
+	let ray = windows.get_single().ok().and_then(|window| {
+		let (camera, camera_transform, _) = cameras.get_single().ok()?;
+		camera.viewport_to_world(camera_transform, window.cursor_position()?)
+	});
+	let Some(ray) = ray else {
+		gizmo.hovered = None;
+		return;
+	};
+
+	if let Some(drag) = &gizmo.drag {
+		let snap = settings.snap
+			!= keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+		if let Some(point) = handle_point(drag.handle, &drag.frame, drag.forward, ray) {
+			apply_drag(drag, point, &settings, snap, &mut transforms);
+		}
+		return;
+	}
+
+	// The mouse is over the UI instead.
+	let over_egui = contexts.get_single_mut().is_ok_and(|mut context| {
+		let ctx = context.get_mut();
+		ctx.is_pointer_over_area() || ctx.is_using_pointer()
+	});
+	let over_panel = cameras.get_single().is_ok_and(|(_, _, pointer)| {
+		pointer.is_some_and(|pointer| pointer.hovered.is_some())
+	});
+	let targets = targets(&selection, &globals);
+	let frame = (!over_egui && !over_panel)
+		.then(|| frame(&settings, &targets, &globals, ray.origin))
+		.flatten();
+	let Some(frame) = frame else {
+		gizmo.hovered = None;
+		return;
+	};
+	gizmo.hovered = hovered_handle(settings.mode, &frame, ray);
+
+	if let Some(handle) = gizmo.hovered {
+		if buttons.just_pressed(MouseButton::Left) {
+			let (right, forward) = cameras
+				.get_single()
+				.map_or((Vec3::X, ray.direction), |(_, camera, _)| {
+					(camera.right(), camera.forward())
+				});
+			let Some(grabbed) = handle_point(handle, &frame, forward, ray) else {
+				return;
+			};
+			let targets: Vec<_> = targets
+				.into_iter()
+				.filter_map(|entity| {
+					let (global, parent) = globals.get(entity).ok()?;
+					let parent = parent
+						.and_then(|parent| globals.get(parent.get()).ok())
+						.map(|(parent, _)| parent.compute_matrix().inverse());
+					Some((entity, global.compute_transform(), parent))
+				})
+				.collect();
+			let before = targets
+				.iter()
+				.filter_map(|&(entity, ..)| {
+					Some((entity, *transforms.get(entity).ok()?))
+				})
+				.collect();
+			gizmo.drag = Some(Drag {
+				handle,
+				frame,
+				grabbed,
+				right,
+				forward,
+				targets,
+				before,
+			});
+		}
+	}
+}
+
+fn draw_gizmo(
+	mut gizmos: Gizmos,
+	gizmo: Res<TransformGizmo>,
+	settings: Res<GizmoSettings>,
+	selection: Res<Selection>,
+	cameras: Query<&GlobalTransform, With<InspectorCamera>>,
+	globals: Query<(&GlobalTransform, Option<&Parent>)>,
+) {
+	let Ok(camera) = cameras.get_single() else {
+		return;
+	};
+	let frame = match &gizmo.drag {
+		// Keep the axes that are being dragged along, but follow the entities.
+		Some(drag) => {
+			let targets: Vec<Entity> =
+				drag.targets.iter().map(|(entity, ..)| *entity).collect();
+			let Some(now) = frame(&settings, &targets, &globals, camera.translation())
+			else {
+				return;
+			};
+			Frame {
+				pivot: now.pivot,
+				..drag.frame
+			}
+		}
+		None => {
+			let targets = targets(&selection, &globals);
+			let Some(frame) =
+				frame(&settings, &targets, &globals, camera.translation())
+			else {
+				return;
+			};
+			frame
+		}
+	};
+	let active = gizmo
+		.drag
+		.as_ref()
+		.map(|drag| drag.handle)
+		.or(gizmo.hovered);
+	let color = |handle: GizmoHandle, color: Color| {
+		if active == Some(handle) {
+			ACTIVE_COLOR
+		} else {
+			color
+		}
+	};
+
+	let Frame {
+		pivot,
+		axes,
+		length,
+	} = frame;
+	let facing = (camera.translation() - pivot).normalize_or_zero();
+	match settings.mode {
+		GizmoMode::Translate => {
+			for (i, axis) in axes.into_iter().enumerate() {
+				let color = color(GizmoHandle::Axis(i), AXIS_COLORS[i]);
+				let tip = pivot + axis * length;
+				gizmos.line(pivot, tip, color);
+				// An arrowhead
+				let base = tip - axis * length * 0.15;
+				let (a, b) = axis.any_orthonormal_pair();
+				for side in [a, -a, b, -b] {
+					gizmos.line(tip, base + side * length * 0.05, color);
+				}
+			}
+			gizmos.circle(
+				pivot,
+				facing,
+				length * GRAB_DISTANCE,
+				color(GizmoHandle::Center, CENTER_COLOR),
+			);
+		}
+		GizmoMode::Rotate => {
+			for (i, axis) in axes.into_iter().enumerate() {
+				gizmos
+					.circle(
+						pivot,
+						axis,
+						length,
+						color(GizmoHandle::Ring(i), AXIS_COLORS[i]),
+					)
+					.segments(64);
+			}
+		}
+		GizmoMode::Scale => {
+			let rotation = Quat::from_mat3(&Mat3::from_cols(axes[0], axes[1], axes[2]));
+			for (i, axis) in axes.into_iter().enumerate() {
+				let color = color(GizmoHandle::Axis(i), AXIS_COLORS[i]);
+				let tip = pivot + axis * length;
+				gizmos.line(pivot, tip, color);
+				gizmos.cuboid(
+					Transform::from_translation(tip)
+						.with_rotation(rotation)
+						.with_scale(Vec3::splat(length * 0.1)),
+					color,
+				);
+			}
+			gizmos.cuboid(
+				Transform::from_translation(pivot)
+					.with_rotation(rotation)
+					.with_scale(Vec3::splat(length * 0.15)),
+				color(GizmoHandle::Center, CENTER_COLOR),
+			);
+		}
+	}
+}
+
+/// Records what a drag did in the [`History`].
+fn record_drag(
+	drag: Drag,
+	mode: GizmoMode,
+	transforms: &Query<&mut Transform>,
+	history: &mut History,
+) {
+	let edits: Vec<Edit> = drag
+		.before
+		.into_iter()
+		.filter_map(|(entity, before)| {
+			let after = *transforms.get(entity).ok()?;
+			(after != before).then(|| Edit::component(entity, before, after))
+		})
+		.collect();
+	let action = match mode {
+		GizmoMode::Translate => "Move",
+		GizmoMode::Rotate => "Rotate",
+		GizmoMode::Scale => "Scale",
+	};
+	let label = match edits.len() {
+		1 => format!("{action} entity"),
+		count => format!("{action} {count} entities"),
+	};
+	history.push(HistoryEntry::new(label, edits));
+}
+
+/// The selected entities that can be moved, leaving out those with a selected
+/// ancestor, which already move along with it.
+fn targets(
+	selection: &Selection,
+	globals: &Query<(&GlobalTransform, Option<&Parent>)>,
+) -> Vec<Entity> {
+	selection
+		.iter()
+		.filter(|&entity| {
+			let Ok((_, mut parent)) = globals.get(entity) else {
+				return false;
+			};
+			while let Some(ancestor) = parent {
+				if selection.contains(ancestor.get()) {
+					return false;
+				}
+				parent = globals
+					.get(ancestor.get())
+					.ok()
+					.and_then(|(_, parent)| parent);
+			}
+			true
+		})
+		.collect()
+}
+
+/// Where the gizmo of `targets` goes, seen from `eye`. The last target is the active
+/// one.
+fn frame(
+	settings: &GizmoSettings,
+	targets: &[Entity],
+	globals: &Query<(&GlobalTransform, Option<&Parent>)>,
+	eye: Vec3,
+) -> Option<Frame> {
+	let transforms: Vec<Transform> = targets
+		.iter()
+		.filter_map(|&entity| globals.get(entity).ok())
+		.map(|(global, _)| global.compute_transform())
+		.collect();
+	let active = transforms.last()?;
+	let pivot = match settings.pivot {
+		GizmoPivot::Median | GizmoPivot::Individual => {
+			transforms
+				.iter()
+				.map(|transform| transform.translation)
+				.sum::<Vec3>()
+				/ transforms.len() as f32
+		}
+		GizmoPivot::Active => active.translation,
+	};
+	let axes = match settings.space {
+		GizmoSpace::World => [Vec3::X, Vec3::Y, Vec3::Z],
+		GizmoSpace::Local => [active.right(), active.up(), active.back()],
+	};
+	let length = (pivot.distance(eye) * settings.size).max(0.01);
+	Some(Frame {
+		pivot,
+		axes,
+		length,
+	})
+}
+
+/// The handle of `mode` that the ray passes closest to, if it's close enough.
+fn hovered_handle(mode: GizmoMode, frame: &Frame, ray: Ray) -> Option<GizmoHandle> {
+	let Frame {
+		pivot,
+		axes,
+		length,
+	} = *frame;
+	let mut handles = Vec::new();
+	match mode {
+		GizmoMode::Translate | GizmoMode::Scale => {
+			let center = ray_point_distance(ray, pivot);
+			// The middle is small, so it wins when the mouse is on it.
+			if center < length * GRAB_DISTANCE * 1.5 {
+				return Some(GizmoHandle::Center);
+			}
+			for (i, axis) in axes.into_iter().enumerate() {
+				let Some((_, along)) = closest_on_line(ray, pivot, axis) else {
+					continue;
+				};
+				let along = along.clamp(length * 0.15, length);
+				handles.push((
+					GizmoHandle::Axis(i),
+					ray_point_distance(ray, pivot + axis * along),
+				));
+			}
+		}
+		GizmoMode::Rotate => {
+			for (i, axis) in axes.into_iter().enumerate() {
+				let Some(distance) = ray.intersect_plane(pivot, axis) else {
+					continue;
+				};
+				let hit = ray.get_point(distance);
+				handles
+					.push((GizmoHandle::Ring(i), (hit.distance(pivot) - length).abs()));
+			}
+		}
+	}
+	handles
+		.into_iter()
+		.filter(|(_, distance)| *distance < length * GRAB_DISTANCE)
+		.min_by(|(_, a), (_, b)| a.total_cmp(b))
+		.map(|(handle, _)| handle)
+}
+
+/// Where the ray is on the handle: the closest point on an axis, or the point on the
+/// plane of a ring, or on the plane facing `forward` for the middle.
+fn handle_point(
+	handle: GizmoHandle,
+	frame: &Frame,
+	forward: Vec3,
+	ray: Ray,
+) -> Option<Vec3> {
+	match handle {
+		GizmoHandle::Axis(i) => {
+			let axis = frame.axes[i];
+			let (_, along) = closest_on_line(ray, frame.pivot, axis)?;
+			Some(frame.pivot + axis * along)
+		}
+		GizmoHandle::Ring(i) => {
+			let distance = ray.intersect_plane(frame.pivot, frame.axes[i])?;
+			Some(ray.get_point(distance))
+		}
+		GizmoHandle::Center => {
+			let distance = ray.intersect_plane(frame.pivot, forward)?;
+			Some(ray.get_point(distance))
+		}
+	}
+}
+
+fn apply_drag(
+	drag: &Drag,
+	point: Vec3,
+	settings: &GizmoSettings,
+	snap: bool,
+	transforms: &mut Query<&mut Transform>,
+) {
+	let Frame {
+		pivot,
+		axes,
+		length,
+	} = drag.frame;
+	let moved = point - drag.grabbed;
+	let step = |value: f32, step: f32| {
+		if snap && step > 0.0 {
+			(value / step).round() * step
+		} else {
+			value
+		}
+	};
+	let around_pivot = settings.pivot != GizmoPivot::Individual;
+
+	for &(entity, start, parent_inverse) in &drag.targets {
+		let mut global = start;
+		match (settings.mode, drag.handle) {
+			(GizmoMode::Translate, GizmoHandle::Axis(i)) => {
+				global.translation +=
+					axes[i] * step(moved.dot(axes[i]), settings.translate_step);
+			}
+			(GizmoMode::Translate, GizmoHandle::Center) => {
+				let step = |value| step(value, settings.translate_step);
+				global.translation +=
+					Vec3::new(step(moved.x), step(moved.y), step(moved.z));
+			}
+			(GizmoMode::Rotate, GizmoHandle::Ring(i)) => {
+				let from = drag.grabbed - pivot;
+				let to = point - pivot;
+				let angle = axes[i].dot(from.cross(to)).atan2(from.dot(to));
+				let angle = step(angle.to_degrees(), settings.rotate_step).to_radians();
+				let rotation = Quat::from_axis_angle(axes[i], angle);
+				global.rotation = rotation * start.rotation;
+				if around_pivot {
+					global.translation = pivot + rotation * (start.translation - pivot);
+				}
+			}
+			(GizmoMode::Scale, GizmoHandle::Axis(i)) => {
+				let axis = axes[i];
+				let factor = scale_factor(moved.dot(axis), length, settings, snap);
+				// Scale along the entity's own axis that is closest to the handle.
+				let own = [start.right(), start.up(), start.back()]
+					.into_iter()
+					.map(|own| own.dot(axis).abs())
+					.enumerate()
+					.max_by(|(_, a), (_, b)| a.total_cmp(b))
+					.map_or(i, |(own, _)| own);
+				global.scale[own] *= factor;
+				if around_pivot {
+					let offset = start.translation - pivot;
+					global.translation += axis * offset.dot(axis) * (factor - 1.0);
+				}
+			}
+			(GizmoMode::Scale, GizmoHandle::Center) => {
+				// Dragging to the right grows.
+				let factor =
+					scale_factor(moved.dot(drag.right), length, settings, snap);
+				global.scale *= factor;
+				if around_pivot {
+					global.translation = pivot + (start.translation - pivot) * factor;
+				}
+			}
+			// The mode changed while dragging.
+			_ => continue,
+		}
+		let Ok(mut transform) = transforms.get_mut(entity) else {
+			continue;
+		};
+		*transform = match parent_inverse {
+			Some(parent_inverse) => {
+				Transform::from_matrix(parent_inverse * global.compute_matrix())
+			}
+			None => global,
+		};
+	}
+}
+
+fn scale_factor(moved: f32, length: f32, settings: &GizmoSettings, snap: bool) -> f32 {
+	let factor = 1.0 + moved / length;
+	let factor = if snap && settings.scale_step > 0.0 {
+		1.0 + ((factor - 1.0) / settings.scale_step).round() * settings.scale_step
+	} else {
+		factor
+	};
+	factor.max(0.01)
+}
+
+/// How far along the line through `origin` along `direction` the ray passes closest
+/// to it, as the distances along the ray and along the line. `None` if they are
+/// parallel, or if the closest point is behind the ray.
+fn closest_on_line(ray: Ray, origin: Vec3, direction: Vec3) -> Option<(f32, f32)> {
+	let w = ray.origin - origin;
+	let b = ray.direction.dot(direction);
+	let d = ray.direction.dot(w);
+	let e = direction.dot(w);
+	let denominator = 1.0 - b * b;
+	if denominator < 1e-6 {
+		return None;
+	}
+	let along_ray = (b * e - d) / denominator;
+	let along_line = (e - b * d) / denominator;
+	(along_ray > 0.0).then_some((along_ray, along_line))
+}
+
+fn ray_point_distance(ray: Ray, point: Vec3) -> f32 {
+	let to_point = point - ray.origin;
+	let along = to_point.dot(ray.direction).max(0.0);
+	to_point.distance(ray.direction * along)
+}
+
+#[cfg(test)]
+mod tests {
+	use bevy::ecs::system::SystemState;
+
+	use super::*;
+
+	fn assert_close(actual: Vec3, expected: Vec3) {
+		assert!(
+			actual.abs_diff_eq(expected, 1e-4),
+			"expected {expected}, got {actual}"
+		);
+	}
+
+	#[test]
+	fn closest_on_line_finds_both_distances() {
+		// Looking down -Z from 5 in front of the line along X, 2 along it.
+		let ray = Ray {
+			origin: Vec3::new(2.0, 0.0, 5.0),
+			direction: Vec3::NEG_Z,
+		};
+		let (along_ray, along_line) =
+			closest_on_line(ray, Vec3::ZERO, Vec3::X).unwrap();
+		assert!((along_ray - 5.0).abs() < 1e-5);
+		assert!((along_line - 2.0).abs() < 1e-5);
+
+		// Skew lines: the ray passes over the line, one above it.
+		let ray = Ray {
+			origin: Vec3::new(-1.0, 1.0, 3.0),
+			direction: Vec3::new(0.0, 0.0, -1.0),
+		};
+		let (along_ray, along_line) =
+			closest_on_line(ray, Vec3::ZERO, Vec3::X).unwrap();
+		assert!((along_ray - 3.0).abs() < 1e-5);
+		assert!((along_line + 1.0).abs() < 1e-5);
+
+		// Parallel, and behind the ray.
+		let parallel = Ray {
+			origin: Vec3::Y,
+			direction: Vec3::X,
+		};
+		assert_eq!(closest_on_line(parallel, Vec3::ZERO, Vec3::X), None);
+		let away = Ray {
+			origin: Vec3::Z,
+			direction: Vec3::Z,
+		};
+		assert_eq!(closest_on_line(away, Vec3::ZERO, Vec3::X), None);
+	}
+
+	#[test]
+	fn scale_factor_snaps_and_stays_positive() {
+		let settings = GizmoSettings {
+			scale_step: 0.25,
+			..default()
+		};
+		// Dragging one gizmo length doubles the size.
+		assert_eq!(scale_factor(2.0, 2.0, &settings, false), 2.0);
+		assert_eq!(scale_factor(0.0, 2.0, &settings, false), 1.0);
+		assert_eq!(scale_factor(0.3, 1.0, &settings, false), 1.3);
+		assert_eq!(scale_factor(0.3, 1.0, &settings, true), 1.25);
+		assert_eq!(scale_factor(0.4, 1.0, &settings, true), 1.5);
+		// Dragging past the middle doesn't flip or flatten it.
+		assert_eq!(scale_factor(-5.0, 1.0, &settings, false), 0.01);
+	}
+
+	#[test]
+	fn frame_follows_the_pivot_and_space() {
+		let mut world = World::new();
+		let a = world.spawn(GlobalTransform::from_xyz(0.0, 0.0, 0.0)).id();
+		let rotation = Quat::from_rotation_y(std::f32::consts::FRAC_PI_2);
+		let b = world
+			.spawn(GlobalTransform::from(
+				Transform::from_xyz(2.0, 0.0, 0.0).with_rotation(rotation),
+			))
+			.id();
+		let mut state =
+			SystemState::<Query<(&GlobalTransform, Option<&Parent>)>>::new(&mut world);
+		let globals = state.get(&world);
+		let eye = Vec3::new(1.0, 0.0, 10.0);
+
+		let settings = GizmoSettings::default();
+		let median = frame(&settings, &[a, b], &globals, eye).unwrap();
+		assert_close(median.pivot, Vec3::X);
+		assert_eq!(median.axes, [Vec3::X, Vec3::Y, Vec3::Z]);
+		// The same size on screen, however far away it is.
+		assert!((median.length - 10.0 * settings.size).abs() < 1e-5);
+
+		let settings = GizmoSettings {
+			pivot: GizmoPivot::Active,
+			space: GizmoSpace::Local,
+			..default()
+		};
+		let active = frame(&settings, &[a, b], &globals, eye).unwrap();
+		assert_close(active.pivot, Vec3::new(2.0, 0.0, 0.0));
+		assert_close(active.axes[0], rotation * Vec3::X);
+		assert_close(active.axes[1], Vec3::Y);
+		assert_close(active.axes[2], rotation * Vec3::Z);
+
+		assert!(frame(&settings, &[], &globals, eye).is_none());
+	}
+
+	#[test]
+	fn middle_moves_on_a_fixed_plane() {
+		let frame = Frame {
+			pivot: Vec3::ZERO,
+			axes: [Vec3::X, Vec3::Y, Vec3::Z],
+			length: 1.0,
+		};
+		let eye = Vec3::new(0.0, 0.0, 5.0);
+		// The mouse moves to the side, so the ray turns, but the point stays on the
+		// plane through the pivot facing the camera, rather than drifting away.
+		let ray = Ray {
+			origin: eye,
+			direction: (Vec3::new(3.0, 0.0, 0.0) - eye).normalize(),
+		};
+		let point =
+			handle_point(GizmoHandle::Center, &frame, Vec3::NEG_Z, ray).unwrap();
+		assert_close(point, Vec3::new(3.0, 0.0, 0.0));
+	}
+}
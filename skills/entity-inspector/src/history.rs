@@ -0,0 +1,1023 @@
+//! Undoing and redoing what was done with the inspector.
+
+use std::any::TypeId;
+use std::time::{Duration, Instant};
+
+use bevy::asset::{ReflectAsset, ReflectHandle, UntypedAssetId};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy::window::PrimaryWindow;
+use bevy_egui::{egui, EguiContext};
+use bevy_inspector_egui::bevy_inspector::guess_entity_name;
+
+use crate::scene::is_reflected;
+use crate::Selection;
+
+/// Edits of the same things less than this apart are undone together, like the
+/// steps of dragging a value.
+const MERGE_TIME: Duration = Duration::from_millis(500);
+
+/// What the inspector did, so that it can be undone and redone. The inspector records
+/// edits of reflected components and of the assets that they point to, entities that
+/// it spawns and despawns, and entities that it moves to another parent.
+///
+/// Despawned entities come back as new entities, with only their reflected
+/// components. Edits recorded before then follow them.
+#[derive(Resource)]
+pub struct History {
+	done: Vec<HistoryEntry>,
+	undone: Vec<HistoryEntry>,
+	/// How many entries are kept. Older ones can't be undone anymore.
+	pub max_len: usize,
+}
+
+impl Default for History {
+	fn default() -> Self {
+		Self {
+			done: Vec::new(),
+			undone: Vec::new(),
+			max_len: 100,
+		}
+	}
+}
+
+/// What one action did.
+pub struct HistoryEntry {
+	pub label: String,
+	edits: Vec<Edit>,
+	/// When it was last changed, for merging.
+	at: Instant,
+	merges: bool,
+}
+
+impl HistoryEntry {
+	pub fn new(label: impl Into<String>, edits: Vec<Edit>) -> Self {
+		Self {
+			label: label.into(),
+			edits,
+			at: Instant::now(),
+			merges: false,
+		}
+	}
+
+	/// Makes edits of the same things, recorded soon after, part of this entry.
+	fn merging(mut self) -> Self {
+		self.merges = true;
+		self
+	}
+
+	fn targets(&self) -> Vec<Target> {
+		self.edits.iter().filter_map(Edit::target).collect()
+	}
+
+	fn remap(&mut self, map: &HashMap<Entity, Entity>) {
+		for edit in &mut self.edits {
+			edit.remap(map);
+		}
+	}
+}
+
+/// One change to the world.
+pub enum Edit {
+	/// A reflected component changed value.
+	Component {
+		entity: Entity,
+		type_id: TypeId,
+		before: Box<dyn Reflect>,
+		after: Box<dyn Reflect>,
+	},
+	/// A reflected asset changed value.
+	Asset {
+		handle: UntypedHandle,
+		before: Box<dyn Reflect>,
+		after: Box<dyn Reflect>,
+	},
+	/// Entities were spawned, with their descendants. Once undone, they are kept in
+	/// `despawned` until they are spawned again.
+	Spawn {
+		roots: Vec<Entity>,
+		despawned: Option<Despawned>,
+	},
+	/// Entities were despawned, with their descendants.
+	Despawn {
+		roots: Vec<Entity>,
+		despawned: Option<Despawned>,
+	},
+	/// An entity moved to another parent.
+	Reparent {
+		entity: Entity,
+		from: Option<Entity>,
+		to: Option<Entity>,
+	},
+}
+
+/// What an edit changed, to tell whether two edits can be merged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+	Component(Entity, TypeId),
+	Asset(UntypedAssetId),
+}
+
+/// Despawned entities, which can be spawned back.
+pub struct Despawned {
+	scene: DynamicScene,
+	/// The entities that were despawned, which are the scene's roots, and their
+	/// parents.
+	roots: Vec<(Entity, Option<Entity>)>,
+}
+
+impl Edit {
+	/// A component edit, with the value that the component has now as `after`.
+	pub fn component<C: Component + Reflect>(
+		entity: Entity,
+		before: C,
+		after: C,
+	) -> Self {
+		Self::Component {
+			entity,
+			type_id: TypeId::of::<C>(),
+			before: Box::new(before),
+			after: Box::new(after),
+		}
+	}
+
+	fn target(&self) -> Option<Target> {
+		match self {
+			Self::Component {
+				entity, type_id, ..
+			} => Some(Target::Component(*entity, *type_id)),
+			Self::Asset { handle, .. } => Some(Target::Asset(handle.id())),
+			_ => None,
+		}
+	}
+
+	fn undo(&mut self, world: &mut World, map: &mut HashMap<Entity, Entity>) {
+		match self {
+			Self::Component {
+				entity,
+				type_id,
+				before,
+				..
+			} => set_component(world, *entity, *type_id, &**before),
+			Self::Asset { handle, before, .. } => set_asset(world, handle, &**before),
+			Self::Spawn { roots, despawned } => {
+				*despawned = Some(despawn(world, roots))
+			}
+			Self::Despawn { roots, despawned } => {
+				if let Some(despawned) = despawned.take() {
+					*roots = respawn(world, despawned, map);
+				}
+			}
+			Self::Reparent { entity, from, .. } => set_parent(world, *entity, *from),
+		}
+	}
+
+	fn redo(&mut self, world: &mut World, map: &mut HashMap<Entity, Entity>) {
+		match self {
+			Self::Component {
+				entity,
+				type_id,
+				after,
+				..
+			} => set_component(world, *entity, *type_id, &**after),
+			Self::Asset { handle, after, .. } => set_asset(world, handle, &**after),
+			Self::Spawn { roots, despawned } => {
+				if let Some(despawned) = despawned.take() {
+					*roots = respawn(world, despawned, map);
+				}
+			}
+			Self::Despawn { roots, despawned } => {
+				*despawned = Some(despawn(world, roots))
+			}
+			Self::Reparent { entity, to, .. } => set_parent(world, *entity, *to),
+		}
+	}
+
+	/// Follows entities that were spawned again as new entities.
+	fn remap(&mut self, map: &HashMap<Entity, Entity>) {
+		let remap = |entity: &mut Entity| {
+			if let Some(&new) = map.get(entity) {
+				*entity = new;
+			}
+		};
+		match self {
+			Self::Component { entity, .. } => remap(entity),
+			Self::Asset { .. } => {}
+			Self::Spawn { roots, despawned } | Self::Despawn { roots, despawned } => {
+				roots.iter_mut().for_each(remap);
+				// The despawned entities themselves can't come back another way,
+				// but their parents can.
+				for (_, parent) in despawned.iter_mut().flat_map(|d| &mut d.roots) {
+					parent.as_mut().map(remap);
+				}
+			}
+			Self::Reparent { entity, from, to } => {
+				remap(entity);
+				from.as_mut().map(remap);
+				to.as_mut().map(remap);
+			}
+		}
+	}
+}
+
+impl History {
+	/// What can be undone, oldest first.
+	pub fn done(&self) -> &[HistoryEntry] {
+		&self.done
+	}
+
+	/// What can be redone, next first.
+	pub fn undone(&self) -> impl Iterator<Item = &HistoryEntry> {
+		self.undone.iter().rev()
+	}
+
+	pub fn clear(&mut self) {
+		self.done.clear();
+		self.undone.clear();
+	}
+
+	/// Records an action, which can't be redone anymore after it is undone and
+	/// something else is done.
+	pub fn push(&mut self, entry: HistoryEntry) {
+		if entry.edits.is_empty() {
+			return;
+		}
+		self.undone.clear();
+		if let Some(last) = self.done.last_mut() {
+			let recent = entry.at.duration_since(last.at) < MERGE_TIME;
+			if entry.merges
+				&& last.merges
+				&& recent && last.targets() == entry.targets()
+			{
+				for (last, edit) in last.edits.iter_mut().zip(entry.edits) {
+					match (last, edit) {
+						(
+							Edit::Component { after, .. },
+							Edit::Component { after: new, .. },
+						)
+						| (Edit::Asset { after, .. }, Edit::Asset { after: new, .. }) => {
+							*after = new;
+						}
+						_ => unreachable!("edits with the same targets"),
+					}
+				}
+				last.at = entry.at;
+				return;
+			}
+		}
+		self.done.push(entry);
+		let extra = self.done.len().saturating_sub(self.max_len);
+		self.done.drain(..extra);
+	}
+
+	fn remap(&mut self, map: &HashMap<Entity, Entity>) {
+		if map.is_empty() {
+			return;
+		}
+		for entry in self.done.iter_mut().chain(&mut self.undone) {
+			entry.remap(map);
+		}
+	}
+}
+
+/// Undoes the last action, returning whether there was one.
+pub fn undo(world: &mut World) -> bool {
+	let Some(mut entry) = world.resource_mut::<History>().done.pop() else {
+		return false;
+	};
+	let mut map = HashMap::default();
+	for edit in entry.edits.iter_mut().rev() {
+		edit.undo(world, &mut map);
+	}
+	finish(world, entry, &map, |history| &mut history.undone);
+	true
+}
+
+/// Redoes the last action that was undone, returning whether there was one.
+pub fn redo(world: &mut World) -> bool {
+	let Some(mut entry) = world.resource_mut::<History>().undone.pop() else {
+		return false;
+	};
+	let mut map = HashMap::default();
+	for edit in &mut entry.edits {
+		edit.redo(world, &mut map);
+	}
+	finish(world, entry, &map, |history| &mut history.done);
+	true
+}
+
+fn finish(
+	world: &mut World,
+	mut entry: HistoryEntry,
+	map: &HashMap<Entity, Entity>,
+	stack: impl FnOnce(&mut History) -> &mut Vec<HistoryEntry>,
+) {
+	entry.remap(map);
+	// Undoing then redoing right away shouldn't merge with what comes next.
+	entry.merges = false;
+	let mut history = world.resource_mut::<History>();
+	history.remap(map);
+	stack(&mut history).push(entry);
+
+	let alive: Vec<Entity> = world
+		.resource::<Selection>()
+		.iter()
+		.map(|entity| map.get(&entity).copied().unwrap_or(entity))
+		.filter(|&entity| world.get_entity(entity).is_some())
+		.collect();
+	let mut selection = world.resource_mut::<Selection>();
+	if alive != selection.as_slice() {
+		selection.clear();
+		for entity in alive {
+			selection.select_maybe_add(entity, true);
+		}
+	}
+}
+
+fn set_component(
+	world: &mut World,
+	entity: Entity,
+	type_id: TypeId,
+	value: &dyn Reflect,
+) {
+	let registry = world.resource::<AppTypeRegistry>().clone();
+	let registry = registry.read();
+	let Some(reflect) = registry
+		.get(type_id)
+		.and_then(|registration| registration.data::<ReflectComponent>())
+	else {
+		return;
+	};
+	match world.get_entity_mut(entity) {
+		Some(mut entity) => reflect.insert(&mut entity, value),
+		None => warn!(
+			"Can't change {}: {entity:?} is gone",
+			value.reflect_short_type_path()
+		),
+	}
+}
+
+fn set_asset(world: &mut World, handle: &UntypedHandle, value: &dyn Reflect) {
+	let registry = world.resource::<AppTypeRegistry>().clone();
+	let registry = registry.read();
+	if let Some(reflect) = registry
+		.get(handle.type_id())
+		.and_then(|registration| registration.data::<ReflectAsset>())
+	{
+		reflect.insert(world, handle.clone(), value);
+	}
+}
+
+fn set_parent(world: &mut World, entity: Entity, parent: Option<Entity>) {
+	let parent = parent.filter(|&parent| world.get_entity(parent).is_some());
+	let Some(mut entity) = world.get_entity_mut(entity) else {
+		return;
+	};
+	match parent {
+		Some(parent) => entity.set_parent(parent),
+		None => entity.remove_parent(),
+	};
+}
+
+/// Despawns `roots` and their descendants, keeping their reflected components.
+fn despawn(world: &mut World, roots: &[Entity]) -> Despawned {
+	let mut entities = Vec::new();
+	let mut stack = roots.to_vec();
+	while let Some(entity) = stack.pop() {
+		if entities.contains(&entity) || world.get_entity(entity).is_none() {
+			continue;
+		}
+		entities.push(entity);
+		if let Some(children) = world.get::<Children>(entity) {
+			stack.extend(children.iter().copied());
+		}
+	}
+
+	{
+		let registry = world.resource::<AppTypeRegistry>().read();
+		let mut lost: Vec<&str> = entities
+			.iter()
+			.flat_map(|&entity| world.inspect_entity(entity))
+			.filter(|info| !is_reflected(&registry, info))
+			.map(|info| info.name())
+			.collect();
+		lost.sort_unstable();
+		lost.dedup();
+		if !lost.is_empty() {
+			warn!(
+				"Despawning components that undo can't bring back: {}",
+				lost.join(", ")
+			);
+		}
+	}
+
+	let mut scene = DynamicSceneBuilder::from_world(world)
+		.extract_entities(entities.iter().copied())
+		.build();
+	for dynamic_entity in &mut scene.entities {
+		if roots.contains(&dynamic_entity.entity) {
+			dynamic_entity.components.retain(|component| {
+				component
+					.get_represented_type_info()
+					.map(|info| info.type_id())
+					!= Some(TypeId::of::<Parent>())
+			});
+		}
+	}
+	let roots = roots
+		.iter()
+		.filter(|&&root| world.get_entity(root).is_some())
+		.map(|&root| (root, world.get::<Parent>(root).map(|parent| parent.get())))
+		.collect();
+	for &entity in &entities {
+		if let Some(entity) = world.get_entity_mut(entity) {
+			entity.despawn_recursive();
+		}
+	}
+	Despawned { scene, roots }
+}
+
+/// Spawns despawned entities again, returning the new roots. `map` gets the new
+/// entity of each old one.
+fn respawn(
+	world: &mut World,
+	despawned: Despawned,
+	map: &mut HashMap<Entity, Entity>,
+) -> Vec<Entity> {
+	let registry = world.resource::<AppTypeRegistry>().clone();
+	if let Err(err) = despawned.scene.write_to_world_with(world, map, &registry) {
+		warn!("Failed to spawn entities back: {err}");
+	}
+	despawned
+		.roots
+		.into_iter()
+		.filter_map(|(root, parent)| {
+			let root = *map.get(&root)?;
+			let parent =
+				parent.map(|parent| map.get(&parent).copied().unwrap_or(parent));
+			set_parent(world, root, parent);
+			Some(root)
+		})
+		.collect()
+}
+
+/// The reflected components of some entities, and the assets that they point to, to
+/// find out what changed later.
+pub(crate) struct Watched {
+	components: Vec<(Entity, TypeId, Box<dyn Reflect>)>,
+	assets: Vec<(UntypedHandle, Box<dyn Reflect>)>,
+}
+
+impl Watched {
+	pub(crate) fn new(world: &World, entities: &[Entity]) -> Self {
+		let registry = world.resource::<AppTypeRegistry>().read();
+		let mut watched = Self {
+			components: Vec::new(),
+			assets: Vec::new(),
+		};
+		for &entity in entities {
+			let Some(entity_ref) = world.get_entity(entity) else {
+				continue;
+			};
+			for info in world.inspect_entity(entity) {
+				let Some(registration) = info.type_id().and_then(|id| registry.get(id))
+				else {
+					continue;
+				};
+				let Some(component) = registration
+					.data::<ReflectComponent>()
+					.and_then(|reflect| reflect.reflect(entity_ref))
+				else {
+					continue;
+				};
+				watched.components.push((
+					entity,
+					registration.type_id(),
+					component.clone_value(),
+				));
+
+				let Some(handle) =
+					registration.data::<ReflectHandle>().and_then(|reflect| {
+						reflect.downcast_handle_untyped(component.as_any())
+					})
+				else {
+					continue;
+				};
+				if watched.assets.iter().any(|(watched, _)| watched == &handle) {
+					continue;
+				}
+				let asset = registry
+					.get(handle.type_id())
+					.and_then(|registration| registration.data::<ReflectAsset>())
+					.and_then(|reflect| reflect.get(world, handle.clone()));
+				if let Some(asset) = asset {
+					watched.assets.push((handle, asset.clone_value()));
+				}
+			}
+		}
+		watched
+	}
+
+	/// What changed since the entities were watched.
+	pub(crate) fn changes(self, world: &World) -> Vec<Edit> {
+		let registry = world.resource::<AppTypeRegistry>().read();
+		let mut edits = Vec::new();
+		for (entity, type_id, before) in self.components {
+			let after = world.get_entity(entity).and_then(|entity| {
+				registry
+					.get_type_data::<ReflectComponent>(type_id)?
+					.reflect(entity)
+			});
+			let Some(after) = after.map(|after| after.clone_value()) else {
+				continue;
+			};
+			if differs(&*before, &*after) {
+				edits.push(Edit::Component {
+					entity,
+					type_id,
+					before,
+					after,
+				});
+			}
+		}
+		for (handle, before) in self.assets {
+			let after = registry
+				.get_type_data::<ReflectAsset>(handle.type_id())
+				.and_then(|reflect| reflect.get(world, handle.clone()));
+			let Some(after) = after.map(|after| after.clone_value()) else {
+				continue;
+			};
+			if differs(&*before, &*after) {
+				edits.push(Edit::Asset {
+					handle,
+					before,
+					after,
+				});
+			}
+		}
+		edits
+	}
+}
+
+/// Compares two values cloned with [`Reflect::clone_value`], so that they are the same
+/// kind of value, like two `DynamicStruct`s, rather than one of them being concrete.
+fn differs(a: &dyn Reflect, b: &dyn Reflect) -> bool {
+	match a.reflect_partial_eq(b) {
+		Some(equal) => !equal,
+		// Some types can't be compared, but most print all of their fields.
+		None => format!("{a:?}") != format!("{b:?}"),
+	}
+}
+
+/// Records what the inspector changed on `entities`, comparing with when they were
+/// `watched`.
+pub(crate) fn record_changes(world: &mut World, entities: &[Entity], watched: Watched) {
+	let edits = watched.changes(world);
+	let Some(first) = edits.first() else {
+		return;
+	};
+	let label = match first {
+		Edit::Component { type_id, .. } => {
+			let registry = world.resource::<AppTypeRegistry>().read();
+			let component = registry
+				.get_type_info(*type_id)
+				.map_or("component", |info| info.type_path_table().short_path())
+				.to_owned();
+			match entities {
+				&[entity] => {
+					format!("Edit {component} of {}", guess_entity_name(world, entity))
+				}
+				entities => format!("Edit {component} of {} entities", entities.len()),
+			}
+		}
+		Edit::Asset { handle, .. } => {
+			let registry = world.resource::<AppTypeRegistry>().read();
+			let asset = registry
+				.get_type_info(handle.type_id())
+				.map_or("asset", |info| info.type_path_table().short_path());
+			format!("Edit {asset}")
+		}
+		_ => "Edit".to_owned(),
+	};
+	world
+		.resource_mut::<History>()
+		.push(HistoryEntry::new(label, edits).merging());
+}
+
+/// Spawns an empty entity as a child of the active entity, or as a root.
+pub fn spawn_entity(world: &mut World) -> Entity {
+	let parent = world.resource::<Selection>().as_slice().last().copied();
+	let mut entity = world.spawn((SpatialBundle::default(), Name::new("Entity")));
+	if let Some(parent) = parent {
+		entity.set_parent(parent);
+	}
+	let entity = entity.id();
+	world.resource_mut::<History>().push(HistoryEntry::new(
+		"Spawn entity",
+		vec![Edit::Spawn {
+			roots: vec![entity],
+			despawned: None,
+		}],
+	));
+	entity
+}
+
+/// Despawns the selected entities and their descendants.
+pub fn despawn_selected(world: &mut World) {
+	let selection = world.resource::<Selection>().clone();
+	// Descendants of selected entities go along with them anyway.
+	let roots: Vec<Entity> = selection
+		.iter()
+		.filter(|&entity| {
+			let mut parent = world.get::<Parent>(entity);
+			while let Some(ancestor) = parent {
+				if selection.contains(ancestor.get()) {
+					return false;
+				}
+				parent = world.get::<Parent>(ancestor.get());
+			}
+			true
+		})
+		.collect();
+	if roots.is_empty() {
+		return;
+	}
+	let label = match roots.as_slice() {
+		&[root] => format!("Despawn {}", guess_entity_name(world, root)),
+		roots => format!("Despawn {} entities", roots.len()),
+	};
+	let despawned = despawn(world, &roots);
+	world.resource_mut::<Selection>().clear();
+	world.resource_mut::<History>().push(HistoryEntry::new(
+		label,
+		vec![Edit::Despawn {
+			roots,
+			despawned: Some(despawned),
+		}],
+	));
+}
+
+/// Moves the selected entities into the active entity, the last one selected,
+/// keeping where they are in the world.
+pub fn parent_selected(world: &mut World) {
+	let selection = world.resource::<Selection>().as_slice().to_vec();
+	let Some((&parent, children)) = selection.split_last() else {
+		return;
+	};
+	let edits = children
+		.iter()
+		.flat_map(|&child| reparent(world, child, Some(parent)))
+		.collect();
+	let label = format!("Parent to {}", guess_entity_name(world, parent));
+	world
+		.resource_mut::<History>()
+		.push(HistoryEntry::new(label, edits));
+}
+
+/// Makes the selected entities roots, keeping where they are in the world.
+pub fn unparent_selected(world: &mut World) {
+	let selection = world.resource::<Selection>().as_slice().to_vec();
+	let edits = selection
+		.iter()
+		.flat_map(|&entity| reparent(world, entity, None))
+		.collect();
+	world
+		.resource_mut::<History>()
+		.push(HistoryEntry::new("Unparent", edits));
+}
+
+/// Moves `entity` into `parent`, or makes it a root, keeping where it is in the
+/// world. Does nothing if `parent` is inside `entity`.
+fn reparent(world: &mut World, entity: Entity, parent: Option<Entity>) -> Vec<Edit> {
+	let from = world.get::<Parent>(entity).map(|parent| parent.get());
+	if from == parent || world.get_entity(entity).is_none() {
+		return Vec::new();
+	}
+	let mut ancestor = parent;
+	while let Some(current) = ancestor {
+		if current == entity {
+			return Vec::new();
+		}
+		ancestor = world.get::<Parent>(current).map(|parent| parent.get());
+	}
+
+	let global = world.get::<GlobalTransform>(entity).copied();
+	let before = world.get::<Transform>(entity).copied();
+	set_parent(world, entity, parent);
+	let mut edits = vec![Edit::Reparent {
+		entity,
+		from,
+		to: parent,
+	}];
+	if let (Some(global), Some(before)) = (global, before) {
+		let parent_global = parent
+			.and_then(|parent| world.get::<GlobalTransform>(parent))
+			.copied()
+			.unwrap_or_default();
+		let after = global.reparented_to(&parent_global);
+		world.entity_mut(entity).insert(after);
+		edits.push(Edit::component(entity, before, after));
+	}
+	edits
+}
+
+/// Undoes with control+Z, and redoes with control+Y or control+shift+Z, unless egui
+/// is using the keyboard.
+pub(crate) fn history_shortcuts(world: &mut World) {
+	let typing = world
+		.query_filtered::<&mut EguiContext, With<PrimaryWindow>>()
+		.get_single_mut(world)
+		.is_ok_and(|mut context| context.get_mut().wants_keyboard_input());
+	let Some(keys) = world.get_resource::<Input<KeyCode>>() else {
+		return;
+	};
+	if typing || !keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+		return;
+	}
+	let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+	if keys.just_pressed(KeyCode::Y) || (shift && keys.just_pressed(KeyCode::Z)) {
+		redo(world);
+	} else if keys.just_pressed(KeyCode::Z) {
+		undo(world);
+	}
+}
+
+/// Draws buttons that spawn, despawn and reparent entities.
+pub fn edit_ui(world: &mut World, ui: &mut egui::Ui) {
+	let selected = world.resource::<Selection>().len();
+	ui.horizontal(|ui| {
+		if ui
+			.button("Spawn")
+			.on_hover_text("Spawns an empty entity in the active one")
+			.clicked()
+		{
+			let entity = spawn_entity(world);
+			world.resource_mut::<Selection>().select_replace(entity);
+		}
+		if ui
+			.add_enabled(selected > 0, egui::Button::new("Despawn"))
+			.clicked()
+		{
+			despawn_selected(world);
+		}
+		if ui
+			.add_enabled(selected > 1, egui::Button::new("Parent"))
+			.on_hover_text("Moves the selected entities into the last one selected")
+			.clicked()
+		{
+			parent_selected(world);
+		}
+		if ui
+			.add_enabled(selected > 0, egui::Button::new("Unparent"))
+			.clicked()
+		{
+			unparent_selected(world);
+		}
+	});
+}
+
+/// Draws what can be undone and redone. Clicking an entry undoes or redoes up to it.
+pub fn history_ui(world: &mut World, ui: &mut egui::Ui) {
+	let history = world.resource::<History>();
+	let done: Vec<String> = history
+		.done()
+		.iter()
+		.map(|entry| entry.label.clone())
+		.collect();
+	let undone: Vec<String> =
+		history.undone().map(|entry| entry.label.clone()).collect();
+	let mut undos = 0;
+	let mut redos = 0;
+	ui.horizontal(|ui| {
+		if ui
+			.add_enabled(!done.is_empty(), egui::Button::new("Undo"))
+			.clicked()
+		{
+			undos = 1;
+		}
+		if ui
+			.add_enabled(!undone.is_empty(), egui::Button::new("Redo"))
+			.clicked()
+		{
+			redos = 1;
+		}
+	});
+	if done.is_empty() && undone.is_empty() {
+		ui.weak("Nothing to undo");
+	}
+	for (i, label) in done.iter().enumerate() {
+		let current = i + 1 == done.len();
+		if ui.selectable_label(current, label).clicked() {
+			undos = done.len() - 1 - i;
+		}
+	}
+	for (i, label) in undone.iter().enumerate() {
+		let label = egui::RichText::new(label).weak();
+		if ui.selectable_label(false, label).clicked() {
+			redos = i + 1;
+		}
+	}
+	for _ in 0..undos {
+		undo(world);
+	}
+	for _ in 0..redos {
+		redo(world);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[derive(Component, Reflect, Clone, Default, Debug, PartialEq)]
+	#[reflect(Component)]
+	struct Health(f32);
+
+	/// Reflected, but can't be compared without printing it.
+	#[derive(Component, Reflect, Default)]
+	#[reflect(Component)]
+	struct Cached(Opaque);
+
+	#[derive(Reflect, Clone, Default, Debug)]
+	#[reflect_value(Debug)]
+	struct Opaque;
+
+	fn world() -> World {
+		let mut app = App::new();
+		app.add_plugins((MinimalPlugins, TransformPlugin, HierarchyPlugin))
+			.register_type::<Health>()
+			.register_type::<Cached>()
+			.init_resource::<Selection>()
+			.init_resource::<History>();
+		std::mem::take(&mut app.world)
+	}
+
+	fn spawn(world: &mut World, name: &str, translation: Vec3) -> Entity {
+		let transform = Transform::from_translation(translation);
+		world
+			.spawn((
+				Name::new(name.to_owned()),
+				transform,
+				GlobalTransform::from(transform),
+			))
+			.id()
+	}
+
+	fn select(world: &mut World, entities: &[Entity]) {
+		let mut selection = world.resource_mut::<Selection>();
+		selection.clear();
+		for &entity in entities {
+			selection.select_maybe_add(entity, true);
+		}
+	}
+
+	/// Sets the health of `entity` the way the inspector would, recording it.
+	fn edit_health(world: &mut World, entity: Entity, health: f32) {
+		let watched = Watched::new(world, &[entity]);
+		world.get_mut::<Health>(entity).unwrap().0 = health;
+		record_changes(world, &[entity], watched);
+	}
+
+	fn health(world: &World, entity: Entity) -> f32 {
+		world.get::<Health>(entity).unwrap().0
+	}
+
+	fn labels(world: &World) -> Vec<&str> {
+		let history = world.resource::<History>();
+		history
+			.done()
+			.iter()
+			.map(|entry| entry.label.as_str())
+			.collect()
+	}
+
+	#[test]
+	fn records_nothing_when_nothing_changed() {
+		let mut world = world();
+		let entity = spawn(&mut world, "thing", Vec3::ZERO);
+		world
+			.entity_mut(entity)
+			.insert((Health(1.0), Cached(Opaque)));
+		edit_health(&mut world, entity, 2.0);
+		assert!(undo(&mut world));
+
+		// Nothing changes while the inspector is shown, which keeps the redo.
+		let watched = Watched::new(&world, &[entity]);
+		assert!(watched.changes(&world).is_empty());
+		let watched = Watched::new(&world, &[entity]);
+		record_changes(&mut world, &[entity], watched);
+		assert!(redo(&mut world));
+		assert_eq!(health(&world, entity), 2.0);
+	}
+
+	#[test]
+	fn undoes_and_redoes_edits() {
+		let mut world = world();
+		let entity = spawn(&mut world, "thing", Vec3::ZERO);
+		world.entity_mut(entity).insert(Health(1.0));
+		edit_health(&mut world, entity, 2.0);
+		assert_eq!(labels(&world), ["Edit Health of thing"]);
+
+		assert!(undo(&mut world));
+		assert_eq!(health(&world, entity), 1.0);
+		assert!(!undo(&mut world));
+		assert!(redo(&mut world));
+		assert_eq!(health(&world, entity), 2.0);
+		assert!(!redo(&mut world));
+	}
+
+	#[test]
+	fn merges_edits_close_together() {
+		let mut world = world();
+		let entity = spawn(&mut world, "thing", Vec3::ZERO);
+		world.entity_mut(entity).insert(Health(1.0));
+		edit_health(&mut world, entity, 2.0);
+		edit_health(&mut world, entity, 3.0);
+		assert_eq!(labels(&world).len(), 1);
+
+		// Long enough after the last one, it is another action.
+		world.resource_mut::<History>().done[0].at -= MERGE_TIME;
+		edit_health(&mut world, entity, 4.0);
+		assert_eq!(labels(&world).len(), 2);
+
+		assert!(undo(&mut world));
+		assert_eq!(health(&world, entity), 3.0);
+		assert!(undo(&mut world));
+		assert_eq!(health(&world, entity), 1.0);
+	}
+
+	#[test]
+	fn edits_follow_despawned_entities_back() {
+		let mut world = world();
+		let parent = spawn(&mut world, "parent", Vec3::ZERO);
+		let child = spawn(&mut world, "child", Vec3::X);
+		world
+			.entity_mut(child)
+			.insert(Health(1.0))
+			.set_parent(parent);
+		edit_health(&mut world, child, 2.0);
+		select(&mut world, &[parent, child]);
+		despawn_selected(&mut world);
+		assert_eq!(labels(&world)[1], "Despawn parent");
+		assert!(world.get_entity(parent).is_none());
+		assert!(world.get_entity(child).is_none());
+
+		assert!(undo(&mut world));
+		let (child, health) = world.query::<(Entity, &Health)>().single(&world);
+		assert_eq!(health.0, 2.0);
+		let parent = world.get::<Parent>(child).unwrap().get();
+		assert_eq!(world.get::<Name>(parent).unwrap().as_str(), "parent");
+		assert!(world.get::<Parent>(parent).is_none());
+
+		// The edit made before despawning applies to the new entity.
+		assert!(undo(&mut world));
+		assert_eq!(self::health(&world, child), 1.0);
+		assert!(redo(&mut world));
+		assert_eq!(self::health(&world, child), 2.0);
+		assert!(redo(&mut world));
+		assert!(world.get_entity(child).is_none());
+		assert!(world.query::<&Health>().iter(&world).next().is_none());
+	}
+
+	#[test]
+	fn reparents_in_place() {
+		let mut world = world();
+		let child = spawn(&mut world, "child", Vec3::X);
+		let parent = spawn(&mut world, "parent", Vec3::Y * 2.0);
+		select(&mut world, &[child, parent]);
+		parent_selected(&mut world);
+		assert_eq!(labels(&world), ["Parent to parent"]);
+		assert_eq!(world.get::<Parent>(child).unwrap().get(), parent);
+		let translation = world.get::<Transform>(child).unwrap().translation;
+		assert!(translation.abs_diff_eq(Vec3::new(1.0, -2.0, 0.0), 1e-5));
+
+		// Moving it into its own child does nothing.
+		select(&mut world, &[parent, child]);
+		parent_selected(&mut world);
+		assert_eq!(labels(&world).len(), 1);
+
+		assert!(undo(&mut world));
+		assert!(world.get::<Parent>(child).is_none());
+		assert_eq!(world.get::<Transform>(child).unwrap().translation, Vec3::X);
+		assert!(world.get::<Children>(parent).is_none());
+		assert!(redo(&mut world));
+		assert_eq!(world.get::<Parent>(child).unwrap().get(), parent);
+	}
+
+	#[test]
+	fn keeps_max_len_entries() {
+		let mut world = world();
+		let entity = spawn(&mut world, "thing", Vec3::ZERO);
+		world.entity_mut(entity).insert(Health(0.0));
+		let mut history = world.resource_mut::<History>();
+		history.max_len = 3;
+		for i in 0..5 {
+			let i = i as f32;
+			history.push(HistoryEntry::new(
+				format!("{i}"),
+				vec![Edit::component(entity, Health(i), Health(i + 1.0))],
+			));
+		}
+		assert_eq!(labels(&world), ["2", "3", "4"]);
+	}
+}
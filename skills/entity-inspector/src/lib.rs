@@ -0,0 +1,96 @@
+//! An entity inspector, built on `bevy-inspector-egui`: a hierarchy of the entities,
+//! and the components of the selected ones, which can be edited live.
+//!
+//! The [`Selection`] is shared by every view of the inspector:
+//!
+//! - [`screen`] shows it as side panels on the primary window.
+//! - [`panel`] shows it on a [`WorldspaceUi`](worldspace_ui::WorldspaceUi) panel that
+//!   pointers can use and grab, for debugging from inside a headset.
+//! - [`xr`] gives VR controllers laser pointers that use and grab those panels.
+//!
+//! What is done with the inspector can be undone and redone, with [`history`].
+//! Both views can save entities to a scene file and load them back, with [`scene`].
+//! In an [`InspectorCamera`]'s viewport, [`picking`] selects entities by clicking on
+//! them, and [`gizmo`] moves the selected ones.
+
+pub mod gizmo;
+pub mod history;
+pub mod panel;
+pub mod picking;
+pub mod scene;
+pub mod screen;
+pub mod xr;
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use bevy_inspector_egui::bevy_inspector;
+use bevy_inspector_egui::bevy_inspector::hierarchy::SelectedEntities;
+use bevy_inspector_egui::DefaultInspectorConfigPlugin;
+
+use crate::history::{history_shortcuts, record_changes, History, Watched};
+use crate::scene::SceneFile;
+
+/// Adds the [`Selection`], the [`History`] with its shortcuts, and the [`SceneFile`],
+/// and registers how to show Bevy's types in the inspector.
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+	fn build(&self, app: &mut App) {
+		if !app.is_plugin_added::<DefaultInspectorConfigPlugin>() {
+			app.add_plugins(DefaultInspectorConfigPlugin);
+		}
+		app.init_resource::<Selection>()
+			.init_resource::<History>()
+			.init_resource::<SceneFile>()
+			.add_systems(Update, history_shortcuts);
+	}
+}
+
+/// The entities selected in the inspector.
+#[derive(Resource, Debug, Default, Clone, Deref, DerefMut)]
+pub struct Selection(pub SelectedEntities);
+
+/// The camera whose viewport the mouse edits the scene in.
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct InspectorCamera;
+
+/// Draws the hierarchy of every entity, which selects entities when clicked.
+pub fn hierarchy_ui(world: &mut World, ui: &mut egui::Ui) {
+	world.resource_scope(|world, mut selection: Mut<Selection>| {
+		bevy_inspector::hierarchy::hierarchy_ui(world, ui, &mut selection);
+	});
+}
+
+/// Draws the components of the selected entity, or the components that all of the
+/// selected entities have. Edits are recorded in the [`History`].
+pub fn inspector_ui(world: &mut World, ui: &mut egui::Ui) {
+	let selection = world.resource::<Selection>().clone();
+	// Cloning every component and asset is slow, so only when this frame's input
+	// could edit one of them.
+	let watched = may_edit(ui).then(|| Watched::new(world, selection.as_slice()));
+	match selection.as_slice() {
+		[] => {
+			ui.weak("Nothing selected");
+		}
+		&[entity] => bevy_inspector::ui_for_entity(world, entity, ui),
+		entities => {
+			bevy_inspector::ui_for_entities_shared_components(world, entities, ui)
+		}
+	}
+	if let Some(watched) = watched {
+		record_changes(world, selection.as_slice(), watched);
+	}
+}
+
+/// Whether a widget could change a value this frame: a button is held or released,
+/// or there is any input other than the pointer moving.
+fn may_edit(ui: &egui::Ui) -> bool {
+	ui.input(|input| {
+		input.pointer.any_down()
+			|| input.pointer.any_released()
+			|| input
+				.events
+				.iter()
+				.any(|event| !matches!(event, egui::Event::PointerMoved(_)))
+	})
+}
@@ -1,10 +1,20 @@
-use bevy::{pbr::DirectionalLightShadowMap, prelude::*, window::PrimaryWindow};
-use bevy_egui::{EguiContext, EguiPlugin};
-use bevy_inspector_egui::{
-	bevy_inspector::hierarchy::SelectedEntities, DefaultInspectorConfigPlugin,
-};
+use bevy::{pbr::DirectionalLightShadowMap, prelude::*};
+use bevy_egui::EguiPlugin;
+use bevy_mod_picking::backends::raycast::RaycastPickable;
+use bevy_mod_picking::DefaultPickingPlugins;
+use bevy_oxr::xr_input::trackers::OpenXRTrackingRoot;
+use bevy_oxr::DefaultXrPlugins;
 use color_eyre::eyre::Result;
+use entity_inspector::gizmo::TransformGizmoPlugin;
+use entity_inspector::panel::{WorldspaceInspector, WorldspaceInspectorPlugin};
+use entity_inspector::picking::{ViewportPickingPlugin, ViewportPickingSettings};
+use entity_inspector::screen::ScreenInspectorPlugin;
+use entity_inspector::xr::XrInspectorPlugin;
+use entity_inspector::{InspectorCamera, InspectorPlugin};
+use openxr_6dof::poses::XrPosePlugin;
 use tracing::info;
+use worldspace_ui::pointer::{PointerSource, WorldspacePointer};
+use worldspace_ui::{WorldspaceUi, WorldspaceUiPlugin};
 
 const ASSET_FOLDER: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../assets/");
 
@@ -14,16 +24,34 @@ fn main() -> Result<()> {
 
 	info!("Running `entity-inspector` skill");
 
-	App::new()
-		.add_plugins(DefaultPlugins.set(AssetPlugin {
-			file_path: ASSET_FOLDER.to_string(),
-			..Default::default()
-		}))
-		.add_plugins(EguiPlugin)
-		.add_plugins(DefaultInspectorConfigPlugin)
+	let asset_plugin = AssetPlugin {
+		file_path: ASSET_FOLDER.to_string(),
+		..Default::default()
+	};
+	let mut app = App::new();
+	// With `--xr`, use the inspector panel from inside a headset.
+	if std::env::args().any(|arg| arg == "--xr") {
+		app.add_plugins(DefaultXrPlugins.set(asset_plugin))
+			.add_plugins((XrPosePlugin, XrInspectorPlugin))
+			.add_systems(Update, place_rig);
+	} else {
+		app.add_plugins(DefaultPlugins.set(asset_plugin))
+			.add_plugins(DefaultPickingPlugins)
+			.add_plugins((TransformGizmoPlugin, ViewportPickingPlugin))
+			.insert_resource(ViewportPickingSettings {
+				ignore_unpickable: true,
+			})
+			.add_systems(Startup, setup_camera);
+	}
+	app.add_plugins(EguiPlugin)
+		.add_plugins((
+			WorldspaceUiPlugin,
+			InspectorPlugin,
+			ScreenInspectorPlugin,
+			WorldspaceInspectorPlugin,
+		))
 		.add_systems(Startup, setup)
 		.add_systems(Update, animate_light)
-		.add_systems(PostUpdate, inspector_ui)
 		.run();
 
 	Ok(())
@@ -33,6 +61,7 @@ fn setup(
 	assets: Res<AssetServer>,
 	mut meshes: ResMut<Assets<Mesh>>,
 	mut materials: ResMut<Assets<StandardMaterial>>,
+	mut images: ResMut<Assets<Image>>,
 	mut commands: Commands,
 ) {
 	info!("Running setup system");
@@ -50,6 +79,13 @@ fn setup(
 		..default()
 	});
 
+	// An avatar, which clicking in the viewport selects as a whole
+	commands.spawn(SceneBundle {
+		scene: assets.load("malek.gltf#Scene0"),
+		transform: Transform::from_xyz(-2.0, 0.0, 0.0),
+		..default()
+	});
+
 	// Build the rest of the scene
 	commands.spawn(DirectionalLightBundle {
 		directional_light: DirectionalLight {
@@ -61,11 +97,6 @@ fn setup(
 		..default()
 	});
 	commands.insert_resource(DirectionalLightShadowMap { size: 4096 });
-	commands.spawn(Camera3dBundle {
-		transform: Transform::from_xyz(0.0, 6., 12.0)
-			.looking_at(Vec3::new(0., 1., 0.), Vec3::Y),
-		..default()
-	});
 	commands.spawn(PbrBundle {
 		mesh: meshes.add(
 			shape::Plane {
@@ -78,6 +109,37 @@ fn setup(
 		transform: Transform::from_xyz(0., 0., 0.),
 		..default()
 	});
+
+	// The inspector, in the world
+	commands.spawn((
+		SpatialBundle::from_transform(Transform::from_xyz(0.0, 1.4, 2.2)),
+		WorldspaceUi::menu(&mut images, Vec2::new(1.2, 0.8)),
+		WorldspaceInspector::default(),
+	));
+}
+
+/// The mouse points at the inspector panel, picks entities and drags the gizmo.
+fn setup_camera(mut config: ResMut<GizmoConfig>, mut commands: Commands) {
+	// Show the gizmo through the scene.
+	config.depth_bias = -1.0;
+
+	commands.spawn((
+		Camera3dBundle {
+			transform: Transform::from_xyz(0.0, 6., 12.0)
+				.looking_at(Vec3::new(0., 1., 0.), Vec3::Y),
+			..default()
+		},
+		WorldspacePointer::new(PointerSource::Mouse),
+		RaycastPickable,
+		InspectorCamera,
+	));
+}
+
+/// Stands the player behind the panel, rather than inside the cube.
+fn place_rig(mut roots: Query<&mut Transform, Added<OpenXRTrackingRoot>>) {
+	for mut root in roots.iter_mut() {
+		root.translation = Vec3::new(0.0, 0.0, 3.0);
+	}
 }
 
 fn animate_light(mut query: Query<&mut Transform, With<DirectionalLight>>) {
@@ -86,50 +148,3 @@ fn animate_light(mut query: Query<&mut Transform, With<DirectionalLight>>) {
 		t.rotate_y(0.01);
 	}
 }
-
-fn inspector_ui(world: &mut World, mut selected_entities: Local<SelectedEntities>) {
-	let mut egui_context = world
-		.query_filtered::<&mut EguiContext, With<PrimaryWindow>>()
-		.single(world)
-		.clone();
-
-	egui::SidePanel::left("hierarchy")
-		.default_width(200.0)
-		.show(egui_context.get_mut(), |ui| {
-			egui::ScrollArea::vertical().show(ui, |ui| {
-				ui.heading("Hierarchy");
-
-				bevy_inspector_egui::bevy_inspector::hierarchy::hierarchy_ui(
-					world,
-					ui,
-					&mut selected_entities,
-				);
-
-				ui.label("Press escape to toggle UI");
-				ui.allocate_space(ui.available_size());
-			});
-		});
-
-	egui::SidePanel::right("inspector")
-		.default_width(250.0)
-		.show(egui_context.get_mut(), |ui| {
-			egui::ScrollArea::vertical().show(ui, |ui| {
-				ui.heading("Inspector");
-
-				match selected_entities.as_slice() {
-					&[entity] => {
-						bevy_inspector_egui::bevy_inspector::ui_for_entity(
-							world, entity, ui,
-						);
-					}
-					entities => {
-						bevy_inspector_egui::bevy_inspector::ui_for_entities_shared_components(
-							world, entities, ui,
-						);
-					}
-				}
-
-				ui.allocate_space(ui.available_size());
-			});
-		});
-}
@@ -0,0 +1,148 @@
+//! The inspector on world-space panels, for using it from inside a headset.
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use worldspace_ui::pointer::WorldspacePointer;
+use worldspace_ui::WorldspaceUi;
+
+use crate::gizmo::gizmo_ui;
+use crate::history::{edit_ui, history_ui};
+use crate::scene::scene_ui;
+use crate::{hierarchy_ui, inspector_ui};
+
+/// Height of a panel's title bar, which can be dragged to move the panel, in points.
+const TITLE_HEIGHT: f32 = 28.0;
+
+/// Draws the inspector on [`WorldspaceInspector`] panels, and lets [`Grabber`]s carry
+/// them around. Needs the `WorldspaceUiPlugin` and the
+/// [`InspectorPlugin`](crate::InspectorPlugin).
+pub struct WorldspaceInspectorPlugin;
+
+impl Plugin for WorldspaceInspectorPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_systems(Update, (draw_panels, grab_panels));
+	}
+}
+
+/// A [`WorldspaceUi`] panel that shows the hierarchy on its left and the inspector on
+/// its right. Dragging its title bar with a pointer moves it in its own plane, and
+/// scrolling while dragging pushes it away or pulls it closer. The panel shouldn't
+/// have a parent.
+#[derive(Component, Debug, Clone)]
+pub struct WorldspaceInspector {
+	/// Width of the hierarchy, in points.
+	pub hierarchy_width: f32,
+}
+
+impl Default for WorldspaceInspector {
+	fn default() -> Self {
+		Self {
+			hierarchy_width: 220.0,
+		}
+	}
+}
+
+/// A [`WorldspacePointer`] that can pick up [`WorldspaceInspector`] panels. While
+/// [`Self::held`] is set, the panel that it pointed at when it was set moves along
+/// with it, as if it were holding it.
+#[derive(Component, Debug, Clone, Default)]
+pub struct Grabber {
+	pub held: bool,
+	was_held: bool,
+	/// The panel being held, and where it is relative to the grabber.
+	holding: Option<(Entity, Transform)>,
+}
+
+fn draw_panels(world: &mut World) {
+	let panels: Vec<(Entity, egui::Context, f32)> = world
+		.query::<(Entity, &mut WorldspaceUi, &WorldspaceInspector)>()
+		.iter_mut(world)
+		.map(|(entity, mut panel, inspector)| {
+			(entity, panel.ctx_mut().clone(), inspector.hierarchy_width)
+		})
+		.collect();
+	for (entity, ctx, hierarchy_width) in panels {
+		let mut moved = egui::Vec2::ZERO;
+		let mut pushed = 0.0;
+		egui::TopBottomPanel::top("title")
+			.exact_height(TITLE_HEIGHT)
+			.show(&ctx, |ui| {
+				let bar = ui.interact(
+					ui.max_rect(),
+					ui.id().with("drag"),
+					egui::Sense::drag(),
+				);
+				ui.centered_and_justified(|ui| ui.strong("⠿ Inspector"));
+				if bar.dragged() {
+					moved = bar.drag_delta();
+					pushed = ui.input(|input| input.scroll_delta.y);
+				}
+				bar.on_hover_cursor(egui::CursorIcon::Grab);
+			});
+		egui::SidePanel::left("hierarchy")
+			.exact_width(hierarchy_width)
+			.show(&ctx, |ui| {
+				egui::ScrollArea::vertical().show(ui, |ui| {
+					scene_ui(world, ui);
+					ui.separator();
+					edit_ui(world, ui);
+					hierarchy_ui(world, ui);
+					egui::CollapsingHeader::new("History")
+						.show(ui, |ui| history_ui(world, ui));
+					ui.allocate_space(ui.available_size());
+				});
+			});
+		egui::CentralPanel::default().show(&ctx, |ui| {
+			egui::ScrollArea::vertical().show(ui, |ui| {
+				gizmo_ui(world, ui);
+				ui.separator();
+				inspector_ui(world, ui);
+				ui.allocate_space(ui.available_size());
+			});
+		});
+
+		if moved == egui::Vec2::ZERO && pushed == 0.0 {
+			continue;
+		}
+		let points_per_metre =
+			world.get::<WorldspaceUi>(entity).unwrap().points_per_metre;
+		let Some(mut transform) = world.get_mut::<Transform>(entity) else {
+			continue;
+		};
+		// egui's y is down, and the panel faces +Z.
+		let offset = Vec3::new(moved.x, -moved.y, -pushed) / points_per_metre;
+		let offset = transform.rotation * (transform.scale * offset);
+		transform.translation += offset;
+	}
+}
+
+fn grab_panels(
+	mut grabbers: Query<(&mut Grabber, &WorldspacePointer, &GlobalTransform)>,
+	mut panels: Query<&mut Transform, With<WorldspaceInspector>>,
+) {
+	for (mut grabber, pointer, pointer_transform) in grabbers.iter_mut() {
+		let just_held = grabber.held && !grabber.was_held;
+		grabber.was_held = grabber.held;
+		if !grabber.held {
+			grabber.holding = None;
+			continue;
+		}
+		let pointer_transform = pointer_transform.compute_transform();
+		if just_held {
+			grabber.holding = pointer
+				.hovered
+				.and_then(|(entity, _)| Some((entity, panels.get(entity).ok()?)))
+				.map(|(entity, panel)| {
+					let offset = pointer_transform.compute_matrix().inverse()
+						* panel.compute_matrix();
+					(entity, Transform::from_matrix(offset))
+				});
+		}
+		let Some((entity, offset)) = grabber.holding else {
+			continue;
+		};
+		if let Ok(mut panel) = panels.get_mut(entity) {
+			*panel = pointer_transform * offset;
+		}
+	}
+}
@@ -0,0 +1,454 @@
+//! Selecting entities by clicking on them in the [`InspectorCamera`]'s viewport.
+//!
+//! What is under the mouse comes from `bevy_mod_picking`, so the app needs its
+//! `DefaultPickingPlugins`. This only maps what was clicked to what should be
+//! selected, and adds box selection on top.
+
+use bevy::prelude::*;
+use bevy::render::mesh::skinning::SkinnedMesh;
+use bevy::render::primitives::Aabb;
+use bevy::utils::HashSet;
+use bevy::window::PrimaryWindow;
+use bevy_egui::{egui, EguiContext};
+use bevy_mod_picking::events::{Click, Pointer};
+use bevy_mod_picking::focus::HoverMap;
+use bevy_mod_picking::pointer::{PointerButton, PointerId};
+use bevy_mod_picking::prelude::Pickable;
+use worldspace_ui::pointer::WorldspacePointer;
+use worldspace_ui::WorldspaceUi;
+
+use crate::gizmo::{GizmoSet, TransformGizmo};
+use crate::{InspectorCamera, Selection};
+
+/// How far the mouse must move while pressed to select a box rather than click, in
+/// logical pixels.
+const BOX_THRESHOLD: f32 = 4.0;
+/// How far a skinned mesh reaches past its joints, in metres.
+const JOINT_PADDING: f32 = 0.1;
+
+const SELECTED_COLOR: Color = Color::ORANGE;
+const HOVERED_COLOR: Color = Color::rgb(1.0, 0.85, 0.6);
+
+/// Selects what the left mouse button clicks on in the viewport of the camera with an
+/// [`InspectorCamera`], or drags a box around, and outlines the selected meshes.
+/// Needs the [`InspectorPlugin`](crate::InspectorPlugin) and `bevy_mod_picking`'s
+/// `DefaultPickingPlugins`. With the
+/// [`TransformGizmoPlugin`](crate::gizmo::TransformGizmoPlugin), clicks on the gizmo
+/// are left to it.
+pub struct ViewportPickingPlugin;
+
+impl Plugin for ViewportPickingPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<ViewportPicking>()
+			.init_resource::<ViewportPickingSettings>()
+			.add_systems(
+				Update,
+				(ignore_unpickable, pick_entities, outline_selection)
+					.chain()
+					.in_set(PickingSet)
+					.after(GizmoSet),
+			);
+	}
+}
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PickingSet;
+
+/// Clicking any part of an entity with this selects it, rather than the part. Scenes,
+/// like an avatar, are already picked as a whole.
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct PickRoot;
+
+#[derive(Resource, Debug, Default, Clone)]
+pub struct ViewportPickingSettings {
+	/// Whether to make `bevy_mod_picking` ignore skinned meshes and panels as they
+	/// spawn. It casts rays against the bind pose of skinned meshes, which is
+	/// nowhere near an animated avatar, so they can block what is really under the
+	/// mouse, and panels are clicked with a [`WorldspacePointer`]. This is off by
+	/// default since it affects picking in the whole app, and it leaves alone any
+	/// entity that the app already gave a `Pickable`.
+	pub ignore_unpickable: bool,
+}
+
+/// What the mouse is over in the viewport.
+#[derive(Resource, Debug, Default)]
+pub struct ViewportPicking {
+	hovered: Option<Entity>,
+	/// Where the left mouse button was pressed in the viewport, while it is held.
+	pressed_at: Option<Vec2>,
+}
+
+impl ViewportPicking {
+	/// The entity that a click would select.
+	pub fn hovered(&self) -> Option<Entity> {
+		self.hovered
+	}
+}
+
+type MeshBounds<'a> = (
+	Entity,
+	&'a Aabb,
+	&'a GlobalTransform,
+	&'a ViewVisibility,
+	Option<&'a SkinnedMesh>,
+);
+
+/// What is picked as a whole.
+type LogicalRoot = Or<(With<Handle<Scene>>, With<PickRoot>)>;
+
+/// Meshes that were just spawned, and that `bevy_mod_picking` would get wrong or
+/// that are handled elsewhere.
+type NewUnpickable = (
+	With<Handle<Mesh>>,
+	Without<Pickable>,
+	Or<(Added<SkinnedMesh>, Added<WorldspaceUi>)>,
+);
+
+/// See [`ViewportPickingSettings::ignore_unpickable`]. Skinned meshes are still
+/// picked by their joints here.
+fn ignore_unpickable(
+	mut commands: Commands,
+	settings: Res<ViewportPickingSettings>,
+	meshes: Query<Entity, NewUnpickable>,
+) {
+	if !settings.ignore_unpickable {
+		return;
+	}
+	for entity in meshes.iter() {
+		commands.entity(entity).insert(Pickable::IGNORE);
+	}
+}
+
+#[allow(clippy::too_many_arguments)]
+fn pick_entities(
+	mut picking: ResMut<ViewportPicking>,
+	mut selection: ResMut<Selection>,
+	mut clicks: EventReader<Pointer<Click>>,
+	hover_map: Res<HoverMap>,
+	gizmo: Option<Res<TransformGizmo>>,
+	buttons: Res<Input<MouseButton>>,
+	keys: Res<Input<KeyCode>>,
+	windows: Query<&Window, With<PrimaryWindow>>,
+	mut contexts: Query<&mut EguiContext, With<PrimaryWindow>>,
+	cameras: Query<
+		(&Camera, &GlobalTransform, Option<&WorldspacePointer>),
+		With<InspectorCamera>,
+	>,
+	meshes: Query<MeshBounds, (With<Handle<Mesh>>, Without<WorldspaceUi>)>,
+	joints: Query<&GlobalTransform>,
+	parents: Query<&Parent>,
+	roots: Query<(), LogicalRoot>,
+) {
+	let clicked = clicks
+		.read()
+		.filter(|click| click.button == PointerButton::Primary)
+		.map(|click| (click.target, click.hit.depth))
+		.last();
+	let (Ok(window), Ok((camera, camera_transform, pointer))) =
+		(windows.get_single(), cameras.get_single())
+	else {
+		return;
+	};
+	let Some(cursor) = window.cursor_position() else {
+		picking.hovered = None;
+		return;
+	};
+	let mut context = contexts.get_single_mut().ok();
+	let over_ui = context.as_mut().is_some_and(|context| {
+		let ctx = context.get_mut();
+		ctx.is_pointer_over_area() || ctx.is_using_pointer()
+	}) || pointer.is_some_and(|pointer| pointer.hovered.is_some());
+	let over_gizmo = gizmo.is_some_and(|gizmo| gizmo.is_active());
+	let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+	// Alt picks the exact mesh under the mouse, rather than what it's part of.
+	let alt = keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+	let root = |entity: Entity| {
+		if alt {
+			return entity;
+		}
+		std::iter::once(entity)
+			.chain(parents.iter_ancestors(entity))
+			.find(|&ancestor| roots.contains(ancestor))
+			.unwrap_or(entity)
+	};
+	// A skinned mesh wins if the ray reaches its joints before the picked mesh.
+	let skinned_hit = camera
+		.viewport_to_world(camera_transform, cursor)
+		.and_then(|ray| raycast_skinned(ray, &meshes, &joints));
+	let nearest = |hit: Option<(Entity, f32)>| match (hit, skinned_hit) {
+		(Some((_, depth)), Some((skinned, distance))) if distance < depth => {
+			Some(skinned)
+		}
+		(Some((entity, _)), _) => Some(entity),
+		(None, skinned) => skinned.map(|(entity, _)| entity),
+	};
+
+	let hovered = hover_map.get(&PointerId::Mouse).and_then(|hits| {
+		hits.iter()
+			.filter(|(entity, _)| meshes.contains(**entity))
+			.map(|(&entity, hit)| (entity, hit.depth))
+			.min_by(|(_, a), (_, b)| a.total_cmp(b))
+	});
+	picking.hovered = (!over_ui && !over_gizmo)
+		.then(|| nearest(hovered))
+		.flatten()
+		.map(root);
+
+	if buttons.just_pressed(MouseButton::Left) && !over_ui && !over_gizmo {
+		picking.pressed_at = Some(cursor);
+	}
+	let Some(pressed_at) = picking.pressed_at else {
+		return;
+	};
+	let area = Rect::from_corners(pressed_at, cursor);
+	let boxed = area.size().max_element() >= BOX_THRESHOLD;
+
+	if buttons.pressed(MouseButton::Left) {
+		if let (true, Some(context)) = (boxed, context.as_mut()) {
+			let painter = context.get_mut().layer_painter(egui::LayerId::new(
+				egui::Order::Foreground,
+				egui::Id::new("box select"),
+			));
+			let rect = egui::Rect::from_min_max(
+				egui::pos2(area.min.x, area.min.y),
+				egui::pos2(area.max.x, area.max.y),
+			);
+			let color = egui::Color32::from_rgb(255, 165, 0);
+			painter.rect(
+				rect,
+				0.0,
+				color.gamma_multiply(0.1),
+				egui::Stroke::new(1.0, color),
+			);
+		}
+		return;
+	}
+	picking.pressed_at = None;
+
+	if !boxed {
+		let clicked = clicked.filter(|(entity, _)| meshes.contains(*entity));
+		match nearest(clicked).map(root) {
+			Some(entity) => selection.select_maybe_add(entity, shift),
+			None if !shift => selection.clear(),
+			None => {}
+		}
+		return;
+	}
+
+	// Everything whose middle is in the box.
+	let mut boxed_entities = HashSet::new();
+	let entities: Vec<Entity> = meshes
+		.iter()
+		.filter(|(_, aabb, transform, visibility, skinned)| {
+			visibility.get()
+				&& world_bounds(aabb, transform, *skinned, &joints)
+					.and_then(|bounds| {
+						camera.world_to_viewport(camera_transform, bounds.translation())
+					})
+					.is_some_and(|position| area.contains(position))
+		})
+		.map(|(entity, ..)| root(entity))
+		.filter(|&entity| boxed_entities.insert(entity))
+		.collect();
+	if !shift {
+		selection.clear();
+	}
+	for entity in entities {
+		if !selection.contains(entity) {
+			selection.select_maybe_add(entity, true);
+		}
+	}
+}
+
+/// Draws the bounds of every mesh in the selected entities, and in the one under the
+/// mouse.
+fn outline_selection(
+	mut gizmos: Gizmos,
+	picking: Res<ViewportPicking>,
+	selection: Res<Selection>,
+	children: Query<&Children>,
+	meshes: Query<MeshBounds, With<Handle<Mesh>>>,
+	joints: Query<&GlobalTransform>,
+) {
+	let hovered = picking
+		.hovered
+		.filter(|entity| !selection.contains(*entity))
+		.map(|entity| (entity, HOVERED_COLOR));
+	let selected = selection.iter().map(|entity| (entity, SELECTED_COLOR));
+	for (entity, color) in selected.chain(hovered) {
+		for entity in std::iter::once(entity).chain(children.iter_descendants(entity)) {
+			let Ok((_, aabb, transform, visibility, skinned)) = meshes.get(entity)
+			else {
+				continue;
+			};
+			if !visibility.get() {
+				continue;
+			}
+			if let Some(bounds) = world_bounds(aabb, transform, skinned, &joints) {
+				gizmos.cuboid(bounds, color);
+			}
+		}
+	}
+}
+
+/// The mesh's bounds in world space, as the transform of a unit cube. A skinned
+/// mesh's [`Aabb`] is of its bind pose, so its joints are boxed instead.
+fn world_bounds(
+	aabb: &Aabb,
+	transform: &GlobalTransform,
+	skinned: Option<&SkinnedMesh>,
+	joints: &Query<&GlobalTransform>,
+) -> Option<GlobalTransform> {
+	let Some(skinned) = skinned else {
+		let bounds = Transform::from_translation(aabb.center.into())
+			.with_scale(Vec3::from(aabb.half_extents) * 2.0);
+		return Some(transform.mul_transform(bounds));
+	};
+	let (min, max) = joint_bounds(skinned, joints)?;
+	let bounds = Transform::from_translation((min + max) / 2.0).with_scale(max - min);
+	Some(bounds.into())
+}
+
+/// The corners of the box around the joints of a skinned mesh, padded by
+/// [`JOINT_PADDING`].
+fn joint_bounds(
+	skinned: &SkinnedMesh,
+	joints: &Query<&GlobalTransform>,
+) -> Option<(Vec3, Vec3)> {
+	let mut positions = joints
+		.iter_many(&skinned.joints)
+		.map(GlobalTransform::translation);
+	let first = positions.next()?;
+	let (min, max) = positions.fold((first, first), |(min, max), position| {
+		(min.min(position), max.max(position))
+	});
+	Some((min - JOINT_PADDING, max + JOINT_PADDING))
+}
+
+/// The closest visible skinned mesh whose joints the ray passes near, and how far
+/// along the ray it is.
+fn raycast_skinned(
+	ray: Ray,
+	meshes: &Query<MeshBounds, (With<Handle<Mesh>>, Without<WorldspaceUi>)>,
+	joints: &Query<&GlobalTransform>,
+) -> Option<(Entity, f32)> {
+	meshes
+		.iter()
+		.filter(|(.., visibility, _)| visibility.get())
+		.filter_map(|(entity, .., skinned)| {
+			let (min, max) = joint_bounds(skinned?, joints)?;
+			intersect_aabb(ray, min, max).map(|distance| (entity, distance))
+		})
+		.min_by(|(_, a), (_, b)| a.total_cmp(b))
+}
+
+/// Where the ray enters the box, or 0 if it starts inside it.
+fn intersect_aabb(ray: Ray, min: Vec3, max: Vec3) -> Option<f32> {
+	let inverse = ray.direction.recip();
+	let a = (min - ray.origin) * inverse;
+	let b = (max - ray.origin) * inverse;
+	let near = a.min(b).max_element().max(0.0);
+	let far = a.max(b).min_element();
+	(near <= far).then_some(near)
+}
+
+#[cfg(test)]
+mod tests {
+	use bevy::ecs::system::SystemState;
+
+	use super::*;
+
+	#[test]
+	fn rays_enter_boxes() {
+		let (min, max) = (Vec3::splat(-1.0), Vec3::splat(1.0));
+		let ray = |origin: Vec3, direction: Vec3| Ray {
+			origin,
+			direction: direction.normalize(),
+		};
+		// Straight on, and at an angle through a corner region.
+		assert_eq!(
+			intersect_aabb(ray(Vec3::new(0.0, 0.0, 5.0), Vec3::NEG_Z), min, max),
+			Some(4.0)
+		);
+		let diagonal = intersect_aabb(ray(Vec3::splat(3.0), Vec3::NEG_ONE), min, max);
+		assert!((diagonal.unwrap() - 2.0 * 3_f32.sqrt()).abs() < 1e-5);
+		// From inside.
+		assert_eq!(
+			intersect_aabb(ray(Vec3::ZERO, Vec3::X), min, max),
+			Some(0.0)
+		);
+		// Missing it, pointing away, and parallel to a face outside of it.
+		assert_eq!(
+			intersect_aabb(ray(Vec3::new(2.0, 0.0, 5.0), Vec3::NEG_Z), min, max),
+			None
+		);
+		assert_eq!(
+			intersect_aabb(ray(Vec3::new(0.0, 0.0, 5.0), Vec3::Z), min, max),
+			None
+		);
+		assert_eq!(
+			intersect_aabb(ray(Vec3::new(0.0, 2.0, 5.0), Vec3::NEG_Z), min, max),
+			None
+		);
+	}
+
+	#[test]
+	fn skinned_meshes_are_boxed_by_their_joints() {
+		let mut world = World::new();
+		let joints = vec![
+			world.spawn(GlobalTransform::from_xyz(0.0, 1.0, 0.0)).id(),
+			world.spawn(GlobalTransform::from_xyz(0.5, 1.8, -0.2)).id(),
+			world.spawn(GlobalTransform::from_xyz(-0.4, 0.2, 0.1)).id(),
+		];
+		let skinned = SkinnedMesh {
+			inverse_bindposes: Default::default(),
+			joints,
+		};
+		let mut state = SystemState::<Query<&GlobalTransform>>::new(&mut world);
+		let query = state.get(&world);
+		let (min, max) = joint_bounds(&skinned, &query).unwrap();
+		let padding = Vec3::splat(JOINT_PADDING);
+		assert!(min.abs_diff_eq(Vec3::new(-0.4, 0.2, -0.2) - padding, 1e-5));
+		assert!(max.abs_diff_eq(Vec3::new(0.5, 1.8, 0.1) + padding, 1e-5));
+
+		let unposed = SkinnedMesh {
+			inverse_bindposes: Default::default(),
+			joints: Vec::new(),
+		};
+		assert_eq!(joint_bounds(&unposed, &query), None);
+	}
+
+	#[test]
+	fn only_ignores_unpickable_when_asked() {
+		let mut app = App::new();
+		app.init_resource::<ViewportPickingSettings>()
+			.add_systems(Update, ignore_unpickable);
+		let skinned = || {
+			(
+				Handle::<Mesh>::default(),
+				SkinnedMesh {
+					inverse_bindposes: Default::default(),
+					joints: Vec::new(),
+				},
+			)
+		};
+		let off = app.world.spawn(skinned()).id();
+		app.update();
+		assert!(app.world.get::<Pickable>(off).is_none());
+
+		app.world
+			.resource_mut::<ViewportPickingSettings>()
+			.ignore_unpickable = true;
+		let on = app.world.spawn(skinned()).id();
+		let pickable = Pickable {
+			should_block_lower: true,
+			should_emit_events: true,
+		};
+		let chosen = app.world.spawn((skinned(), pickable)).id();
+		let plain = app.world.spawn(Handle::<Mesh>::default()).id();
+		app.update();
+		assert_eq!(app.world.get::<Pickable>(on), Some(&Pickable::IGNORE));
+		assert_eq!(app.world.get::<Pickable>(chosen), Some(&pickable));
+		assert!(app.world.get::<Pickable>(plain).is_none());
+	}
+}
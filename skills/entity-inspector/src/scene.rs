@@ -0,0 +1,413 @@
+//! Saving entities from the inspector to a `.scn.ron` file, and loading them back.
+
+use std::any::TypeId;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use bevy::ecs::component::ComponentInfo;
+use bevy::prelude::*;
+use bevy::reflect::serde::ReflectSerializer;
+use bevy::reflect::TypeRegistry;
+use bevy::scene::serde::SceneDeserializer;
+use bevy::utils::HashMap;
+use bevy::window::Window;
+use bevy_egui::egui;
+use eyre::{Result, WrapErr};
+use serde::de::DeserializeSeed;
+
+use crate::history::{Edit, History, HistoryEntry};
+use crate::panel::WorldspaceInspector;
+use crate::Selection;
+
+/// What was saved, and what couldn't be.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SaveReport {
+	pub entities: usize,
+	/// Components that were left out, by type name.
+	pub skipped: BTreeMap<String, SkippedComponent>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedComponent {
+	pub reason: SkipReason,
+	/// How many of the saved entities had it.
+	pub entities: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+	/// The component isn't registered in the type registry with
+	/// `#[reflect(Component)]`.
+	NotReflected,
+	/// The component is reflected, but some of its fields can't be serialized.
+	NotSerializable(String),
+}
+
+impl std::fmt::Display for SaveReport {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "Saved {} entities", self.entities)?;
+		if self.skipped.is_empty() {
+			return Ok(());
+		}
+		write!(f, ", without:")?;
+		for (name, skipped) in &self.skipped {
+			let reason = match &skipped.reason {
+				SkipReason::NotReflected => "not reflected".to_owned(),
+				SkipReason::NotSerializable(err) => format!("not serializable: {err}"),
+			};
+			write!(f, "\n- {name} on {} ({reason})", skipped.entities)?;
+		}
+		Ok(())
+	}
+}
+
+/// Saves `entities` and their descendants to a scene file at `path`. Components that
+/// can't be saved are left out, and listed in the report. Parents outside of what
+/// is saved are left out too, so the saved entities load as roots.
+pub fn save_scene(
+	world: &World,
+	entities: &[Entity],
+	path: impl AsRef<Path>,
+) -> Result<SaveReport> {
+	let path = path.as_ref();
+	let mut saved = Vec::new();
+	let mut stack = entities.to_vec();
+	while let Some(entity) = stack.pop() {
+		if saved.contains(&entity) || world.get_entity(entity).is_none() {
+			continue;
+		}
+		saved.push(entity);
+		if let Some(children) = world.get::<Children>(entity) {
+			stack.extend(children.iter().copied());
+		}
+	}
+
+	let registry = world.resource::<AppTypeRegistry>();
+	let mut report = SaveReport {
+		entities: saved.len(),
+		..default()
+	};
+	let mut skip = |name: &str, reason: SkipReason| {
+		report
+			.skipped
+			.entry(short_name(name))
+			.or_insert(SkippedComponent {
+				reason,
+				entities: 0,
+			})
+			.entities += 1;
+	};
+	{
+		let registry = registry.read();
+		for &entity in &saved {
+			for info in world.inspect_entity(entity) {
+				if !is_reflected(&registry, info) {
+					skip(info.name(), SkipReason::NotReflected);
+				}
+			}
+		}
+	}
+
+	let mut scene = DynamicSceneBuilder::from_world(world)
+		.extract_entities(saved.iter().copied())
+		.build();
+	{
+		let registry = registry.read();
+		for dynamic_entity in &mut scene.entities {
+			let is_root = !world
+				.get::<Parent>(dynamic_entity.entity)
+				.is_some_and(|parent| saved.contains(&parent.get()));
+			dynamic_entity.components.retain(|component| {
+				let info = component.get_represented_type_info();
+				if is_root
+					&& info.map(|info| info.type_id()) == Some(TypeId::of::<Parent>())
+				{
+					return false;
+				}
+				let serializer = ReflectSerializer::new(&**component, &registry);
+				match ron::to_string(&serializer) {
+					Ok(_) => true,
+					Err(err) => {
+						let reason = SkipReason::NotSerializable(err.to_string());
+						// The component is a dynamic clone, so name what it represents.
+						let name = info.map_or(component.reflect_type_path(), |info| {
+							info.type_path()
+						});
+						skip(name, reason);
+						false
+					}
+				}
+			});
+		}
+	}
+
+	let ron = scene
+		.serialize_ron(registry)
+		.wrap_err("failed to serialize the scene")?;
+	if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+		std::fs::create_dir_all(dir)
+			.wrap_err_with(|| format!("failed to create {}", dir.display()))?;
+	}
+	std::fs::write(path, ron)
+		.wrap_err_with(|| format!("failed to write {}", path.display()))?;
+	Ok(report)
+}
+
+/// Saves every entity, except windows and inspector panels.
+pub fn save_world(world: &mut World, path: impl AsRef<Path>) -> Result<SaveReport> {
+	let roots: Vec<Entity> = world
+		.query_filtered::<Entity, (
+			Without<Parent>,
+			Without<Window>,
+			Without<WorldspaceInspector>,
+		)>()
+		.iter(world)
+		.collect();
+	save_scene(world, &roots, path)
+}
+
+/// Spawns the entities in a scene file, and returns the ones without a parent.
+pub fn load_scene(world: &mut World, path: impl AsRef<Path>) -> Result<Vec<Entity>> {
+	let path = path.as_ref();
+	let ron = std::fs::read_to_string(path)
+		.wrap_err_with(|| format!("failed to read {}", path.display()))?;
+	let registry = world.resource::<AppTypeRegistry>().clone();
+	let scene = {
+		let registry = registry.read();
+		let mut deserializer = ron::de::Deserializer::from_str(&ron)
+			.wrap_err_with(|| format!("failed to parse {}", path.display()))?;
+		SceneDeserializer {
+			type_registry: &registry,
+		}
+		.deserialize(&mut deserializer)
+		.wrap_err_with(|| format!("failed to parse {}", path.display()))?
+	};
+	let mut entity_map = HashMap::default();
+	scene
+		.write_to_world_with(world, &mut entity_map, &registry)
+		.wrap_err_with(|| format!("failed to spawn {}", path.display()))?;
+	Ok(entity_map
+		.values()
+		.copied()
+		.filter(|&entity| world.get::<Parent>(entity).is_none())
+		.collect())
+}
+
+/// Whether the component can be copied out of the world and back with reflection.
+pub(crate) fn is_reflected(registry: &TypeRegistry, info: &ComponentInfo) -> bool {
+	info.type_id().is_some_and(|type_id| {
+		registry.get(type_id).is_some_and(|registration| {
+			registration.data::<ReflectComponent>().is_some()
+		})
+	})
+}
+
+fn short_name(type_name: &str) -> String {
+	bevy::utils::get_short_name(type_name)
+}
+
+/// The file that the inspector saves to and loads from, and how that last went.
+#[derive(Resource, Debug, Clone)]
+pub struct SceneFile {
+	pub path: PathBuf,
+	/// What happened the last time the scene was saved or loaded.
+	status: Option<Result<String, String>>,
+}
+
+impl Default for SceneFile {
+	fn default() -> Self {
+		Self {
+			path: "inspector.scn.ron".into(),
+			status: None,
+		}
+	}
+}
+
+/// Draws the scene file's path, with buttons to save the selected entities or the
+/// whole world to it, and to load it. Loaded entities are selected.
+pub fn scene_ui(world: &mut World, ui: &mut egui::Ui) {
+	let mut file = world.resource::<SceneFile>().clone();
+	let mut path = file.path.display().to_string();
+	ui.horizontal(|ui| {
+		ui.label("Scene:");
+		if ui.text_edit_singleline(&mut path).changed() {
+			file.path = path.into();
+		}
+	});
+	ui.horizontal(|ui| {
+		let selection = world.resource::<Selection>().as_slice().to_vec();
+		let save_selected = egui::Button::new("Save selected");
+		if ui
+			.add_enabled(!selection.is_empty(), save_selected)
+			.clicked()
+		{
+			file.status = Some(report(save_scene(world, &selection, &file.path)));
+		}
+		if ui.button("Save all").clicked() {
+			file.status = Some(report(save_world(world, &file.path)));
+		}
+		if ui.button("Load").clicked() {
+			file.status = Some(match load_scene(world, &file.path) {
+				Ok(roots) => {
+					let label = format!("Load {}", file.path.display());
+					world.resource_mut::<History>().push(HistoryEntry::new(
+						label,
+						vec![Edit::Spawn {
+							roots: roots.clone(),
+							despawned: None,
+						}],
+					));
+					let mut selection = world.resource_mut::<Selection>();
+					selection.clear();
+					for &root in &roots {
+						selection.select_maybe_add(root, true);
+					}
+					Ok(format!("Loaded {} entities", roots.len()))
+				}
+				Err(err) => Err(format!("{err:#}")),
+			});
+		}
+	});
+	match &file.status {
+		Some(Ok(status)) => {
+			ui.label(status);
+		}
+		Some(Err(err)) => {
+			ui.colored_label(ui.visuals().error_fg_color, err);
+		}
+		None => {}
+	}
+	world.insert_resource(file);
+}
+
+fn report(result: Result<SaveReport>) -> Result<String, String> {
+	match result {
+		Ok(report) => {
+			if !report.skipped.is_empty() {
+				warn!("{report}");
+			}
+			Ok(report.to_string())
+		}
+		Err(err) => Err(format!("{err:#}")),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[derive(Component, Reflect, Default, Debug, PartialEq)]
+	#[reflect(Component)]
+	struct Health(f32);
+
+	/// Not registered at all.
+	#[derive(Component)]
+	struct Unreflected;
+
+	/// Reflected, but `Opaque` can't be serialized.
+	#[derive(Component, Reflect, Default)]
+	#[reflect(Component)]
+	struct Cached(Opaque);
+
+	#[derive(Reflect, Clone, Default)]
+	#[reflect_value]
+	struct Opaque;
+
+	fn world() -> World {
+		let mut app = App::new();
+		app.add_plugins((MinimalPlugins, TransformPlugin, HierarchyPlugin))
+			.register_type::<Health>()
+			.register_type::<Cached>();
+		std::mem::take(&mut app.world)
+	}
+
+	/// A file of its own for each test, since tests run in parallel.
+	fn temp_path(name: &str) -> PathBuf {
+		let dir = std::env::temp_dir()
+			.join(format!("entity-inspector-{}-{name}", std::process::id()));
+		let _ = std::fs::remove_dir_all(&dir);
+		dir.join("scene.scn.ron")
+	}
+
+	#[test]
+	fn round_trips_a_hierarchy() {
+		let path = temp_path("round-trip");
+		let mut world = world();
+		let child = world
+			.spawn((
+				Name::new("child"),
+				TransformBundle::from_transform(Transform::from_xyz(1.0, 2.0, 3.0)),
+			))
+			.id();
+		let parent = world
+			.spawn((Name::new("parent"), TransformBundle::default(), Health(5.0)))
+			.push_children(&[child])
+			.id();
+		// Not saved, since it isn't under the parent.
+		world.spawn(Name::new("other"));
+
+		let report = save_scene(&world, &[parent], &path).unwrap();
+		assert_eq!(report.entities, 2);
+		assert!(report.skipped.is_empty(), "{report}");
+
+		let mut world = self::world();
+		let roots = load_scene(&mut world, &path).unwrap();
+		let [root] = roots[..] else {
+			panic!("expected one root, got {roots:?}");
+		};
+		assert_eq!(world.get::<Name>(root).unwrap().as_str(), "parent");
+		assert_eq!(world.get::<Health>(root), Some(&Health(5.0)));
+		let children = world.get::<Children>(root).unwrap();
+		let [child] = children[..] else {
+			panic!("expected one child, got {children:?}");
+		};
+		assert_eq!(world.get::<Name>(child).unwrap().as_str(), "child");
+		assert_eq!(world.get::<Parent>(child).unwrap().get(), root);
+		assert_eq!(
+			world.get::<Transform>(child).unwrap().translation,
+			Vec3::new(1.0, 2.0, 3.0)
+		);
+		assert_eq!(world.entities().len(), 2);
+	}
+
+	#[test]
+	fn reports_components_that_cant_be_saved() {
+		let path = temp_path("report");
+		let mut world = world();
+		let child = world.spawn((Unreflected, Cached::default())).id();
+		let parent = world
+			.spawn((Unreflected, Health(1.0)))
+			.push_children(&[child])
+			.id();
+
+		let report = save_scene(&world, &[parent], &path).unwrap();
+		assert_eq!(report.entities, 2);
+		assert_eq!(
+			report.skipped["Unreflected"],
+			SkippedComponent {
+				reason: SkipReason::NotReflected,
+				entities: 2,
+			}
+		);
+		let cached = &report.skipped["Cached"];
+		assert_eq!(cached.entities, 1);
+		assert!(matches!(cached.reason, SkipReason::NotSerializable(_)));
+		assert_eq!(report.skipped.len(), 2, "{report}");
+
+		// The rest still loads.
+		let mut world = self::world();
+		let roots = load_scene(&mut world, &path).unwrap();
+		assert_eq!(world.get::<Health>(roots[0]), Some(&Health(1.0)));
+		let child = world.get::<Children>(roots[0]).unwrap()[0];
+		assert!(world.get::<Cached>(child).is_none());
+	}
+
+	#[test]
+	fn load_errors_name_the_file() {
+		let path = temp_path("broken");
+		std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+		std::fs::write(&path, "(entities: {").unwrap();
+		let err = load_scene(&mut world(), &path).unwrap_err();
+		assert!(format!("{err}").contains("scene.scn.ron"), "{err}");
+	}
+}
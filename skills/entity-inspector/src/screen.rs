@@ -0,0 +1,83 @@
+//! The inspector as side panels on the primary window.
+
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_egui::{egui, EguiContext, EguiSet};
+
+use crate::gizmo::gizmo_ui;
+use crate::history::{edit_ui, history_ui};
+use crate::scene::scene_ui;
+use crate::{hierarchy_ui, inspector_ui};
+
+/// Shows the hierarchy on the left of the primary window and the inspector on the
+/// right, toggled with escape. Needs the `EguiPlugin` and the [`InspectorPlugin`](crate::InspectorPlugin).
+pub struct ScreenInspectorPlugin;
+
+impl Plugin for ScreenInspectorPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<ScreenInspector>()
+			.add_systems(Update, toggle_inspector)
+			.add_systems(
+				PostUpdate,
+				draw_inspector
+					.before(EguiSet::ProcessOutput)
+					.run_if(|inspector: Res<ScreenInspector>| inspector.visible),
+			);
+	}
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct ScreenInspector {
+	pub visible: bool,
+}
+
+impl Default for ScreenInspector {
+	fn default() -> Self {
+		Self { visible: true }
+	}
+}
+
+fn toggle_inspector(keys: Res<Input<KeyCode>>, mut inspector: ResMut<ScreenInspector>) {
+	if keys.just_pressed(KeyCode::Escape) {
+		inspector.visible = !inspector.visible;
+	}
+}
+
+fn draw_inspector(world: &mut World) {
+	let Ok(mut egui_context) = world
+		.query_filtered::<&mut EguiContext, With<PrimaryWindow>>()
+		.get_single_mut(world)
+		.map(|context| context.clone())
+	else {
+		return;
+	};
+	let ctx = egui_context.get_mut();
+
+	egui::SidePanel::left("hierarchy")
+		.default_width(200.0)
+		.show(ctx, |ui| {
+			egui::ScrollArea::vertical().show(ui, |ui| {
+				ui.heading("Hierarchy");
+				scene_ui(world, ui);
+				ui.separator();
+				edit_ui(world, ui);
+				hierarchy_ui(world, ui);
+				egui::CollapsingHeader::new("History")
+					.show(ui, |ui| history_ui(world, ui));
+				ui.label("Press escape to toggle UI");
+				ui.allocate_space(ui.available_size());
+			});
+		});
+
+	egui::SidePanel::right("inspector")
+		.default_width(250.0)
+		.show(ctx, |ui| {
+			egui::ScrollArea::vertical().show(ui, |ui| {
+				ui.heading("Inspector");
+				gizmo_ui(world, ui);
+				ui.separator();
+				inspector_ui(world, ui);
+				ui.allocate_space(ui.available_size());
+			});
+		});
+}
@@ -0,0 +1,87 @@
+//! Laser pointers on the VR controllers, for using the inspector panels from inside a
+//! headset. This is the only part of the crate that needs one.
+
+use bevy::prelude::*;
+use bevy_oxr::input::XrInput;
+use bevy_oxr::resources::{XrFrameState, XrInstance, XrSession};
+use bevy_oxr::xr_input::oculus_touch::OculusController;
+use bevy_oxr::xr_input::trackers::OpenXRTrackingRoot;
+use bevy_oxr::xr_input::Hand;
+use openxr_6dof::poses::{XrPoseSet, XrPoses};
+use worldspace_ui::pointer::{PointerSet, PointerSource, WorldspacePointer};
+
+use crate::panel::Grabber;
+
+/// How far the trigger or grip has to be pulled to press or grab, in `[0, 1]`.
+const PULL_THRESHOLD: f32 = 0.5;
+/// How fast a thumbstick pushed all the way scrolls, in points per second.
+const SCROLL_SPEED: f32 = 600.0;
+
+/// Gives each controller a laser [`WorldspacePointer`] along its aim pose, which
+/// presses with the trigger, scrolls with the thumbstick and grabs panels with the
+/// grip. Needs the [`XrPosePlugin`](openxr_6dof::poses::XrPosePlugin).
+pub struct XrInspectorPlugin;
+
+impl Plugin for XrInspectorPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_systems(
+			Update,
+			(setup_lasers, track_lasers.in_set(XrPoseSet::Apply)).chain(),
+		)
+		.add_systems(PreUpdate, controller_input.before(PointerSet));
+	}
+}
+
+/// A laser along the hand's aim pose.
+#[derive(Component, Debug, Clone, Copy)]
+struct Laser(Hand);
+
+/// Poses are relative to the stage, which is the local space of the tracking root.
+fn setup_lasers(
+	mut commands: Commands,
+	roots: Query<Entity, Added<OpenXRTrackingRoot>>,
+) {
+	for root in roots.iter() {
+		for hand in [Hand::Left, Hand::Right] {
+			let laser = commands
+				.spawn((
+					SpatialBundle::default(),
+					Laser(hand),
+					WorldspacePointer::new(PointerSource::Forward),
+					Grabber::default(),
+				))
+				.id();
+			commands.entity(root).add_child(laser);
+		}
+	}
+}
+
+fn track_lasers(poses: Res<XrPoses>, mut lasers: Query<(&mut Transform, &Laser)>) {
+	for (mut transform, laser) in lasers.iter_mut() {
+		if let Some(pose) = poses.aim(laser.0) {
+			*transform = pose;
+		}
+	}
+}
+
+fn controller_input(
+	oculus_controller: Res<OculusController>,
+	frame_state: Res<XrFrameState>,
+	xr_input: Res<XrInput>,
+	instance: Res<XrInstance>,
+	session: Res<XrSession>,
+	time: Res<Time>,
+	mut lasers: Query<(&mut WorldspacePointer, &mut Grabber, &Laser)>,
+) {
+	let frame_state = *frame_state.lock().unwrap();
+	let controller =
+		oculus_controller.get_ref(&instance, &session, &frame_state, &xr_input);
+	for (mut pointer, mut grabber, laser) in lasers.iter_mut() {
+		let hand = laser.0;
+		pointer.pressed = controller.trigger(hand) > PULL_THRESHOLD;
+		grabber.held = controller.squeeze(hand) > PULL_THRESHOLD;
+		let thumbstick = controller.thumbstick(hand);
+		pointer.scroll =
+			Vec2::new(thumbstick.x, thumbstick.y) * SCROLL_SPEED * time.delta_seconds();
+	}
+}
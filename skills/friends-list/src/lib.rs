@@ -0,0 +1,153 @@
+//! A friends list menu, backed by any [`FriendsProvider`].
+//!
+//! The [`Friends`] resource holds the provider and a cached copy of the list, which
+//! is refreshed on a timer. A [`FriendsList`] draws it with egui, either on a
+//! [`WorldspaceUi`] panel that it is on, or in any other egui `Ui` with
+//! [`FriendsList::ui`]. Pressing its buttons sends [`FriendAction`]s.
+
+mod provider;
+mod ui;
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use worldspace_ui::WorldspaceUi;
+
+pub use crate::provider::{
+	Friend, FriendId, FriendsProvider, JsonFriendsProvider, Status, WorldRef,
+};
+pub use crate::ui::FriendsList;
+
+/// Refreshes [`Friends`], draws the [`FriendsList`]s on world-space panels, and
+/// invites and removes friends when asked to with a [`FriendAction`]. Nothing
+/// happens until the app inserts the [`Friends`] resource.
+pub struct FriendsListPlugin;
+
+impl Plugin for FriendsListPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_event::<FriendAction>().add_systems(
+			Update,
+			(refresh_friends, panel_ui, apply_actions)
+				.chain()
+				.run_if(resource_exists::<Friends>()),
+		);
+	}
+}
+
+/// Something to do with a friend, from the buttons of a [`FriendsList`].
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct FriendAction {
+	pub friend: FriendId,
+	pub kind: FriendActionKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FriendActionKind {
+	/// Go to the friend's [`Friend::world`]. Loading worlds is up to the app, so the
+	/// plugin leaves these alone.
+	Join,
+	Invite,
+	Remove,
+}
+
+/// The user's friends, from a [`FriendsProvider`].
+#[derive(Resource)]
+pub struct Friends {
+	provider: Box<dyn FriendsProvider>,
+	/// Sorted by status, then name.
+	list: Vec<Friend>,
+	/// The last thing that went wrong with the provider, shown in the list.
+	error: Option<String>,
+	/// Asks the provider for the list again each time it finishes. Repeats every 5
+	/// seconds by default. The list is also fetched as soon as the resource is added.
+	pub refresh_timer: Timer,
+}
+
+impl Friends {
+	pub fn new(provider: impl FriendsProvider) -> Self {
+		Self {
+			provider: Box::new(provider),
+			list: Vec::new(),
+			error: None,
+			refresh_timer: Timer::new(Duration::from_secs(5), TimerMode::Repeating),
+		}
+	}
+
+	pub fn list(&self) -> &[Friend] {
+		&self.list
+	}
+
+	pub fn get(&self, friend: &FriendId) -> Option<&Friend> {
+		self.list.iter().find(|f| f.id == *friend)
+	}
+
+	pub fn error(&self) -> Option<&str> {
+		self.error.as_deref()
+	}
+
+	pub fn provider_mut(&mut self) -> &mut dyn FriendsProvider {
+		self.provider.as_mut()
+	}
+
+	/// Asks the provider for the list now, rather than waiting for the next refresh.
+	pub fn refresh(&mut self) {
+		match self.provider.friends() {
+			Ok(mut list) => {
+				list.sort_by(|a, b| {
+					(a.status, a.name.to_lowercase())
+						.cmp(&(b.status, b.name.to_lowercase()))
+				});
+				self.list = list;
+				self.error = None;
+			}
+			Err(err) => {
+				warn!("failed to get friends: {err:#}");
+				self.error = Some(format!("{err:#}"));
+			}
+		}
+	}
+}
+
+fn refresh_friends(mut friends: ResMut<Friends>, time: Res<Time>) {
+	let due = friends.refresh_timer.tick(time.delta()).just_finished();
+	if due || friends.is_added() {
+		friends.refresh();
+	}
+}
+
+fn panel_ui(
+	mut panels: Query<(&mut WorldspaceUi, &mut FriendsList)>,
+	friends: Res<Friends>,
+	mut actions: EventWriter<FriendAction>,
+) {
+	for (mut panel, mut list) in panels.iter_mut() {
+		egui::CentralPanel::default().show(panel.ctx_mut(), |ui| {
+			actions.send_batch(list.ui(ui, &friends));
+		});
+	}
+}
+
+fn apply_actions(mut friends: ResMut<Friends>, mut actions: EventReader<FriendAction>) {
+	let mut changed = false;
+	let mut error = None;
+	for action in actions.read() {
+		let result = match action.kind {
+			FriendActionKind::Join => continue,
+			FriendActionKind::Invite => friends.provider.invite(&action.friend),
+			FriendActionKind::Remove => friends.provider.remove(&action.friend),
+		};
+		if let Err(err) = result {
+			warn!("failed to {:?} {}: {err:#}", action.kind, action.friend.0);
+			error = Some(format!("{err:#}"));
+		}
+		changed = true;
+	}
+	if changed {
+		friends.refresh();
+	}
+	// Refreshing clears errors, but this one should still be shown.
+	if error.is_some() {
+		friends.error = error;
+	}
+}
@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use bevy_flycam::FlyCam;
+use color_eyre::Result;
+use friends_list::{
+	Friend, FriendAction, FriendActionKind, Friends, FriendsList, FriendsListPlugin,
+	JsonFriendsProvider,
+};
+use worldspace_ui::pointer::add_mouse_pointer;
+use worldspace_ui::{WorldspaceUi, WorldspaceUiPlugin};
+
+/// Made up friends. The demo keeps them in memory, so removing one doesn't change the
+/// file.
+const FRIENDS: &str = include_str!("../friends.json");
+
+fn main() -> Result<()> {
+	color_eyre::install()?;
+
+	let friends: Vec<Friend> = serde_json::from_str(FRIENDS)?;
+	App::new()
+		.add_plugins(DefaultPlugins)
+		.add_plugins(bevy_flycam::PlayerPlugin)
+		.add_plugins(bevy_egui::EguiPlugin)
+		.add_plugins((WorldspaceUiPlugin, FriendsListPlugin))
+		.insert_resource(Friends::new(JsonFriendsProvider::new(friends)))
+		.add_systems(Startup, setup)
+		.add_systems(
+			Update,
+			(screenspace_ui, join_worlds, add_mouse_pointer::<FlyCam>),
+		)
+		.run();
+
+	Ok(())
+}
+
+fn setup(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+	let panel = WorldspaceUi::menu(&mut images, Vec2::new(0.5, 0.6));
+	commands.spawn((
+		SpatialBundle::from_transform(Transform::from_xyz(0.0, 0.0, -0.5)),
+		panel,
+		FriendsList::default(),
+	));
+}
+
+/// The same list, on the screen.
+fn screenspace_ui(
+	mut contexts: EguiContexts,
+	mut list: Local<FriendsList>,
+	friends: Res<Friends>,
+	mut actions: EventWriter<FriendAction>,
+) {
+	egui::Window::new("Friends").show(contexts.ctx_mut(), |ui| {
+		actions.send_batch(list.ui(ui, &friends));
+	});
+}
+
+fn join_worlds(mut actions: EventReader<FriendAction>, friends: Res<Friends>) {
+	for action in actions.read() {
+		if action.kind != FriendActionKind::Join {
+			continue;
+		}
+		let Some(world) = friends.get(&action.friend).and_then(|f| f.world.as_ref())
+		else {
+			continue;
+		};
+		info!("joining {} in {}", action.friend.0, world.name);
+	}
+}
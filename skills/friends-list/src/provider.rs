@@ -0,0 +1,201 @@
+//! Where the friends list comes from.
+
+use std::path::{Path, PathBuf};
+
+use eyre::{bail, Result, WrapErr};
+use serde::{Deserialize, Serialize};
+
+/// A social backend that knows who the user's friends are, and can invite and remove
+/// them.
+///
+/// [`Friends`](crate::Friends) asks for the whole list every
+/// [`refresh_timer`](crate::Friends::refresh_timer) and after every invite or
+/// removal, on the main thread. A provider backed by a server should keep its own
+/// copy of the list up to date as the server pushes changes, and return that.
+pub trait FriendsProvider: Send + Sync + 'static {
+	fn friends(&self) -> Result<Vec<Friend>>;
+	/// Invites the friend to the world the user is in.
+	fn invite(&mut self, friend: &FriendId) -> Result<()>;
+	fn remove(&mut self, friend: &FriendId) -> Result<()>;
+}
+
+#[derive(
+	Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct FriendId(pub String);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Friend {
+	pub id: FriendId,
+	pub name: String,
+	pub status: Status,
+	/// The world the friend is in, if they are online and it is visible to us.
+	#[serde(default)]
+	pub world: Option<WorldRef>,
+}
+
+/// Ordered from most to least available, which is how the list is sorted.
+#[derive(
+	Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+	Online,
+	Away,
+	Offline,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorldRef {
+	pub id: String,
+	pub name: String,
+}
+
+/// Keeps the friends in memory, optionally loaded from and saved to a JSON file with
+/// an array of [`Friend`]s. Invites go nowhere; they are only recorded in
+/// [`Self::sent_invites`], so a test can check what was sent.
+#[derive(Debug, Default, Clone)]
+pub struct JsonFriendsProvider {
+	friends: Vec<Friend>,
+	/// Saved to after every change, if set.
+	path: Option<PathBuf>,
+	/// Everyone who was invited, oldest first. Nobody actually receives them.
+	pub sent_invites: Vec<FriendId>,
+}
+
+impl JsonFriendsProvider {
+	/// A provider that only lives in memory.
+	pub fn new(friends: Vec<Friend>) -> Self {
+		Self {
+			friends,
+			..Default::default()
+		}
+	}
+
+	/// Loads the friends from `path`, and saves changes back to it.
+	pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+		let path = path.as_ref();
+		let file = std::fs::File::open(path)
+			.wrap_err_with(|| format!("failed to open {}", path.display()))?;
+		let friends = serde_json::from_reader(std::io::BufReader::new(file))
+			.wrap_err_with(|| format!("failed to parse {}", path.display()))?;
+		Ok(Self {
+			friends,
+			path: Some(path.to_owned()),
+			sent_invites: Vec::new(),
+		})
+	}
+
+	fn save(&self) -> Result<()> {
+		let Some(path) = &self.path else {
+			return Ok(());
+		};
+		let json = serde_json::to_string_pretty(&self.friends)?;
+		std::fs::write(path, json)
+			.wrap_err_with(|| format!("failed to save {}", path.display()))
+	}
+}
+
+impl FriendsProvider for JsonFriendsProvider {
+	fn friends(&self) -> Result<Vec<Friend>> {
+		Ok(self.friends.clone())
+	}
+
+	fn invite(&mut self, friend: &FriendId) -> Result<()> {
+		if !self.friends.iter().any(|f| f.id == *friend) {
+			bail!("{} is not a friend", friend.0);
+		}
+		self.sent_invites.push(friend.clone());
+		Ok(())
+	}
+
+	fn remove(&mut self, friend: &FriendId) -> Result<()> {
+		let Some(index) = self.friends.iter().position(|f| f.id == *friend) else {
+			bail!("{} is not a friend", friend.0);
+		};
+		let removed = self.friends.remove(index);
+		// Keep the friend if the file couldn't be changed, so it still matches.
+		if let Err(err) = self.save() {
+			self.friends.insert(index, removed);
+			return Err(err);
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn friend(id: &str, name: &str, status: Status) -> Friend {
+		Friend {
+			id: FriendId(id.to_owned()),
+			name: name.to_owned(),
+			status,
+			world: None,
+		}
+	}
+
+	fn temp_path(name: &str) -> PathBuf {
+		std::env::temp_dir().join(format!("friends-list-{}-{name}", std::process::id()))
+	}
+
+	#[test]
+	fn refresh_sorts_by_status_then_name() {
+		let mut friends = crate::Friends::new(JsonFriendsProvider::new(vec![
+			friend("1", "zoe", Status::Offline),
+			friend("2", "bob", Status::Away),
+			friend("3", "Carol", Status::Online),
+			friend("4", "alice", Status::Online),
+			friend("5", "Adam", Status::Offline),
+		]));
+		friends.refresh();
+		let names: Vec<&str> = friends.list().iter().map(|f| f.name.as_str()).collect();
+		assert_eq!(names, ["alice", "Carol", "bob", "Adam", "zoe"]);
+	}
+
+	#[test]
+	fn unknown_friends_are_errors() {
+		let mut provider =
+			JsonFriendsProvider::new(vec![friend("1", "alice", Status::Online)]);
+		let stranger = FriendId("2".to_owned());
+		assert!(provider.invite(&stranger).is_err());
+		assert!(provider.remove(&stranger).is_err());
+		assert!(provider.sent_invites.is_empty());
+		assert_eq!(provider.friends().unwrap().len(), 1);
+	}
+
+	#[test]
+	fn saves_changes_to_the_file() {
+		let path = temp_path("round-trip.json");
+		let friends = vec![
+			friend("1", "alice", Status::Online),
+			friend("2", "bob", Status::Away),
+		];
+		std::fs::write(&path, serde_json::to_string(&friends).unwrap()).unwrap();
+
+		let mut provider = JsonFriendsProvider::load(&path).unwrap();
+		assert_eq!(provider.friends().unwrap(), friends);
+		provider.remove(&FriendId("1".to_owned())).unwrap();
+		let reloaded = JsonFriendsProvider::load(&path).unwrap();
+		std::fs::remove_file(&path).unwrap();
+		assert_eq!(reloaded.friends().unwrap(), friends[1..]);
+	}
+
+	#[test]
+	fn failed_save_keeps_the_friend() {
+		// A directory can't be written to like a file.
+		let path = temp_path("not-a-file");
+		std::fs::create_dir_all(&path).unwrap();
+		let friends = vec![friend("1", "alice", Status::Online)];
+		let mut provider = JsonFriendsProvider {
+			path: Some(path.clone()),
+			..JsonFriendsProvider::new(friends.clone())
+		};
+		let result = provider.remove(&FriendId("1".to_owned()));
+		std::fs::remove_dir(&path).unwrap();
+		assert!(result.is_err());
+		assert_eq!(provider.friends().unwrap(), friends);
+	}
+}
@@ -0,0 +1,117 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::{FriendAction, FriendActionKind, FriendId, Friends, Status};
+
+/// The state of one friends list menu: its name filter, and which friend is waiting
+/// for the remove button to be pressed again. Put it next to a
+/// [`WorldspaceUi`](worldspace_ui::WorldspaceUi) to fill the panel with the list, or
+/// keep it anywhere and call [`Self::ui`] inside another egui window.
+#[derive(Component, Debug, Default, Clone)]
+pub struct FriendsList {
+	/// Only friends whose names contain this are shown, ignoring case.
+	pub filter: String,
+	/// Removing a friend has to be pressed twice.
+	confirm_remove: Option<FriendId>,
+}
+
+impl FriendsList {
+	/// Draws the list, returning what its buttons asked for.
+	pub fn ui(&mut self, ui: &mut egui::Ui, friends: &Friends) -> Vec<FriendAction> {
+		let mut actions = Vec::new();
+		let online = friends
+			.list()
+			.iter()
+			.filter(|f| f.status != Status::Offline)
+			.count();
+		ui.horizontal(|ui| {
+			ui.heading("Friends");
+			ui.label(format!("{online} online"));
+		});
+		ui.horizontal(|ui| {
+			ui.label("Search:");
+			ui.text_edit_singleline(&mut self.filter);
+		});
+		if let Some(error) = friends.error() {
+			ui.colored_label(ui.visuals().error_fg_color, error);
+		}
+		ui.separator();
+
+		let filter = self.filter.to_lowercase();
+		egui::ScrollArea::vertical().show(ui, |ui| {
+			for friend in friends.list() {
+				if !friend.name.to_lowercase().contains(&filter) {
+					continue;
+				}
+				let mut act = |kind| {
+					actions.push(FriendAction {
+						friend: friend.id.clone(),
+						kind,
+					})
+				};
+				ui.horizontal(|ui| {
+					let (dot, _) = ui.allocate_exact_size(
+						egui::Vec2::splat(10.0),
+						egui::Sense::hover(),
+					);
+					ui.painter().circle_filled(
+						dot.center(),
+						4.0,
+						status_color(friend.status),
+					);
+					ui.vertical(|ui| {
+						ui.strong(&friend.name);
+						let whereabouts = match (&friend.world, friend.status) {
+							(Some(world), Status::Online | Status::Away) => {
+								format!("In {}", world.name)
+							}
+							(_, status) => format!("{status:?}"),
+						};
+						ui.small(whereabouts);
+					});
+					ui.with_layout(
+						egui::Layout::right_to_left(egui::Align::Center),
+						|ui| {
+							if self.confirm_remove.as_ref() == Some(&friend.id) {
+								if ui.button("Cancel").clicked() {
+									self.confirm_remove = None;
+								}
+								if ui.button("Really remove?").clicked() {
+									self.confirm_remove = None;
+									act(FriendActionKind::Remove);
+								}
+								return;
+							}
+							if ui.button("Remove").clicked() {
+								self.confirm_remove = Some(friend.id.clone());
+							}
+							let online = friend.status != Status::Offline;
+							if ui
+								.add_enabled(online, egui::Button::new("Invite"))
+								.clicked()
+							{
+								act(FriendActionKind::Invite);
+							}
+							let joinable = online && friend.world.is_some();
+							if ui
+								.add_enabled(joinable, egui::Button::new("Join"))
+								.clicked()
+							{
+								act(FriendActionKind::Join);
+							}
+						},
+					);
+				});
+			}
+		});
+		actions
+	}
+}
+
+fn status_color(status: Status) -> egui::Color32 {
+	match status {
+		Status::Online => egui::Color32::from_rgb(80, 200, 120),
+		Status::Away => egui::Color32::from_rgb(230, 180, 60),
+		Status::Offline => egui::Color32::GRAY,
+	}
+}
@@ -0,0 +1,252 @@
+//! A world-space panel on the wrist, that shows up when the user turns their palm
+//! towards their face.
+//!
+//! The plugin attaches each [`HandMenu`] to the [`HandMenuAnchor`] of its hand, and
+//! shows or hides it based on where the [`HandMenuViewer`] is and where it is looking.
+//! Apart from the [`xr`] module, nothing in here talks to OpenXR:
+//! [`xr::XrHandMenuPlugin`] tracks the head and controllers and gives the hands
+//! pointers, but the anchors can just as well be moved by something else.
+
+pub mod xr;
+
+use bevy::prelude::*;
+use bevy_oxr::xr_input::Hand;
+use worldspace_ui::WorldspaceUi;
+
+pub struct HandMenuPlugin;
+
+impl Plugin for HandMenuPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<HandMenuSettings>().add_systems(
+			Update,
+			(attach_menus, show_menus).chain().in_set(HandMenuSet),
+		);
+	}
+}
+
+/// Attaches and shows or hides the [`HandMenu`]s. Systems that move the anchors or
+/// the viewer should run before this set.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HandMenuSet;
+
+/// The entity whose [`GlobalTransform`] is the user's head.
+#[derive(Component, Debug, Default)]
+pub struct HandMenuViewer;
+
+/// An entity whose [`GlobalTransform`] is the grip pose of a hand, which menus on that
+/// hand are attached to. Like OpenXR's grip pose, -Z points along the controller and
+/// the palm faces +X on the left hand and -X on the right.
+#[derive(Component, Debug)]
+pub struct HandMenuAnchor(pub Hand);
+
+/// Attaches the entity's [`WorldspaceUi`] panel to a hand, and only shows it while the
+/// palm faces the viewer and the viewer looks at it.
+#[derive(Component, Debug, Clone)]
+pub struct HandMenu {
+	pub hand: Hand,
+	/// Where the panel is relative to the hand's [`HandMenuAnchor`].
+	pub offset: Transform,
+	/// The direction out of the palm, relative to the anchor.
+	pub palm_normal: Vec3,
+	/// Whether the menu is showing. Changes with the hand, so there is no point
+	/// setting it.
+	pub shown: bool,
+}
+
+impl HandMenu {
+	/// A menu floating just off the palm, near the wrist, facing out of it.
+	pub fn new(hand: Hand) -> Self {
+		let palm_normal = match hand {
+			Hand::Left => Vec3::X,
+			Hand::Right => Vec3::NEG_X,
+		};
+		Self {
+			hand,
+			offset: Transform::from_translation(palm_normal * 0.06 + Vec3::Z * 0.08)
+				.with_rotation(Quat::from_rotation_arc(Vec3::Z, palm_normal)),
+			palm_normal,
+			shown: false,
+		}
+	}
+}
+
+/// When [`HandMenu`]s show and hide. Each hides at a bigger angle than it shows at,
+/// so that a menu doesn't flicker when the hand is held right at the limit.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct HandMenuSettings {
+	/// How far from the viewer's eyes the palm can point when the menu appears, in
+	/// radians.
+	pub show_palm_angle: f32,
+	/// How far from the viewer's eyes the palm can point before the menu hides.
+	pub hide_palm_angle: f32,
+	/// How far from where the viewer looks the menu can be when it appears, in
+	/// radians.
+	pub show_gaze_angle: f32,
+	/// How far from where the viewer looks the menu can be before it hides.
+	pub hide_gaze_angle: f32,
+}
+
+impl Default for HandMenuSettings {
+	fn default() -> Self {
+		Self {
+			show_palm_angle: 40_f32.to_radians(),
+			hide_palm_angle: 60_f32.to_radians(),
+			show_gaze_angle: 30_f32.to_radians(),
+			hide_gaze_angle: 45_f32.to_radians(),
+		}
+	}
+}
+
+/// Makes each menu a child of its hand's anchor, so that it follows the hand.
+fn attach_menus(
+	mut commands: Commands,
+	mut menus: Query<(Entity, &HandMenu, &mut Transform, Option<&Parent>)>,
+	anchors: Query<(Entity, &HandMenuAnchor)>,
+) {
+	for (entity, menu, mut transform, parent) in menus.iter_mut() {
+		let Some((anchor, _)) =
+			anchors.iter().find(|(_, anchor)| anchor.0 == menu.hand)
+		else {
+			continue;
+		};
+		if parent.map(Parent::get) != Some(anchor) {
+			commands.entity(anchor).add_child(entity);
+		}
+		if *transform != menu.offset {
+			*transform = menu.offset;
+		}
+	}
+}
+
+fn show_menus(
+	settings: Res<HandMenuSettings>,
+	viewers: Query<&GlobalTransform, With<HandMenuViewer>>,
+	anchors: Query<(&HandMenuAnchor, &GlobalTransform)>,
+	mut menus: Query<(&mut HandMenu, &mut Visibility), With<WorldspaceUi>>,
+) {
+	let Ok(viewer) = viewers.get_single() else {
+		return;
+	};
+	let eye = viewer.translation();
+	for (mut menu, mut visibility) in menus.iter_mut() {
+		let anchor = anchors
+			.iter()
+			.find(|(anchor, _)| anchor.0 == menu.hand)
+			.map(|(_, transform)| transform);
+		let shown = anchor.is_some_and(|anchor| {
+			let position = anchor.transform_point(menu.offset.translation);
+			let to_eye = eye - position;
+			let palm = anchor.affine().transform_vector3(menu.palm_normal);
+			let palm_angle = palm.angle_between(to_eye);
+			let gaze_angle = viewer.forward().angle_between(-to_eye);
+			if menu.shown {
+				palm_angle < settings.hide_palm_angle
+					&& gaze_angle < settings.hide_gaze_angle
+			} else {
+				palm_angle < settings.show_palm_angle
+					&& gaze_angle < settings.show_gaze_angle
+			}
+		});
+		if menu.shown != shown {
+			menu.shown = shown;
+		}
+		let wanted = if shown {
+			Visibility::Inherited
+		} else {
+			Visibility::Hidden
+		};
+		if *visibility != wanted {
+			*visibility = wanted;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// How far in front of the viewer the hand is held.
+	const DISTANCE: f32 = 0.4;
+
+	/// A viewer at the origin looking down -Z, a left hand anchor, and a menu on it
+	/// whose palm faces +Z of the anchor.
+	fn menu_app() -> (App, Entity, Entity) {
+		let mut app = App::new();
+		app.add_plugins(MinimalPlugins)
+			.init_resource::<HandMenuSettings>()
+			.add_systems(Update, show_menus);
+		app.world.spawn((GlobalTransform::IDENTITY, HandMenuViewer));
+		let anchor = app
+			.world
+			.spawn((GlobalTransform::IDENTITY, HandMenuAnchor(Hand::Left)))
+			.id();
+		let mut images = Assets::<Image>::default();
+		let menu = HandMenu {
+			hand: Hand::Left,
+			offset: Transform::IDENTITY,
+			palm_normal: Vec3::Z,
+			shown: false,
+		};
+		let menu = app
+			.world
+			.spawn((
+				WorldspaceUi::new(&mut images, Vec2::new(0.12, 0.09)),
+				menu,
+				Visibility::Hidden,
+			))
+			.id();
+		(app, anchor, menu)
+	}
+
+	/// Holds the hand `gaze` degrees to the right of where the viewer looks, with
+	/// the palm turned `palm` degrees away from the viewer, and returns whether the
+	/// menu shows.
+	fn hold(app: &mut App, anchor: Entity, menu: Entity, gaze: f32, palm: f32) -> bool {
+		let gaze = gaze.to_radians();
+		let position = Vec3::new(gaze.sin(), 0.0, -gaze.cos()) * DISTANCE;
+		let facing_eye = Quat::from_rotation_arc(Vec3::Z, -position.normalize());
+		let rotation = facing_eye * Quat::from_rotation_x(palm.to_radians());
+		*app.world.get_mut::<GlobalTransform>(anchor).unwrap() = GlobalTransform::from(
+			Transform::from_translation(position).with_rotation(rotation),
+		);
+		app.update();
+		let shown = app.world.get::<HandMenu>(menu).unwrap().shown;
+		let visibility = *app.world.get::<Visibility>(menu).unwrap();
+		assert_eq!(visibility == Visibility::Inherited, shown);
+		shown
+	}
+
+	#[test]
+	fn palm_angle_has_hysteresis() {
+		let (mut app, anchor, menu) = menu_app();
+		// Between the show and hide angles, it depends on whether it was showing.
+		assert!(!hold(&mut app, anchor, menu, 0.0, 50.0));
+		assert!(hold(&mut app, anchor, menu, 0.0, 30.0));
+		assert!(hold(&mut app, anchor, menu, 0.0, 50.0));
+		assert!(!hold(&mut app, anchor, menu, 0.0, 70.0));
+		assert!(!hold(&mut app, anchor, menu, 0.0, 50.0));
+	}
+
+	#[test]
+	fn gaze_angle_has_hysteresis() {
+		let (mut app, anchor, menu) = menu_app();
+		assert!(!hold(&mut app, anchor, menu, 40.0, 0.0));
+		assert!(hold(&mut app, anchor, menu, 20.0, 0.0));
+		assert!(hold(&mut app, anchor, menu, 40.0, 0.0));
+		assert!(!hold(&mut app, anchor, menu, 50.0, 0.0));
+		assert!(!hold(&mut app, anchor, menu, 40.0, 0.0));
+	}
+
+	#[test]
+	fn hides_without_an_anchor() {
+		let (mut app, anchor, menu) = menu_app();
+		assert!(hold(&mut app, anchor, menu, 0.0, 0.0));
+		app.world.entity_mut(anchor).remove::<HandMenuAnchor>();
+		app.update();
+		assert!(!app.world.get::<HandMenu>(menu).unwrap().shown);
+		assert_eq!(
+			*app.world.get::<Visibility>(menu).unwrap(),
+			Visibility::Hidden
+		);
+	}
+}
@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use bevy_oxr::xr_input::Hand;
+use bevy_oxr::DefaultXrPlugins;
+use hand_menu::xr::XrHandMenuPlugin;
+use hand_menu::{HandMenu, HandMenuPlugin};
+use openxr_6dof::poses::XrPosePlugin;
+use worldspace_ui::{WorldspaceUi, WorldspaceUiPlugin};
+
+fn main() {
+	color_eyre::install().unwrap();
+
+	info!("Running `hand-menu` skill");
+	App::new()
+		.add_plugins(DefaultXrPlugins)
+		.add_plugins(XrPosePlugin)
+		.add_plugins((WorldspaceUiPlugin, HandMenuPlugin, XrHandMenuPlugin))
+		.add_systems(Startup, setup)
+		.add_systems(Update, menu_ui)
+		.run();
+}
+
+/// The cube that the menu changes.
+#[derive(Component)]
+struct Cube;
+
+fn setup(
+	mut commands: Commands,
+	mut meshes: ResMut<Assets<Mesh>>,
+	mut materials: ResMut<Assets<StandardMaterial>>,
+	mut images: ResMut<Assets<Image>>,
+) {
+	// the menu, on the left wrist
+	let mut panel = WorldspaceUi::menu(&mut images, Vec2::new(0.12, 0.09));
+	panel.corner_radius = 0.01;
+	commands.spawn((SpatialBundle::default(), panel, HandMenu::new(Hand::Left)));
+	// cube
+	commands.spawn((
+		PbrBundle {
+			mesh: meshes.add(shape::Cube::new(0.3).into()),
+			material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
+			transform: Transform::from_xyz(0.0, 1.0, -1.0),
+			..default()
+		},
+		Cube,
+	));
+	// floor
+	commands.spawn(PbrBundle {
+		mesh: meshes.add(shape::Plane::from_size(5.0).into()),
+		material: materials.add(Color::rgb(0.3, 0.5, 0.3).into()),
+		..default()
+	});
+	// light
+	commands.spawn(PointLightBundle {
+		point_light: PointLight {
+			intensity: 1500.0,
+			shadows_enabled: true,
+			..default()
+		},
+		transform: Transform::from_xyz(4.0, 8.0, 4.0),
+		..default()
+	});
+}
+
+/// Turn the left palm towards your face, and point at the menu with the right
+/// controller.
+fn menu_ui(
+	mut menus: Query<&mut WorldspaceUi, With<HandMenu>>,
+	cubes: Query<&Handle<StandardMaterial>, With<Cube>>,
+	mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+	for mut panel in menus.iter_mut() {
+		egui::CentralPanel::default()
+			.frame(egui::Frame::none().inner_margin(8.0))
+			.show(panel.ctx_mut(), |ui| {
+				ui.heading("Cube");
+				for (name, color) in [
+					("Red", Color::RED),
+					("Green", Color::GREEN),
+					("Blue", Color::BLUE),
+				] {
+					if ui.button(name).clicked() {
+						for material in cubes.iter() {
+							materials.get_mut(material).unwrap().base_color = color;
+						}
+					}
+				}
+			});
+	}
+}
@@ -0,0 +1,101 @@
+//! Drives the hand menus from OpenXR. This is the only part of the crate that needs a
+//! headset.
+
+use bevy::prelude::*;
+use bevy_oxr::input::XrInput;
+use bevy_oxr::resources::{XrFrameState, XrInstance, XrSession};
+use bevy_oxr::xr_input::oculus_touch::OculusController;
+use bevy_oxr::xr_input::trackers::OpenXRTrackingRoot;
+use bevy_oxr::xr_input::Hand;
+use openxr_6dof::poses::{XrPoseSet, XrPoses};
+use worldspace_ui::pointer::{PointerSet, PointerSource, WorldspacePointer};
+
+use crate::{HandMenuAnchor, HandMenuSet, HandMenuViewer};
+
+/// How far the trigger has to be pulled to press a panel, in `[0, 1]`.
+const TRIGGER_THRESHOLD: f32 = 0.5;
+
+/// Tracks the head and both grips under the OpenXR tracking root, and gives each
+/// controller a laser [`WorldspacePointer`] that clicks with its trigger, so a menu on
+/// one hand can be used with the other. Needs the
+/// [`XrPosePlugin`](openxr_6dof::poses::XrPosePlugin).
+pub struct XrHandMenuPlugin;
+
+impl Plugin for XrHandMenuPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_systems(
+			Update,
+			(setup_rig, track_poses.in_set(XrPoseSet::Apply))
+				.chain()
+				.before(HandMenuSet),
+		)
+		.add_systems(PreUpdate, trigger_input.before(PointerSet));
+	}
+}
+
+/// Which of the [`XrPoses`] the entity follows.
+#[derive(Component, Debug, Clone, Copy)]
+enum Tracked {
+	Head,
+	Grip(Hand),
+	/// A controller's aim pose, which its laser points along.
+	Aim(Hand),
+}
+
+/// Poses are relative to the stage, which is the local space of the tracking root.
+fn setup_rig(mut commands: Commands, roots: Query<Entity, Added<OpenXRTrackingRoot>>) {
+	for root in roots.iter() {
+		let head = commands
+			.spawn((SpatialBundle::default(), HandMenuViewer, Tracked::Head))
+			.id();
+		commands.entity(root).add_child(head);
+		for hand in [Hand::Left, Hand::Right] {
+			let grip = commands
+				.spawn((
+					SpatialBundle::default(),
+					HandMenuAnchor(hand),
+					Tracked::Grip(hand),
+				))
+				.id();
+			let aim = commands
+				.spawn((
+					SpatialBundle::default(),
+					Tracked::Aim(hand),
+					WorldspacePointer::new(PointerSource::Forward),
+				))
+				.id();
+			commands.entity(root).push_children(&[grip, aim]);
+		}
+	}
+}
+
+fn track_poses(poses: Res<XrPoses>, mut tracked: Query<(&mut Transform, &Tracked)>) {
+	for (mut transform, tracked) in tracked.iter_mut() {
+		let pose = match *tracked {
+			Tracked::Head => poses.head,
+			Tracked::Grip(hand) => poses.grip(hand),
+			Tracked::Aim(hand) => poses.aim(hand),
+		};
+		if let Some(pose) = pose {
+			*transform = pose;
+		}
+	}
+}
+
+fn trigger_input(
+	oculus_controller: Res<OculusController>,
+	frame_state: Res<XrFrameState>,
+	xr_input: Res<XrInput>,
+	instance: Res<XrInstance>,
+	session: Res<XrSession>,
+	mut pointers: Query<(&mut WorldspacePointer, &Tracked)>,
+) {
+	let frame_state = *frame_state.lock().unwrap();
+	let controller =
+		oculus_controller.get_ref(&instance, &session, &frame_state, &xr_input);
+	for (mut pointer, tracked) in pointers.iter_mut() {
+		if let Tracked::Aim(hand) = *tracked {
+			pointer.pressed = controller.trigger(hand) > TRIGGER_THRESHOLD;
+		}
+	}
+}
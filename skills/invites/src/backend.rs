@@ -0,0 +1,103 @@
+//! How invites get from one user to another.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use bevy::utils::HashMap;
+use eyre::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{Invite, InviteId, UserId};
+
+/// What users send each other.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InviteMessage {
+	Invite(Invite),
+	/// The invitee accepted or declined the invite, sent back to whoever sent it.
+	Response {
+		id: InviteId,
+		to: UserId,
+		accepted: bool,
+	},
+}
+
+impl InviteMessage {
+	/// Who the message is for.
+	pub fn recipient(&self) -> &UserId {
+		match self {
+			Self::Invite(invite) => &invite.to,
+			Self::Response { to, .. } => to,
+		}
+	}
+}
+
+/// Carries [`InviteMessage`]s between users, such as a social server or a relay.
+///
+/// [`Self::receive`] is polled every frame, and [`Self::send`] is called as soon as
+/// the user sends or answers an invite. A backend over a network should queue
+/// outgoing messages for a background task, and keep what arrives in an inbox that
+/// `receive` drains.
+pub trait InviteBackend: Send + Sync + 'static {
+	fn send(&mut self, message: InviteMessage) -> Result<()>;
+	/// The messages for this user that arrived since the last call, oldest first.
+	fn receive(&mut self) -> Result<Vec<InviteMessage>>;
+}
+
+/// Users in the same process, which pass messages to each other in memory, such as
+/// the demo's simulated friend, or both sides of an invite in a test. Messages arrive
+/// on the recipient's next [`InviteBackend::receive`].
+///
+/// Clones share the same users.
+#[derive(Debug, Default, Clone)]
+pub struct LoopbackNetwork {
+	inboxes: Arc<Mutex<HashMap<UserId, VecDeque<InviteMessage>>>>,
+}
+
+impl LoopbackNetwork {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Joins the network as `user`, who can then be sent messages.
+	pub fn connect(&self, user: UserId) -> LoopbackBackend {
+		self.inboxes
+			.lock()
+			.unwrap()
+			.entry(user.clone())
+			.or_default();
+		LoopbackBackend {
+			user,
+			network: self.clone(),
+		}
+	}
+}
+
+/// One user's connection to a [`LoopbackNetwork`].
+#[derive(Debug, Clone)]
+pub struct LoopbackBackend {
+	user: UserId,
+	network: LoopbackNetwork,
+}
+
+impl LoopbackBackend {
+	pub fn user(&self) -> &UserId {
+		&self.user
+	}
+}
+
+impl InviteBackend for LoopbackBackend {
+	fn send(&mut self, message: InviteMessage) -> Result<()> {
+		let mut inboxes = self.network.inboxes.lock().unwrap();
+		let Some(inbox) = inboxes.get_mut(message.recipient()) else {
+			bail!("{} is not connected", message.recipient().0);
+		};
+		inbox.push_back(message);
+		Ok(())
+	}
+
+	fn receive(&mut self) -> Result<Vec<InviteMessage>> {
+		let mut inboxes = self.network.inboxes.lock().unwrap();
+		let inbox = inboxes.entry(self.user.clone()).or_default();
+		Ok(inbox.drain(..).collect())
+	}
+}
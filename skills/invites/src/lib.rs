@@ -0,0 +1,439 @@
+//! Inviting other users to a world, and accepting or declining their invites.
+//!
+//! The [`Invites`] resource is one user's side of it: the invites they have
+//! received and sent that are still waiting for an answer. Invites travel through an
+//! [`InviteBackend`], and expire if they aren't answered in time. Every change is
+//! sent as an [`InviteEvent`].
+//!
+//! Expiry is measured in [`SystemTime`], since invites go between machines. Methods
+//! that need the time take it as `now`, so tests can move the clock along.
+//!
+//! The [`InviteNotificationsPlugin`] shows received invites as notifications from
+//! the `notifications` skill, with buttons to accept or decline them.
+//! [`InvitesPlugin`] doesn't need it, so it runs headless too.
+
+mod backend;
+mod notify;
+
+use std::time::{Duration, SystemTime};
+
+use bevy::prelude::*;
+use eyre::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+pub use crate::backend::{
+	InviteBackend, InviteMessage, LoopbackBackend, LoopbackNetwork,
+};
+pub use crate::notify::InviteNotificationsPlugin;
+
+/// Sends and receives [`Invites`], expires them, and sends [`InviteEvent`]s. Nothing
+/// happens until the app inserts the [`Invites`] resource.
+pub struct InvitesPlugin;
+
+impl Plugin for InvitesPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_event::<InviteEvent>().add_systems(
+			Update,
+			update_invites
+				.in_set(InvitesSet)
+				.run_if(resource_exists::<Invites>()),
+		);
+	}
+}
+
+/// Where [`InviteEvent`]s are sent. Things that answer invites run after it.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InvitesSet;
+
+#[derive(
+	Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct UserId(pub String);
+
+#[derive(
+	Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct InviteId(pub String);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorldRef {
+	pub id: String,
+	pub name: String,
+}
+
+/// An invite from one user to another, to join the world that the sender is in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Invite {
+	pub id: InviteId,
+	pub from: UserId,
+	/// The sender's display name.
+	pub from_name: String,
+	pub to: UserId,
+	pub world: WorldRef,
+	/// After this, the invite can't be accepted.
+	pub expires_at: SystemTime,
+}
+
+impl Invite {
+	/// How long is left to answer the invite.
+	pub fn time_left(&self, now: SystemTime) -> Duration {
+		self.expires_at.duration_since(now).unwrap_or_default()
+	}
+
+	pub fn is_expired(&self, now: SystemTime) -> bool {
+		now >= self.expires_at
+	}
+}
+
+/// Something happened to an invite. For [`InviteEventKind::Accepted`] and
+/// [`InviteEventKind::Declined`], whether the user answered someone else's invite or
+/// someone answered theirs is told by who the invite is from.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct InviteEvent {
+	pub invite: Invite,
+	pub kind: InviteEventKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InviteEventKind {
+	/// The user sent the invite.
+	Sent,
+	/// The user was sent the invite.
+	Received,
+	/// Joining the world is up to the app.
+	Accepted,
+	Declined,
+	/// Nobody answered the invite in time. Sent on both sides.
+	Expired,
+}
+
+/// One user's invites, through an [`InviteBackend`].
+#[derive(Resource)]
+pub struct Invites {
+	backend: Box<dyn InviteBackend>,
+	user: UserId,
+	name: String,
+	/// Invites to the user that haven't been answered, oldest first.
+	received: Vec<Invite>,
+	/// Invites from the user that haven't been answered, oldest first.
+	sent: Vec<Invite>,
+	/// How long invites from the user last.
+	pub expires_after: Duration,
+	/// Counts up, to give each sent invite its own id.
+	next_id: u64,
+	/// Events that haven't been returned by [`Self::update`] yet.
+	events: Vec<InviteEvent>,
+}
+
+impl Invites {
+	/// `name` is shown to the users that are invited.
+	pub fn new(
+		user: UserId,
+		name: impl Into<String>,
+		backend: impl InviteBackend,
+	) -> Self {
+		Self {
+			backend: Box::new(backend),
+			user,
+			name: name.into(),
+			received: Vec::new(),
+			sent: Vec::new(),
+			expires_after: Duration::from_secs(60),
+			next_id: 0,
+			events: Vec::new(),
+		}
+	}
+
+	pub fn user(&self) -> &UserId {
+		&self.user
+	}
+
+	/// Invites to the user that are waiting for an answer, oldest first.
+	pub fn received(&self) -> &[Invite] {
+		&self.received
+	}
+
+	/// Invites from the user that are waiting for an answer, oldest first.
+	pub fn sent(&self) -> &[Invite] {
+		&self.sent
+	}
+
+	pub fn backend_mut(&mut self) -> &mut dyn InviteBackend {
+		self.backend.as_mut()
+	}
+
+	/// Invites `to` to `world`. The invite expires [`Self::expires_after`] from `now`.
+	pub fn send(
+		&mut self,
+		to: UserId,
+		world: WorldRef,
+		now: SystemTime,
+	) -> Result<InviteId> {
+		let millis = now
+			.duration_since(SystemTime::UNIX_EPOCH)
+			.unwrap_or_default()
+			.as_millis();
+		let id = InviteId(format!("{}-{millis}-{}", self.user.0, self.next_id));
+		self.next_id += 1;
+		let invite = Invite {
+			id: id.clone(),
+			from: self.user.clone(),
+			from_name: self.name.clone(),
+			to,
+			world,
+			expires_at: now + self.expires_after,
+		};
+		self.backend.send(InviteMessage::Invite(invite.clone()))?;
+		self.sent.push(invite.clone());
+		self.push_event(invite, InviteEventKind::Sent);
+		Ok(id)
+	}
+
+	pub fn accept(&mut self, id: &InviteId, now: SystemTime) -> Result<()> {
+		self.respond(id, true, now)
+	}
+
+	pub fn decline(&mut self, id: &InviteId, now: SystemTime) -> Result<()> {
+		self.respond(id, false, now)
+	}
+
+	fn respond(
+		&mut self,
+		id: &InviteId,
+		accepted: bool,
+		now: SystemTime,
+	) -> Result<()> {
+		let Some(index) = self.received.iter().position(|invite| &invite.id == id)
+		else {
+			bail!("no such invite");
+		};
+		if self.received[index].is_expired(now) {
+			let invite = self.received.remove(index);
+			self.push_event(invite, InviteEventKind::Expired);
+			bail!("the invite has expired");
+		}
+		self.backend.send(InviteMessage::Response {
+			id: id.clone(),
+			to: self.received[index].from.clone(),
+			accepted,
+		})?;
+		let invite = self.received.remove(index);
+		self.push_event(invite, response_kind(accepted));
+		Ok(())
+	}
+
+	/// Receives new invites and answers, and expires old invites. Returns what
+	/// happened since the last call, including from [`Self::send`], [`Self::accept`]
+	/// and [`Self::decline`]. The plugin calls this every frame, with the system time.
+	pub fn update(&mut self, now: SystemTime) -> Result<Vec<InviteEvent>> {
+		let messages = self.backend.receive();
+		// Expire before handling what was received, so that an invite that has
+		// already expired isn't shown for a frame.
+		self.expire(now);
+		for message in messages? {
+			match message {
+				InviteMessage::Invite(invite) if invite.is_expired(now) => {
+					self.push_event(invite, InviteEventKind::Expired)
+				}
+				InviteMessage::Invite(invite) => {
+					self.received.push(invite.clone());
+					self.push_event(invite, InviteEventKind::Received);
+				}
+				InviteMessage::Response { id, accepted, .. } => {
+					// Answers to invites that already expired here are too late.
+					let Some(index) =
+						self.sent.iter().position(|invite| invite.id == id)
+					else {
+						continue;
+					};
+					let invite = self.sent.remove(index);
+					self.push_event(invite, response_kind(accepted));
+				}
+			}
+		}
+		Ok(std::mem::take(&mut self.events))
+	}
+
+	fn expire(&mut self, now: SystemTime) {
+		let mut expired = Vec::new();
+		for list in [&mut self.received, &mut self.sent] {
+			let (old, pending): (Vec<_>, _) = std::mem::take(list)
+				.into_iter()
+				.partition(|invite| invite.is_expired(now));
+			*list = pending;
+			expired.extend(old);
+		}
+		for invite in expired {
+			self.push_event(invite, InviteEventKind::Expired);
+		}
+	}
+
+	fn push_event(&mut self, invite: Invite, kind: InviteEventKind) {
+		self.events.push(InviteEvent { invite, kind });
+	}
+}
+
+fn response_kind(accepted: bool) -> InviteEventKind {
+	if accepted {
+		InviteEventKind::Accepted
+	} else {
+		InviteEventKind::Declined
+	}
+}
+
+fn update_invites(mut invites: ResMut<Invites>, mut events: EventWriter<InviteEvent>) {
+	match invites.update(SystemTime::now()) {
+		Ok(new_events) => events.send_batch(new_events),
+		Err(err) => warn!("failed to receive invites: {err:#}"),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use InviteEventKind::*;
+
+	fn user(id: &str) -> UserId {
+		UserId(id.to_owned())
+	}
+
+	fn grove() -> WorldRef {
+		WorldRef {
+			id: "grove".to_owned(),
+			name: "The Grove".to_owned(),
+		}
+	}
+
+	/// The kinds of [`InviteEvent`]s since the last [`step`].
+	#[derive(Resource, Default)]
+	struct Log(Vec<InviteEventKind>);
+
+	fn headless_app(network: &LoopbackNetwork, id: &str) -> App {
+		let mut app = App::new();
+		app.add_plugins((MinimalPlugins, InvitesPlugin))
+			.insert_resource(Invites::new(user(id), id, network.connect(user(id))))
+			.init_resource::<Log>()
+			.add_systems(Update, record_events.after(InvitesSet));
+		app
+	}
+
+	fn record_events(mut events: EventReader<InviteEvent>, mut log: ResMut<Log>) {
+		log.0.extend(events.read().map(|event| event.kind));
+	}
+
+	/// Lets messages get to the other user and the answers back, and returns what
+	/// each user saw.
+	fn step(
+		alice: &mut App,
+		bob: &mut App,
+	) -> (Vec<InviteEventKind>, Vec<InviteEventKind>) {
+		alice.update();
+		bob.update();
+		alice.update();
+		(take_log(alice), take_log(bob))
+	}
+
+	fn take_log(app: &mut App) -> Vec<InviteEventKind> {
+		std::mem::take(&mut app.world.resource_mut::<Log>().0)
+	}
+
+	fn invites(app: &mut App) -> Mut<'_, Invites> {
+		app.world.resource_mut::<Invites>()
+	}
+
+	fn kinds(events: Result<Vec<InviteEvent>>) -> Vec<InviteEventKind> {
+		events
+			.unwrap()
+			.into_iter()
+			.map(|event| event.kind)
+			.collect()
+	}
+
+	#[test]
+	fn accepted_invites_reach_both_users() {
+		let network = LoopbackNetwork::new();
+		let (mut alice, mut bob) = (
+			headless_app(&network, "alice"),
+			headless_app(&network, "bob"),
+		);
+		let now = SystemTime::now();
+		let id = invites(&mut alice).send(user("bob"), grove(), now).unwrap();
+		assert_eq!(step(&mut alice, &mut bob), (vec![Sent], vec![Received]));
+		assert_eq!(invites(&mut bob).received()[0].from_name, "alice");
+
+		invites(&mut bob).accept(&id, now).unwrap();
+		assert_eq!(step(&mut alice, &mut bob), (vec![Accepted], vec![Accepted]));
+		assert!(invites(&mut alice).sent().is_empty());
+		assert!(invites(&mut bob).received().is_empty());
+	}
+
+	#[test]
+	fn declined_invites_reach_both_users() {
+		let network = LoopbackNetwork::new();
+		let (mut alice, mut bob) = (
+			headless_app(&network, "alice"),
+			headless_app(&network, "bob"),
+		);
+		let now = SystemTime::now();
+		let id = invites(&mut bob).send(user("alice"), grove(), now).unwrap();
+		step(&mut alice, &mut bob);
+		invites(&mut alice).decline(&id, now).unwrap();
+		assert_eq!(step(&mut alice, &mut bob), (vec![Declined], vec![Declined]));
+		assert!(invites(&mut alice).decline(&id, now).is_err());
+	}
+
+	#[test]
+	fn unanswered_invites_expire_on_both_sides() {
+		let network = LoopbackNetwork::new();
+		let mut alice =
+			Invites::new(user("alice"), "Alice", network.connect(user("alice")));
+		let mut bob = Invites::new(user("bob"), "Bob", network.connect(user("bob")));
+		let sent_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+		let id = alice.send(user("bob"), grove(), sent_at).unwrap();
+		assert_eq!(kinds(alice.update(sent_at)), [Sent]);
+		assert_eq!(kinds(bob.update(sent_at)), [Received]);
+
+		let almost = sent_at + alice.expires_after - Duration::from_millis(1);
+		assert_eq!(
+			bob.received()[0].time_left(almost),
+			Duration::from_millis(1)
+		);
+		assert_eq!(kinds(alice.update(almost)), []);
+
+		let expired = sent_at + alice.expires_after;
+		assert!(bob.accept(&id, expired).is_err());
+		assert_eq!(kinds(bob.update(expired)), [Expired]);
+		assert_eq!(kinds(alice.update(expired)), [Expired]);
+		assert!(alice.sent().is_empty() && bob.received().is_empty());
+	}
+
+	#[test]
+	fn late_answers_are_ignored() {
+		let network = LoopbackNetwork::new();
+		let mut alice =
+			Invites::new(user("alice"), "Alice", network.connect(user("alice")));
+		let mut bob = Invites::new(user("bob"), "Bob", network.connect(user("bob")));
+		let sent_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+		let id = alice.send(user("bob"), grove(), sent_at).unwrap();
+		alice.update(sent_at).unwrap();
+		bob.update(sent_at).unwrap();
+		// Bob's clock is behind, so he answers after Alice has given up.
+		bob.accept(&id, sent_at).unwrap();
+		let expired = sent_at + alice.expires_after;
+		assert_eq!(kinds(alice.update(expired)), [Expired]);
+	}
+
+	#[test]
+	fn invites_that_arrive_expired_are_not_received() {
+		let network = LoopbackNetwork::new();
+		let mut alice =
+			Invites::new(user("alice"), "Alice", network.connect(user("alice")));
+		let mut bob = Invites::new(user("bob"), "Bob", network.connect(user("bob")));
+		let sent_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+		alice.send(user("bob"), grove(), sent_at).unwrap();
+		assert_eq!(kinds(bob.update(sent_at + alice.expires_after)), [Expired]);
+		assert!(bob.received().is_empty());
+	}
+}
@@ -0,0 +1,135 @@
+use std::time::SystemTime;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use bevy_flycam::FlyCam;
+use color_eyre::Result;
+use invites::{
+	InviteEvent, InviteEventKind, InviteNotificationsPlugin, Invites, InvitesPlugin,
+	InvitesSet, LoopbackNetwork, UserId, WorldRef,
+};
+use notifications::panel::{HeadLocked, NotificationPanel, PanelNotificationsPlugin};
+use notifications::screen::ScreenNotificationsPlugin;
+use notifications::NotificationsPlugin;
+use worldspace_ui::pointer::add_mouse_pointer;
+use worldspace_ui::{WorldspaceUi, WorldspaceUiPlugin};
+
+fn main() -> Result<()> {
+	color_eyre::install()?;
+
+	// Sam is simulated in the same app, on the same loopback network as you.
+	let network = LoopbackNetwork::new();
+	let you = Invites::new(user("you"), "You", network.connect(user("you")));
+	let sam = Invites::new(user("sam"), "Sam", network.connect(user("sam")));
+	App::new()
+		.add_plugins(DefaultPlugins)
+		.add_plugins(bevy_flycam::PlayerPlugin)
+		.add_plugins(bevy_egui::EguiPlugin)
+		.add_plugins((
+			WorldspaceUiPlugin,
+			InvitesPlugin,
+			NotificationsPlugin,
+			ScreenNotificationsPlugin,
+			PanelNotificationsPlugin,
+			InviteNotificationsPlugin,
+		))
+		.insert_resource(you)
+		.insert_resource(Sam {
+			invites: sam,
+			timer: Timer::from_seconds(10.0, TimerMode::Repeating),
+		})
+		.add_systems(
+			Update,
+			(
+				(screenspace_ui, simulate_sam).before(InvitesSet),
+				log_events.after(InvitesSet),
+				add_notification_panel,
+				add_mouse_pointer::<FlyCam>,
+			),
+		)
+		.run();
+
+	Ok(())
+}
+
+fn user(id: &str) -> UserId {
+	UserId(id.to_owned())
+}
+
+fn grove() -> WorldRef {
+	WorldRef {
+		id: "grove".to_owned(),
+		name: "The Grove".to_owned(),
+	}
+}
+
+/// The simulated friend, who invites you every so often and accepts your invites.
+#[derive(Resource)]
+struct Sam {
+	invites: Invites,
+	timer: Timer,
+}
+
+fn simulate_sam(mut sam: ResMut<Sam>, time: Res<Time>) {
+	let result = sam.invites.update(SystemTime::now()).and_then(|events| {
+		for event in events {
+			if event.kind == InviteEventKind::Received {
+				sam.invites.accept(&event.invite.id, SystemTime::now())?;
+			}
+		}
+		if sam.timer.tick(time.delta()).just_finished() {
+			sam.invites.send(user("you"), grove(), SystemTime::now())?;
+		}
+		Ok(())
+	});
+	if let Err(err) = result {
+		warn!("Sam: {err:#}");
+	}
+}
+
+/// Sends invites to Sam, and lists the ones that haven't been answered.
+fn screenspace_ui(mut contexts: EguiContexts, mut invites: ResMut<Invites>) {
+	egui::Window::new("Invites").show(contexts.ctx_mut(), |ui| {
+		if ui.button("Invite Sam to The Grove").clicked() {
+			if let Err(err) = invites.send(user("sam"), grove(), SystemTime::now()) {
+				warn!("failed to invite Sam: {err:#}");
+			}
+		}
+		for invite in invites.sent() {
+			ui.label(format!(
+				"Waiting for {} ({}s)",
+				invite.to.0,
+				invite.time_left(SystemTime::now()).as_secs()
+			));
+		}
+	});
+}
+
+fn log_events(mut events: EventReader<InviteEvent>, invites: Res<Invites>) {
+	for InviteEvent { invite, kind } in events.read() {
+		if invite.from == *invites.user() {
+			info!("{kind:?} your invite to {}", invite.to.0);
+		} else {
+			info!(
+				"{kind:?} {}'s invite to {}",
+				invite.from_name, invite.world.name
+			);
+		}
+	}
+}
+
+/// Puts a notification panel that follows the camera below the middle of the view.
+fn add_notification_panel(
+	mut commands: Commands,
+	cameras: Query<Entity, Added<FlyCam>>,
+	mut images: ResMut<Assets<Image>>,
+) {
+	for camera in cameras.iter() {
+		commands.spawn((
+			SpatialBundle::default(),
+			WorldspaceUi::new(&mut images, Vec2::new(0.3, 0.35)),
+			NotificationPanel::default(),
+			HeadLocked::new(camera),
+		));
+	}
+}
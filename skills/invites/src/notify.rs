@@ -0,0 +1,101 @@
+//! Invites shown as [`Notifications`], with buttons to accept or decline them.
+
+use std::time::SystemTime;
+
+use bevy::prelude::*;
+use notifications::{ActionPressed, Notification, Notifications, NotificationsSet};
+
+use crate::{InviteEvent, InviteEventKind, InviteId, Invites, InvitesSet};
+
+const ACCEPT: &str = "accept";
+const DECLINE: &str = "decline";
+/// Starts the [`Notification::key`] of notifications about invites, followed by the
+/// invite's id.
+const KEY_PREFIX: &str = "invite:";
+
+/// Notifies the user of received invites, which can be accepted or declined from the
+/// notification, and of the answers to their own. Needs the `NotificationsPlugin`,
+/// and something that shows the notifications.
+pub struct InviteNotificationsPlugin;
+
+impl Plugin for InviteNotificationsPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_systems(
+			Update,
+			(notify, answer)
+				.chain()
+				.after(InvitesSet)
+				.after(NotificationsSet)
+				.run_if(resource_exists::<Invites>()),
+		);
+	}
+}
+
+fn key(id: &InviteId) -> String {
+	format!("{KEY_PREFIX}{}", id.0)
+}
+
+fn notify(
+	mut events: EventReader<InviteEvent>,
+	invites: Res<Invites>,
+	mut notifications: ResMut<Notifications>,
+) {
+	for InviteEvent { invite, kind } in events.read() {
+		let key = key(&invite.id);
+		if invite.from != *invites.user() {
+			if *kind == InviteEventKind::Received {
+				notifications.push(
+					Notification::info(format!("{} invited you", invite.from_name))
+						.with_body(format!("to {}", invite.world.name))
+						.with_duration(invite.time_left(SystemTime::now()))
+						.with_key(key)
+						.with_action(ACCEPT, "Accept")
+						.with_action(DECLINE, "Decline"),
+				);
+			} else {
+				// Answered, maybe from somewhere else, or expired.
+				notifications.dismiss_key(&key);
+			}
+			continue;
+		}
+		let to = &invite.to.0;
+		let notification = match kind {
+			InviteEventKind::Accepted => {
+				Notification::success(format!("{to} accepted your invite"))
+			}
+			InviteEventKind::Declined => {
+				Notification::info(format!("{to} declined your invite"))
+			}
+			InviteEventKind::Expired => {
+				Notification::info(format!("{to} didn't answer your invite"))
+			}
+			InviteEventKind::Sent | InviteEventKind::Received => continue,
+		};
+		notifications.push(notification.with_key(key));
+	}
+}
+
+fn answer(
+	mut pressed: EventReader<ActionPressed>,
+	mut invites: ResMut<Invites>,
+	mut notifications: ResMut<Notifications>,
+) {
+	for ActionPressed { key, action, .. } in pressed.read() {
+		let Some(id) = key.as_deref().and_then(|key| key.strip_prefix(KEY_PREFIX))
+		else {
+			continue;
+		};
+		let id = InviteId(id.to_owned());
+		let result = match action.as_str() {
+			ACCEPT => invites.accept(&id, SystemTime::now()),
+			DECLINE => invites.decline(&id, SystemTime::now()),
+			_ => continue,
+		};
+		if let Err(err) = result {
+			notifications.push(
+				Notification::error("Couldn't answer the invite")
+					.with_body(format!("{err:#}")),
+			);
+		}
+	}
+}
@@ -1,11 +1,14 @@
 use bevy::ecs::system::EntityCommands;
 use bevy::{pbr::DirectionalLightShadowMap, prelude::*};
+use bevy_egui::EguiPlugin;
 use bevy_mod_picking::backends::raycast::RaycastPickable;
 use bevy_mod_picking::events::Drag;
 use bevy_mod_picking::pointer::PointerButton;
 use bevy_mod_picking::prelude::{On, Pointer};
 use bevy_mod_picking::{DefaultPickingPlugins, PickableBundle};
 use color_eyre::eyre::Result;
+use radial_menu::screen::{ScreenRadialMenu, ScreenRadialMenuPlugin, ScreenTrigger};
+use radial_menu::{RadialItem, RadialMenu, RadialMenuPlugin, RadialMenuSelected};
 use tracing::info;
 
 const ASSET_FOLDER: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../assets/");
@@ -22,8 +25,9 @@ fn main() -> Result<()> {
 			..Default::default()
 		}))
 		.add_plugins(DefaultPickingPlugins)
+		.add_plugins((EguiPlugin, RadialMenuPlugin, ScreenRadialMenuPlugin))
 		.add_systems(Startup, setup)
-		.add_systems(Update, animate_light)
+		.add_systems(Update, (animate_light, apply_radial_menu))
 		.run();
 
 	Ok(())
@@ -77,16 +81,33 @@ fn setup(
 	let tree_img: Handle<Image> = assets.load("tree.png");
 
 	// Build cube
-	let mut cube = commands.spawn(PbrBundle {
-		mesh: meshes.add(shape::Cube::default().into()),
-		material: materials.add(StandardMaterial {
-			base_color_texture: Some(tree_img),
+	let mut cube = commands.spawn((
+		PbrBundle {
+			mesh: meshes.add(shape::Cube::default().into()),
+			material: materials.add(StandardMaterial {
+				base_color_texture: Some(tree_img.clone()),
+				..default()
+			}),
 			..default()
-		}),
-		..default()
-	});
+		},
+		Cube,
+	));
 	add_events(&mut cube);
 
+	// Hold tab to open a radial menu under the cursor
+	commands.spawn((
+		RadialMenu::new(vec![
+			RadialItem::new("reset", "Reset").with_icon(tree_img),
+			RadialItem::new("tint", "Tint").with_children(vec![
+				RadialItem::new("white", "White"),
+				RadialItem::new("red", "Red"),
+				RadialItem::new("green", "Green"),
+				RadialItem::new("blue", "Blue"),
+			]),
+		]),
+		ScreenRadialMenu::new(ScreenTrigger::Key(KeyCode::Tab)),
+	));
+
 	// Build the rest of the scene
 	commands.spawn(DirectionalLightBundle {
 		directional_light: DirectionalLight {
@@ -120,6 +141,29 @@ fn setup(
 	});
 }
 
+#[derive(Component)]
+struct Cube;
+
+fn apply_radial_menu(
+	mut selected: EventReader<RadialMenuSelected>,
+	mut cubes: Query<(&mut Transform, &Handle<StandardMaterial>), With<Cube>>,
+	mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+	for selected in selected.read() {
+		for (mut transform, material) in cubes.iter_mut() {
+			let material = materials.get_mut(material).unwrap();
+			match selected.id.as_str() {
+				"reset" => *transform = Transform::default(),
+				"white" => material.base_color = Color::WHITE,
+				"red" => material.base_color = Color::RED,
+				"green" => material.base_color = Color::GREEN,
+				"blue" => material.base_color = Color::BLUE,
+				_ => (),
+			}
+		}
+	}
+}
+
 fn animate_light(mut query: Query<&mut Transform, With<DirectionalLight>>) {
 	for mut t in query.iter_mut() {
 		let t: &mut Transform = &mut t;
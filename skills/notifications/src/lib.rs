@@ -0,0 +1,579 @@
+//! Transient messages for the user, like toasts.
+//!
+//! Anything can [`Notifications::push`] a [`Notification`], with a [`Severity`], an
+//! optional timeout, and optional buttons that send an [`ActionPressed`] event.
+//! Identical notifications stack into one with a count, and pushing too many at
+//! once makes the rest wait their turn. Where they are shown is up to the other
+//! modules:
+//!
+//! - [`screen`] shows them as an egui overlay on a flatscreen, with a history
+//!   window.
+//! - [`panel`] shows them on a [`WorldspaceUi`](worldspace_ui::WorldspaceUi) panel,
+//!   which can follow the head with some lag in VR.
+
+pub mod panel;
+pub mod screen;
+mod ui;
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+pub use crate::ui::{history_ui, notifications_ui, NOTIFICATION_WIDTH};
+
+pub struct NotificationsPlugin;
+
+impl Plugin for NotificationsPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<Notifications>()
+			.add_event::<ActionPressed>()
+			.add_systems(Update, expire_notifications.in_set(NotificationsSet));
+	}
+}
+
+/// Expires [`Notifications`]. Systems that show them should run after this set.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NotificationsSet;
+
+/// Sent when one of a notification's [`Notification::actions`] is pressed, which
+/// also dismisses it.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct ActionPressed {
+	pub notification: NotificationId,
+	/// The notification's [`Notification::key`].
+	pub key: Option<String>,
+	/// The [`NotificationAction::id`] of the button.
+	pub action: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NotificationId(pub u64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Severity {
+	#[default]
+	Info,
+	Success,
+	Warning,
+	Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+	pub severity: Severity,
+	pub title: String,
+	/// More detail, shown under the title if it isn't empty.
+	pub body: String,
+	/// How long it is shown for, or `None` to show it until it is dismissed.
+	pub duration: Option<Duration>,
+	/// Buttons shown on the notification.
+	pub actions: Vec<NotificationAction>,
+	/// What the notification is about, such as an invite's id. Pushing a
+	/// notification with the same key as one that is shown replaces it, and
+	/// [`Notifications::dismiss_key`] dismisses it.
+	pub key: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotificationAction {
+	/// Sent in [`ActionPressed`] when the button is pressed.
+	pub id: String,
+	pub label: String,
+}
+
+impl Notification {
+	/// A notification that is shown for 5 seconds, or 10 for errors.
+	pub fn new(severity: Severity, title: impl Into<String>) -> Self {
+		let seconds = if severity == Severity::Error { 10 } else { 5 };
+		Self {
+			severity,
+			title: title.into(),
+			body: String::new(),
+			duration: Some(Duration::from_secs(seconds)),
+			actions: Vec::new(),
+			key: None,
+		}
+	}
+
+	pub fn info(title: impl Into<String>) -> Self {
+		Self::new(Severity::Info, title)
+	}
+
+	pub fn success(title: impl Into<String>) -> Self {
+		Self::new(Severity::Success, title)
+	}
+
+	pub fn warning(title: impl Into<String>) -> Self {
+		Self::new(Severity::Warning, title)
+	}
+
+	pub fn error(title: impl Into<String>) -> Self {
+		Self::new(Severity::Error, title)
+	}
+
+	pub fn with_body(mut self, body: impl Into<String>) -> Self {
+		self.body = body.into();
+		self
+	}
+
+	pub fn with_duration(mut self, duration: Duration) -> Self {
+		self.duration = Some(duration);
+		self
+	}
+
+	/// Shows the notification until it is dismissed.
+	pub fn without_timeout(mut self) -> Self {
+		self.duration = None;
+		self
+	}
+
+	pub fn with_action(
+		mut self,
+		id: impl Into<String>,
+		label: impl Into<String>,
+	) -> Self {
+		self.actions.push(NotificationAction {
+			id: id.into(),
+			label: label.into(),
+		});
+		self
+	}
+
+	pub fn with_key(mut self, key: impl Into<String>) -> Self {
+		self.key = Some(key.into());
+		self
+	}
+
+	/// Whether pushing `other` while this is shown should stack them.
+	fn stacks_with(&self, other: &Notification) -> bool {
+		match (&self.key, &other.key) {
+			(Some(key), Some(other_key)) => key == other_key,
+			(None, None) => self.says_the_same_as(other),
+			_ => false,
+		}
+	}
+
+	/// Whether `other` would look the same, ignoring how long it is shown for.
+	fn says_the_same_as(&self, other: &Notification) -> bool {
+		self.severity == other.severity
+			&& self.title == other.title
+			&& self.body == other.body
+			&& self.actions == other.actions
+	}
+}
+
+/// A notification that is being shown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveNotification {
+	pub id: NotificationId,
+	pub notification: Notification,
+	/// How many times it was pushed while it was shown. Replacing it by its key with
+	/// something that says something else starts again from 1.
+	pub count: u32,
+	/// When it was first shown or last stacked onto, as [`Time::elapsed`].
+	pub shown_at: Duration,
+	/// Whether it has been shown yet, rather than waiting for room or for the rate
+	/// limit. Shown notifications always come before waiting ones.
+	on_screen: bool,
+}
+
+impl ActiveNotification {
+	/// How much of its duration is left, from 1 to 0. Always 1 without a timeout.
+	pub fn time_left(&self, now: Duration) -> f32 {
+		let Some(duration) = self.notification.duration else {
+			return 1.0;
+		};
+		let elapsed = now.saturating_sub(self.shown_at);
+		1.0 - (elapsed.as_secs_f32() / duration.as_secs_f32()).min(1.0)
+	}
+
+	fn is_expired(&self, now: Duration) -> bool {
+		self.notification
+			.duration
+			.is_some_and(|duration| now >= self.shown_at + duration)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+	pub notification: Notification,
+	/// When it was pushed, as [`Time::elapsed`].
+	pub at: Duration,
+	/// Whether too many notifications were pushed at once or just before it, so it
+	/// had to wait to be shown.
+	pub rate_limited: bool,
+}
+
+/// The notifications that are shown, and the ones that were.
+#[derive(Resource, Debug, Clone)]
+pub struct Notifications {
+	/// Oldest first.
+	active: Vec<ActiveNotification>,
+	/// Newest first.
+	history: VecDeque<HistoryEntry>,
+	/// How many notifications the history keeps.
+	pub history_len: usize,
+	/// How many notifications are shown at once. Newer ones wait until there is
+	/// room, without timing out.
+	pub max_shown: usize,
+	/// At most this many new notifications are shown per [`Self::rate_window`], and
+	/// the rest wait like they do for [`Self::max_shown`]. Stacking and replacing
+	/// doesn't count.
+	pub rate_limit: usize,
+	pub rate_window: Duration,
+	/// When recent notifications were first shown, oldest first.
+	recent: VecDeque<Duration>,
+	next_id: u64,
+	/// [`Time::elapsed`] as of the last update.
+	now: Duration,
+}
+
+impl Default for Notifications {
+	fn default() -> Self {
+		Self {
+			active: Vec::new(),
+			history: VecDeque::new(),
+			history_len: 100,
+			max_shown: 4,
+			rate_limit: 5,
+			rate_window: Duration::from_secs(3),
+			recent: VecDeque::new(),
+			next_id: 0,
+			now: Duration::ZERO,
+		}
+	}
+}
+
+impl Notifications {
+	/// Shows the notification, or stacks it onto one like it that is shown or
+	/// waiting. If there is no room for it, or too many were shown recently, it waits
+	/// its turn. Returns the id of the one that is shown or waiting.
+	pub fn push(&mut self, notification: Notification) -> NotificationId {
+		let now = self.now;
+		let mut entry = HistoryEntry {
+			notification: notification.clone(),
+			at: now,
+			rate_limited: false,
+		};
+		let stacked = self
+			.active
+			.iter_mut()
+			.find(|active| active.notification.stacks_with(&notification));
+		let id = match stacked {
+			Some(active) => {
+				if active.notification.says_the_same_as(&notification) {
+					active.count += 1;
+				} else {
+					active.count = 1;
+				}
+				active.shown_at = now;
+				active.notification = notification;
+				active.id
+			}
+			None => {
+				let id = NotificationId(self.next_id);
+				self.next_id += 1;
+				self.active.push(ActiveNotification {
+					id,
+					notification,
+					count: 1,
+					shown_at: now,
+					on_screen: false,
+				});
+				self.show_waiting();
+				id
+			}
+		};
+		entry.rate_limited = self.get(id).is_some_and(|active| !active.on_screen);
+		self.history.push_front(entry);
+		self.history.truncate(self.history_len);
+		id
+	}
+
+	pub fn dismiss(&mut self, id: NotificationId) {
+		self.active.retain(|active| active.id != id);
+	}
+
+	/// Dismisses the notification with this [`Notification::key`], if it is shown.
+	pub fn dismiss_key(&mut self, key: &str) {
+		self.active
+			.retain(|active| active.notification.key.as_deref() != Some(key));
+	}
+
+	pub fn dismiss_all(&mut self) {
+		self.active.clear();
+	}
+
+	/// The notifications that are being shown, oldest first. At most
+	/// [`Self::max_shown`].
+	pub fn shown(&self) -> &[ActiveNotification] {
+		&self.active[..self.shown_len()]
+	}
+
+	/// How many notifications are waiting to be shown.
+	pub fn waiting(&self) -> usize {
+		self.active.len() - self.shown_len()
+	}
+
+	pub fn get(&self, id: NotificationId) -> Option<&ActiveNotification> {
+		self.active.iter().find(|active| active.id == id)
+	}
+
+	/// Every notification that was pushed, newest first.
+	pub fn history(&self) -> &VecDeque<HistoryEntry> {
+		&self.history
+	}
+
+	pub fn clear_history(&mut self) {
+		self.history.clear();
+	}
+
+	/// [`Time::elapsed`] as of the last update, which notifications are timed by.
+	pub fn now(&self) -> Duration {
+		self.now
+	}
+
+	fn shown_len(&self) -> usize {
+		self.active
+			.iter()
+			.take_while(|active| active.on_screen)
+			.count()
+	}
+
+	/// Shows waiting notifications, oldest first, while there is room and the rate
+	/// limit allows. They time out from when they are shown.
+	fn show_waiting(&mut self) {
+		let mut shown = self.shown_len();
+		while shown < self.max_shown && self.recent.len() < self.rate_limit {
+			let Some(active) = self.active.get_mut(shown) else {
+				break;
+			};
+			active.on_screen = true;
+			active.shown_at = self.now;
+			self.recent.push_back(self.now);
+			shown += 1;
+		}
+	}
+
+	/// Presses the action on a shown notification, which dismisses it.
+	pub(crate) fn press(
+		&mut self,
+		id: NotificationId,
+		action: String,
+	) -> ActionPressed {
+		let key = self
+			.get(id)
+			.and_then(|active| active.notification.key.clone());
+		self.dismiss(id);
+		ActionPressed {
+			notification: id,
+			key,
+			action,
+		}
+	}
+}
+
+fn expire_notifications(mut notifications: ResMut<Notifications>, time: Res<Time>) {
+	let now = time.elapsed();
+	let notifications = &mut *notifications;
+	notifications.now = now;
+	while notifications
+		.recent
+		.front()
+		.is_some_and(|&at| at + notifications.rate_window <= now)
+	{
+		notifications.recent.pop_front();
+	}
+	// Only the shown ones time out, from when they were first shown.
+	notifications
+		.active
+		.retain(|active| !active.on_screen || !active.is_expired(now));
+	notifications.show_waiting();
+}
+
+#[cfg(test)]
+mod tests {
+	use bevy::time::TimeUpdateStrategy;
+
+	use super::*;
+
+	/// Less than [`Time<Virtual>`]'s maximum delta, which would clamp it.
+	const STEP: Duration = Duration::from_millis(200);
+
+	fn notifications_app(notifications: Notifications) -> App {
+		let mut app = App::new();
+		app.add_plugins((MinimalPlugins, NotificationsPlugin))
+			.insert_resource(TimeUpdateStrategy::ManualDuration(STEP))
+			.insert_resource(notifications);
+		// The first frame has no delta.
+		app.update();
+		app
+	}
+
+	fn push(app: &mut App, notification: Notification) -> NotificationId {
+		app.world.resource_mut::<Notifications>().push(notification)
+	}
+
+	fn notifications(app: &App) -> &Notifications {
+		app.world.resource::<Notifications>()
+	}
+
+	fn shown_titles(app: &App) -> Vec<&str> {
+		notifications(app)
+			.shown()
+			.iter()
+			.map(|active| active.notification.title.as_str())
+			.collect()
+	}
+
+	#[test]
+	fn identical_notifications_stack() {
+		let mut app = notifications_app(Notifications::default());
+		let first = push(&mut app, Notification::info("Saved"));
+		let second = push(&mut app, Notification::info("Saved"));
+		push(&mut app, Notification::info("Saved").with_body("Again"));
+		assert_eq!(first, second);
+		assert_eq!(shown_titles(&app), ["Saved", "Saved"]);
+		assert_eq!(notifications(&app).shown()[0].count, 2);
+		assert_eq!(notifications(&app).shown()[1].count, 1);
+	}
+
+	#[test]
+	fn keys_replace_without_counting() {
+		let mut app = notifications_app(Notifications::default());
+		let first = push(
+			&mut app,
+			Notification::info("Sam is typing").with_key("sam"),
+		);
+		app.update();
+		let second = push(&mut app, Notification::info("Sam left").with_key("sam"));
+		assert_eq!(first, second);
+		let active = notifications(&app).get(first).unwrap();
+		assert_eq!(active.notification.title, "Sam left");
+		assert_eq!(active.count, 1);
+		// Replacing restarts the timeout.
+		assert_eq!(active.shown_at, notifications(&app).now());
+
+		push(&mut app, Notification::info("Sam left").with_key("sam"));
+		assert_eq!(notifications(&app).get(first).unwrap().count, 2);
+
+		app.world.resource_mut::<Notifications>().dismiss_key("sam");
+		assert!(notifications(&app).shown().is_empty());
+	}
+
+	#[test]
+	fn waits_for_room() {
+		let mut app = notifications_app(Notifications {
+			max_shown: 2,
+			..default()
+		});
+		let a = push(&mut app, Notification::info("A").without_timeout());
+		push(&mut app, Notification::info("B").without_timeout());
+		push(&mut app, Notification::info("C"));
+		assert_eq!(shown_titles(&app), ["A", "B"]);
+		assert_eq!(notifications(&app).waiting(), 1);
+		assert!(notifications(&app).history()[0].rate_limited);
+		assert!(!notifications(&app).history()[1].rate_limited);
+
+		// Waiting doesn't use up C's time, which starts when it is shown.
+		for _ in 0..10 {
+			app.update();
+		}
+		app.world.resource_mut::<Notifications>().dismiss(a);
+		app.update();
+		assert_eq!(shown_titles(&app), ["B", "C"]);
+		let c = &notifications(&app).shown()[1];
+		assert_eq!(c.shown_at, notifications(&app).now());
+		assert_eq!(c.time_left(notifications(&app).now()), 1.0);
+	}
+
+	#[test]
+	fn rate_limit_delays_bursts() {
+		let mut app = notifications_app(Notifications {
+			rate_limit: 2,
+			rate_window: STEP * 3,
+			..default()
+		});
+		for title in ["A", "B", "C"] {
+			push(&mut app, Notification::info(title).without_timeout());
+		}
+		assert_eq!(shown_titles(&app), ["A", "B"]);
+		let history = notifications(&app).history();
+		assert!(history[0].rate_limited);
+		assert!(!history[1].rate_limited);
+		assert!(!history[2].rate_limited);
+
+		// Stacking onto a shown one doesn't count towards the limit.
+		push(&mut app, Notification::info("A").without_timeout());
+		assert!(!notifications(&app).history()[0].rate_limited);
+
+		app.update();
+		app.update();
+		assert_eq!(notifications(&app).waiting(), 1);
+		app.update();
+		assert_eq!(shown_titles(&app), ["A", "B", "C"]);
+	}
+
+	#[test]
+	fn shown_notifications_expire() {
+		let mut app = notifications_app(Notifications::default());
+		push(
+			&mut app,
+			Notification::info("Short").with_duration(STEP * 2),
+		);
+		push(&mut app, Notification::info("Forever").without_timeout());
+		app.update();
+		let now = notifications(&app).now();
+		assert_eq!(notifications(&app).shown()[0].time_left(now), 0.5);
+		app.update();
+		assert_eq!(shown_titles(&app), ["Forever"]);
+	}
+
+	#[test]
+	fn history_is_newest_first_and_bounded() {
+		let mut app = notifications_app(Notifications {
+			history_len: 3,
+			..default()
+		});
+		for title in ["A", "B", "B", "C"] {
+			push(&mut app, Notification::info(title));
+		}
+		let titles: Vec<_> = notifications(&app)
+			.history()
+			.iter()
+			.map(|entry| entry.notification.title.as_str())
+			.collect();
+		assert_eq!(titles, ["C", "B", "B"]);
+
+		// Dismissing doesn't touch the history.
+		app.world.resource_mut::<Notifications>().dismiss_all();
+		assert_eq!(notifications(&app).history().len(), 3);
+		app.world.resource_mut::<Notifications>().clear_history();
+		assert!(notifications(&app).history().is_empty());
+	}
+
+	#[test]
+	fn pressing_an_action_dismisses() {
+		let mut app = notifications_app(Notifications::default());
+		let id = push(
+			&mut app,
+			Notification::info("Invite")
+				.with_key("invite-1")
+				.with_action("accept", "Accept"),
+		);
+		let pressed = app
+			.world
+			.resource_mut::<Notifications>()
+			.press(id, "accept".to_owned());
+		assert_eq!(
+			pressed,
+			ActionPressed {
+				notification: id,
+				key: Some("invite-1".to_owned()),
+				action: "accept".to_owned(),
+			}
+		);
+		assert!(notifications(&app).get(id).is_none());
+	}
+}
@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use bevy_flycam::FlyCam;
+use color_eyre::Result;
+use notifications::panel::{HeadLocked, NotificationPanel, PanelNotificationsPlugin};
+use notifications::screen::ScreenNotificationsPlugin;
+use notifications::{ActionPressed, Notification, Notifications, NotificationsPlugin};
+use worldspace_ui::pointer::add_mouse_pointer;
+use worldspace_ui::{WorldspaceUi, WorldspaceUiPlugin};
+
+fn main() -> Result<()> {
+	color_eyre::install()?;
+
+	App::new()
+		.add_plugins(DefaultPlugins)
+		.add_plugins(bevy_flycam::PlayerPlugin)
+		.add_plugins(bevy_egui::EguiPlugin)
+		.add_plugins((
+			WorldspaceUiPlugin,
+			NotificationsPlugin,
+			ScreenNotificationsPlugin,
+			PanelNotificationsPlugin,
+		))
+		.add_systems(
+			Update,
+			(
+				screenspace_ui,
+				log_actions,
+				add_panel,
+				add_mouse_pointer::<FlyCam>,
+			),
+		)
+		.run();
+
+	Ok(())
+}
+
+/// Buttons that push notifications.
+fn screenspace_ui(
+	mut contexts: EguiContexts,
+	mut notifications: ResMut<Notifications>,
+	mut panels: Query<&mut NotificationPanel>,
+) {
+	egui::Window::new("Notify")
+		.anchor(egui::Align2::LEFT_TOP, [12.0, 12.0])
+		.show(contexts.ctx_mut(), |ui| {
+			if ui.button("Info").clicked() {
+				notifications.push(
+					Notification::info("Hello")
+						.with_body("This is an info notification."),
+				);
+			}
+			if ui.button("Success").clicked() {
+				notifications.push(Notification::success("Saved the world"));
+			}
+			if ui.button("Warning").clicked() {
+				notifications.push(
+					Notification::warning("Low battery")
+						.with_body("Your controller is at 10%."),
+				);
+			}
+			if ui.button("Error").clicked() {
+				notifications.push(
+					Notification::error("Disconnected").with_body("Lost the server."),
+				);
+			}
+			if ui.button("With actions").clicked() {
+				notifications.push(
+					Notification::info("Sam wants to be friends")
+						.without_timeout()
+						.with_key("friend-request-sam")
+						.with_action("accept", "Accept")
+						.with_action("ignore", "Ignore"),
+				);
+			}
+			if ui.button("Flood").clicked() {
+				for i in 0..10 {
+					notifications.push(Notification::info(format!("Message {i}")));
+				}
+			}
+			for mut panel in panels.iter_mut() {
+				ui.checkbox(&mut panel.show_history, "History on the panel");
+			}
+		});
+}
+
+fn log_actions(mut pressed: EventReader<ActionPressed>) {
+	for ActionPressed { key, action, .. } in pressed.read() {
+		info!("pressed {action} on {key:?}");
+	}
+}
+
+/// Puts a panel that follows the camera below the middle of the view.
+fn add_panel(
+	mut commands: Commands,
+	cameras: Query<Entity, Added<FlyCam>>,
+	mut images: ResMut<Assets<Image>>,
+) {
+	for camera in cameras.iter() {
+		commands.spawn((
+			SpatialBundle::default(),
+			WorldspaceUi::new(&mut images, Vec2::new(0.3, 0.35)),
+			NotificationPanel::default(),
+			HeadLocked::new(camera),
+		));
+	}
+}
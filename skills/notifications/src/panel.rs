@@ -0,0 +1,98 @@
+//! Notifications on world-space panels, for VR.
+
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+use bevy_egui::egui;
+use worldspace_ui::WorldspaceUi;
+
+use crate::ui::{history_ui, notifications_ui};
+use crate::{ActionPressed, Notifications, NotificationsSet};
+
+/// Draws the [`Notifications`] on [`NotificationPanel`]s, and moves [`HeadLocked`]
+/// entities after the head. Needs the `WorldspaceUiPlugin`.
+pub struct PanelNotificationsPlugin;
+
+impl Plugin for PanelNotificationsPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_systems(Update, draw_panels.after(NotificationsSet))
+			.add_systems(
+				PostUpdate,
+				follow_heads.before(TransformSystem::TransformPropagate),
+			);
+	}
+}
+
+/// A [`WorldspaceUi`] panel that shows the notifications, and is hidden while there
+/// are none and the history isn't shown.
+#[derive(Component, Debug, Default, Clone)]
+pub struct NotificationPanel {
+	pub show_history: bool,
+}
+
+/// Keeps the entity in front of the head, catching up with it smoothly rather than
+/// being fixed to it, which is more comfortable to read in VR. The entity shouldn't
+/// have a parent.
+#[derive(Component, Debug, Clone)]
+pub struct HeadLocked {
+	/// The head, such as the camera.
+	pub head: Entity,
+	/// Where the entity goes, relative to the head.
+	pub offset: Vec3,
+	/// Roughly how many seconds it takes to catch up. 0 keeps up exactly.
+	pub lag: f32,
+}
+
+impl HeadLocked {
+	pub fn new(head: Entity) -> Self {
+		Self {
+			head,
+			offset: Vec3::new(0.0, -0.2, -0.6),
+			lag: 0.3,
+		}
+	}
+}
+
+fn draw_panels(
+	mut panels: Query<(&mut WorldspaceUi, &NotificationPanel, &mut Visibility)>,
+	mut notifications: ResMut<Notifications>,
+	mut pressed: EventWriter<ActionPressed>,
+) {
+	for (mut panel, settings, mut visibility) in panels.iter_mut() {
+		if notifications.shown().is_empty() && !settings.show_history {
+			visibility.set_if_neq(Visibility::Hidden);
+			continue;
+		}
+		visibility.set_if_neq(Visibility::Inherited);
+		let ctx = panel.ctx_mut().clone();
+		egui::CentralPanel::default()
+			.frame(egui::Frame::none())
+			.show(&ctx, |ui| {
+				if settings.show_history {
+					history_ui(ui, &mut notifications);
+				} else {
+					pressed.send_batch(notifications_ui(ui, &mut notifications));
+				}
+			});
+	}
+}
+
+fn follow_heads(
+	mut followers: Query<(&mut Transform, &HeadLocked)>,
+	heads: Query<&GlobalTransform>,
+	time: Res<Time>,
+) {
+	for (mut transform, follow) in followers.iter_mut() {
+		let Ok(head) = heads.get(follow.head) else {
+			continue;
+		};
+		let head = head.compute_transform();
+		let target = head.translation + head.rotation * follow.offset;
+		let t = if follow.lag > 0.0 {
+			1.0 - (-time.delta_seconds() / follow.lag).exp()
+		} else {
+			1.0
+		};
+		transform.translation = transform.translation.lerp(target, t);
+		transform.rotation = transform.rotation.slerp(head.rotation, t);
+	}
+}
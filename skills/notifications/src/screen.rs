@@ -0,0 +1,75 @@
+//! Notifications as an egui overlay on a flatscreen.
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::ui::{history_ui, notifications_ui};
+use crate::{ActionPressed, Notifications, NotificationsSet};
+
+/// Draws the [`Notifications`] over the primary window, in a corner. Needs the
+/// `EguiPlugin`.
+pub struct ScreenNotificationsPlugin;
+
+impl Plugin for ScreenNotificationsPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<ScreenNotifications>()
+			.add_systems(Update, draw_notifications.after(NotificationsSet));
+	}
+}
+
+/// How notifications are shown on the screen.
+#[derive(Resource, Debug, Clone)]
+pub struct ScreenNotifications {
+	/// Which corner or edge they are shown in.
+	pub anchor: egui::Align2,
+	/// Space between them and the edge of the window, in points.
+	pub margin: f32,
+	/// Whether there is a button that opens the history.
+	pub show_bell: bool,
+	/// Whether the history window is open.
+	pub show_history: bool,
+}
+
+impl Default for ScreenNotifications {
+	fn default() -> Self {
+		Self {
+			anchor: egui::Align2::RIGHT_TOP,
+			margin: 12.0,
+			show_bell: true,
+			show_history: false,
+		}
+	}
+}
+
+fn draw_notifications(
+	mut contexts: EguiContexts,
+	mut settings: ResMut<ScreenNotifications>,
+	mut notifications: ResMut<Notifications>,
+	mut pressed: EventWriter<ActionPressed>,
+) {
+	let ctx = contexts.ctx_mut();
+	let margin = settings.margin;
+	let offset = settings.anchor.to_sign() * -margin;
+	egui::Area::new("notifications")
+		.anchor(settings.anchor, offset)
+		.show(ctx, |ui| {
+			let align = settings.anchor.x();
+			ui.with_layout(egui::Layout::top_down(align), |ui| {
+				if settings.show_bell {
+					let bell =
+						ui.small_button("🔔").on_hover_text("Notification history");
+					if bell.clicked() {
+						settings.show_history = !settings.show_history;
+					}
+				}
+				pressed.send_batch(notifications_ui(ui, &mut notifications));
+			});
+		});
+
+	let mut open = settings.show_history;
+	egui::Window::new("Notification history")
+		.open(&mut open)
+		.default_width(300.0)
+		.show(ctx, |ui| history_ui(ui, &mut notifications));
+	settings.show_history = open;
+}
@@ -0,0 +1,148 @@
+use std::time::Duration;
+
+use bevy_egui::egui;
+
+use crate::{ActionPressed, ActiveNotification, Notifications, Severity};
+
+/// Width of a notification, in points.
+pub const NOTIFICATION_WIDTH: f32 = 260.0;
+
+enum Response {
+	Action(String),
+	Dismiss,
+}
+
+/// Draws the shown notifications stacked top to bottom, oldest first, and dismisses
+/// the ones that are closed. Returns the actions that were pressed.
+pub fn notifications_ui(
+	ui: &mut egui::Ui,
+	notifications: &mut Notifications,
+) -> Vec<ActionPressed> {
+	let now = notifications.now();
+	let mut responses = Vec::new();
+	for active in notifications.shown() {
+		egui::Frame::popup(ui.style())
+			.stroke(egui::Stroke::new(
+				1.0,
+				severity_color(ui, active.notification.severity),
+			))
+			.show(ui, |ui| {
+				ui.set_width(NOTIFICATION_WIDTH);
+				if let Some(response) = notification_ui(ui, active, now) {
+					responses.push((active.id, response));
+				}
+			});
+	}
+	let waiting = notifications.waiting();
+	if waiting > 0 {
+		ui.small(format!("{waiting} more"));
+	}
+
+	let mut pressed = Vec::new();
+	for (id, response) in responses {
+		match response {
+			Response::Action(action) => pressed.push(notifications.press(id, action)),
+			Response::Dismiss => notifications.dismiss(id),
+		}
+	}
+	pressed
+}
+
+fn notification_ui(
+	ui: &mut egui::Ui,
+	active: &ActiveNotification,
+	now: Duration,
+) -> Option<Response> {
+	let notification = &active.notification;
+	let mut response = None;
+	ui.horizontal(|ui| {
+		let color = severity_color(ui, notification.severity);
+		ui.label(
+			egui::RichText::new(&notification.title)
+				.strong()
+				.color(color),
+		);
+		if active.count > 1 {
+			ui.small(format!("×{}", active.count));
+		}
+		ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+			if ui.small_button("✖").clicked() {
+				response = Some(Response::Dismiss);
+			}
+		});
+	});
+	if !notification.body.is_empty() {
+		ui.label(&notification.body);
+	}
+	if !notification.actions.is_empty() {
+		ui.horizontal(|ui| {
+			for action in &notification.actions {
+				if ui.button(&action.label).clicked() {
+					response = Some(Response::Action(action.id.clone()));
+				}
+			}
+		});
+	}
+	if notification.duration.is_some() {
+		// How long is left, as a thin bar along the bottom.
+		let (rect, _) = ui.allocate_exact_size(
+			egui::vec2(ui.available_width(), 2.0),
+			egui::Sense::hover(),
+		);
+		let mut left = rect;
+		left.set_width(rect.width() * active.time_left(now));
+		ui.painter()
+			.rect_filled(left, 0.0, ui.visuals().weak_text_color());
+	}
+	response
+}
+
+/// Lists every notification in the history, newest first, with a button to clear
+/// it.
+pub fn history_ui(ui: &mut egui::Ui, notifications: &mut Notifications) {
+	let now = notifications.now();
+	ui.horizontal(|ui| {
+		ui.heading("Notifications");
+		if ui.button("Clear").clicked() {
+			notifications.clear_history();
+		}
+	});
+	ui.separator();
+	if notifications.history().is_empty() {
+		ui.weak("Nothing yet");
+	}
+	egui::ScrollArea::vertical().show(ui, |ui| {
+		for entry in notifications.history() {
+			let notification = &entry.notification;
+			ui.horizontal(|ui| {
+				let color = severity_color(ui, notification.severity);
+				ui.colored_label(color, "●");
+				ui.strong(&notification.title);
+				ui.with_layout(
+					egui::Layout::right_to_left(egui::Align::Center),
+					|ui| {
+						let ago = now.saturating_sub(entry.at).as_secs();
+						ui.small(format!("{ago}s ago"));
+						if entry.rate_limited {
+							ui.small("delayed").on_hover_text(
+								"Too many notifications were pushed at once",
+							);
+						}
+					},
+				);
+			});
+			if !notification.body.is_empty() {
+				ui.small(&notification.body);
+			}
+		}
+	});
+}
+
+fn severity_color(ui: &egui::Ui, severity: Severity) -> egui::Color32 {
+	match severity {
+		Severity::Info => ui.visuals().hyperlink_color,
+		Severity::Success => egui::Color32::from_rgb(90, 200, 90),
+		Severity::Warning => ui.visuals().warn_fg_color,
+		Severity::Error => ui.visuals().error_fg_color,
+	}
+}
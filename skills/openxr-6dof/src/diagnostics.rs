@@ -0,0 +1,304 @@
+//! XR specific frame timing, as Bevy diagnostics.
+//!
+//! [`FrameTimeDiagnosticsPlugin`](bevy::diagnostic::FrameTimeDiagnosticsPlugin) only
+//! tells you the FPS, which doesn't say whether you are keeping up with the headset.
+//! This reports the display period the runtime expects, frames that were missed,
+//! how far ahead of the display time the poses were sampled, and how long tracking
+//! and IK took.
+
+use std::time::{Duration, Instant};
+
+use bevy::diagnostic::{
+	Diagnostic, DiagnosticId, Diagnostics, DiagnosticsStore, RegisterDiagnostic,
+};
+use bevy::prelude::*;
+use bevy_oxr::resources::{XrFrameState, XrInstance};
+
+use crate::poses::{XrPoseSet, XrPoses};
+
+/// Needs the [`XrPosePlugin`](crate::poses::XrPosePlugin). To time IK, order
+/// [`XrIkTimingSet`] around the solver.
+pub struct XrDiagnosticsPlugin;
+
+impl Plugin for XrDiagnosticsPlugin {
+	fn build(&self, app: &mut App) {
+		app.register_diagnostic(
+			Diagnostic::new(Self::DISPLAY_PERIOD, "xr_display_period", 20)
+				.with_suffix("ms"),
+		)
+		.register_diagnostic(
+			Diagnostic::new(Self::MISSED_FRAMES, "xr_missed_frames", 90)
+				.with_smoothing_factor(0.0),
+		)
+		.register_diagnostic(
+			Diagnostic::new(Self::POSE_SAMPLE_TIME, "xr_pose_sample_to_display", 20)
+				.with_suffix("ms"),
+		)
+		.register_diagnostic(
+			Diagnostic::new(Self::TRACKING_TIME, "xr_tracking_cpu_time", 20)
+				.with_suffix("ms"),
+		)
+		.register_diagnostic(
+			Diagnostic::new(Self::IK_TIME, "xr_ik_cpu_time", 20).with_suffix("ms"),
+		)
+		.init_resource::<XrFrameTiming>()
+		.init_resource::<XrDiagnosticsOverlay>()
+		.add_systems(First, start_frame)
+		.add_systems(
+			PreUpdate,
+			(
+				start_tracking.before(XrPoseSet::Sample),
+				end_sample.after(XrPoseSet::Sample),
+			),
+		)
+		.add_systems(
+			Update,
+			(
+				start_tracking.before(XrPoseSet::Apply),
+				end_tracking.after(XrPoseSet::Apply),
+			),
+		)
+		.configure_sets(
+			PostUpdate,
+			(XrIkTimingSet::Start, XrIkTimingSet::End).chain(),
+		)
+		.add_systems(
+			PostUpdate,
+			(
+				start_ik.in_set(XrIkTimingSet::Start),
+				end_ik.in_set(XrIkTimingSet::End),
+			),
+		)
+		.add_systems(Last, (diagnostic_system, draw_overlay).chain());
+	}
+}
+
+impl XrDiagnosticsPlugin {
+	/// How long the runtime expects each frame to be displayed for.
+	pub const DISPLAY_PERIOD: DiagnosticId =
+		DiagnosticId::from_u128(171592620463913398815264312283702954577);
+	/// How many display periods were skipped since the previous frame.
+	pub const MISSED_FRAMES: DiagnosticId =
+		DiagnosticId::from_u128(316487064470152375364658733937209380349);
+	/// Time from when the poses were sampled until the predicted display time, on
+	/// the runtime's clock. The poses are predicted this far ahead, so the longer it
+	/// is, the less accurate they are. It is negative if the sample was too late for
+	/// the display time.
+	///
+	/// Not recorded if the runtime can't convert the current time, which needs
+	/// `XR_KHR_convert_timespec_time` (or the Win32 performance counter extension).
+	pub const POSE_SAMPLE_TIME: DiagnosticId =
+		DiagnosticId::from_u128(48210933874013546913487162651208874562);
+	/// CPU time spent sampling poses and applying them to entities.
+	pub const TRACKING_TIME: DiagnosticId =
+		DiagnosticId::from_u128(265903779105893036571233962406290377034);
+	/// CPU time spent between [`XrIkTimingSet::Start`] and [`XrIkTimingSet::End`].
+	pub const IK_TIME: DiagnosticId =
+		DiagnosticId::from_u128(206139227307766301394934823052787316013);
+}
+
+/// Bounds of the span in `PostUpdate` that is timed as
+/// [`XrDiagnosticsPlugin::IK_TIME`]. IK solvers don't share a set, so configure
+/// `Start` to run before yours and `End` after it.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum XrIkTimingSet {
+	Start,
+	End,
+}
+
+/// Raw timings of the current frame. The diagnostics are recorded from this in
+/// [`Last`].
+#[derive(Resource, Debug, Default, Clone)]
+pub struct XrFrameTiming {
+	/// Nanoseconds from the pose sample until the predicted display time, or `None`
+	/// if the runtime couldn't tell the time.
+	pub pose_sample_to_display: Option<i64>,
+	pub tracking: Duration,
+	pub ik: Duration,
+	/// Display periods skipped since the previous frame.
+	pub missed_frames: u32,
+	/// Display periods skipped since the app started.
+	pub total_missed_frames: u64,
+	previous_display_time: Option<i64>,
+	span_start: Option<Instant>,
+	ik_start: Option<Instant>,
+}
+
+/// Draws the diagnostics as bars in front of the head, since you can't read logs
+/// with a headset on.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct XrDiagnosticsOverlay {
+	pub enabled: bool,
+	/// Entity to draw the bars in front of. If `None`, they are drawn in front of the
+	/// head pose, which is only right while the tracking root is at the origin.
+	pub anchor: Option<Entity>,
+}
+
+fn start_frame(mut timing: ResMut<XrFrameTiming>) {
+	timing.pose_sample_to_display = None;
+	timing.tracking = Duration::ZERO;
+	timing.ik = Duration::ZERO;
+}
+
+fn start_tracking(mut timing: ResMut<XrFrameTiming>) {
+	timing.span_start = Some(Instant::now());
+}
+
+fn end_sample(
+	mut timing: ResMut<XrFrameTiming>,
+	instance: Res<XrInstance>,
+	frame_state: Res<XrFrameState>,
+) {
+	let now = Instant::now();
+	// The poses were predicted for this display time, so compare against it on the
+	// same clock.
+	let display_time = frame_state.lock().unwrap().predicted_display_time;
+	timing.pose_sample_to_display = instance
+		.now()
+		.ok()
+		.map(|xr_now| display_time.as_nanos() - xr_now.as_nanos());
+	end_tracking_span(&mut timing, now);
+}
+
+fn end_tracking(mut timing: ResMut<XrFrameTiming>) {
+	end_tracking_span(&mut timing, Instant::now());
+}
+
+fn end_tracking_span(timing: &mut XrFrameTiming, now: Instant) {
+	if let Some(start) = timing.span_start.take() {
+		timing.tracking += now - start;
+	}
+}
+
+fn start_ik(mut timing: ResMut<XrFrameTiming>) {
+	timing.ik_start = Some(Instant::now());
+}
+
+fn end_ik(mut timing: ResMut<XrFrameTiming>) {
+	if let Some(start) = timing.ik_start.take() {
+		timing.ik = start.elapsed();
+	}
+}
+
+fn diagnostic_system(
+	mut diagnostics: Diagnostics,
+	mut timing: ResMut<XrFrameTiming>,
+	frame_state: Res<XrFrameState>,
+) {
+	let frame_state = *frame_state.lock().unwrap();
+	let period = frame_state.predicted_display_period.as_nanos();
+	let display_time = frame_state.predicted_display_time.as_nanos();
+
+	timing.missed_frames = match timing.previous_display_time {
+		Some(previous) if period > 0 => {
+			let periods = ((display_time - previous) as f64 / period as f64).round();
+			(periods as i64 - 1).max(0) as u32
+		}
+		_ => 0,
+	};
+	timing.total_missed_frames += u64::from(timing.missed_frames);
+	timing.previous_display_time = Some(display_time);
+
+	let to_ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+	diagnostics.add_measurement(XrDiagnosticsPlugin::DISPLAY_PERIOD, || {
+		period as f64 / 1_000_000.0
+	});
+	diagnostics.add_measurement(XrDiagnosticsPlugin::MISSED_FRAMES, || {
+		f64::from(timing.missed_frames)
+	});
+	if let Some(nanos) = timing.pose_sample_to_display {
+		diagnostics.add_measurement(XrDiagnosticsPlugin::POSE_SAMPLE_TIME, || {
+			nanos as f64 / 1_000_000.0
+		});
+	}
+	diagnostics.add_measurement(XrDiagnosticsPlugin::TRACKING_TIME, || {
+		to_ms(timing.tracking)
+	});
+	diagnostics.add_measurement(XrDiagnosticsPlugin::IK_TIME, || to_ms(timing.ik));
+}
+
+/// One horizontal bar per diagnostic, where the full width is one display period,
+/// or three for the pose sample, which is normally a period or two ahead. The white
+/// tick marks half of the full width.
+fn draw_overlay(
+	mut gizmos: Gizmos,
+	overlay: Res<XrDiagnosticsOverlay>,
+	diagnostics: Res<DiagnosticsStore>,
+	timing: Res<XrFrameTiming>,
+	poses: Res<XrPoses>,
+	anchors: Query<&GlobalTransform>,
+) {
+	if !overlay.enabled {
+		return;
+	}
+	let anchor = match overlay.anchor {
+		Some(entity) => match anchors.get(entity) {
+			Ok(anchor) => anchor.compute_transform(),
+			Err(_) => return,
+		},
+		None => match poses.head {
+			Some(head) => head,
+			None => return,
+		},
+	};
+	let smoothed = |id| {
+		diagnostics
+			.get(id)
+			.and_then(|d: &Diagnostic| d.smoothed())
+			.unwrap_or(0.0) as f32
+	};
+	let period = smoothed(XrDiagnosticsPlugin::DISPLAY_PERIOD);
+	if period <= 0.0 {
+		return;
+	}
+
+	const WIDTH: f32 = 0.2;
+	const SPACING: f32 = 0.015;
+	let origin = Vec3::new(-WIDTH / 2.0, -0.1, -0.5);
+	let bars = [
+		(XrDiagnosticsPlugin::POSE_SAMPLE_TIME, 3.0),
+		(XrDiagnosticsPlugin::TRACKING_TIME, 1.0),
+		(XrDiagnosticsPlugin::IK_TIME, 1.0),
+	];
+	for (i, (id, periods)) in bars.into_iter().enumerate() {
+		let value = smoothed(id);
+		// Only the pose sample can be negative, when it was too late to be useful.
+		let fraction = if value < 0.0 {
+			f32::INFINITY
+		} else {
+			value / (period * periods)
+		};
+		let color = if fraction < 0.5 {
+			Color::GREEN
+		} else if fraction < 1.0 {
+			Color::YELLOW
+		} else {
+			Color::RED
+		};
+		let start = origin - Vec3::Y * SPACING * i as f32;
+		let end = start + Vec3::X * WIDTH * fraction.min(1.0);
+		let tick = start + Vec3::X * WIDTH / 2.0;
+		gizmos.line(
+			anchor.transform_point(start),
+			anchor.transform_point(end),
+			color,
+		);
+		gizmos.line(
+			anchor.transform_point(tick - Vec3::Y * SPACING / 3.0),
+			anchor.transform_point(tick + Vec3::Y * SPACING / 3.0),
+			Color::WHITE,
+		);
+	}
+
+	// Flash a frame around the bars whenever a frame was missed.
+	if timing.missed_frames > 0 {
+		let size = Vec2::new(WIDTH, SPACING * bars.len() as f32) * 1.2;
+		let center = origin + Vec3::new(WIDTH / 2.0, -SPACING, 0.0);
+		gizmos.rect(
+			anchor.transform_point(center),
+			anchor.rotation,
+			size,
+			Color::RED,
+		);
+	}
+}
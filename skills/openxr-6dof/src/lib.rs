@@ -1,12 +1,14 @@
+pub mod diagnostics;
+pub mod poses;
+
 use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
 use bevy::prelude::*;
 use bevy::transform::components::Transform;
-use bevy_oxr::input::XrInput;
-use bevy_oxr::resources::XrFrameState;
-use bevy_oxr::xr_input::oculus_touch::OculusController;
-use bevy_oxr::xr_input::{QuatConv, Vec3Conv};
 use bevy_oxr::DefaultXrPlugins;
 
+use crate::diagnostics::{XrDiagnosticsOverlay, XrDiagnosticsPlugin};
+use crate::poses::{XrPosePlugin, XrPoses};
+
 #[bevy_main]
 pub fn main() {
 	color_eyre::install().unwrap();
@@ -16,6 +18,11 @@ pub fn main() {
 		.add_plugins(DefaultXrPlugins)
 		.add_plugins(LogDiagnosticsPlugin::default())
 		.add_plugins(FrameTimeDiagnosticsPlugin)
+		.add_plugins((XrPosePlugin, XrDiagnosticsPlugin))
+		.insert_resource(XrDiagnosticsOverlay {
+			enabled: true,
+			anchor: None,
+		})
 		.add_systems(Startup, setup)
 		.add_systems(Update, hands)
 		.run();
@@ -57,37 +64,13 @@ fn setup(
 	},));
 }
 
-fn hands(
-	mut gizmos: Gizmos,
-	oculus_controller: Res<OculusController>,
-	frame_state: Res<XrFrameState>,
-	xr_input: Res<XrInput>,
-) {
-	let mut func = || -> color_eyre::Result<()> {
-		let frame_state = *frame_state.lock().unwrap();
-
-		let right_controller = oculus_controller
-			.grip_space
-			.right
-			.relate(&xr_input.stage, frame_state.predicted_display_time)?;
-		let left_controller = oculus_controller
-			.grip_space
-			.left
-			.relate(&xr_input.stage, frame_state.predicted_display_time)?;
+fn hands(mut gizmos: Gizmos, poses: Res<XrPoses>) {
+	for grip in [poses.left_grip, poses.right_grip].into_iter().flatten() {
 		gizmos.rect(
-			right_controller.0.pose.position.to_vec3(),
-			right_controller.0.pose.orientation.to_quat(),
+			grip.translation,
+			grip.rotation,
 			Vec2::new(0.05, 0.2),
 			Color::YELLOW_GREEN,
 		);
-		gizmos.rect(
-			left_controller.0.pose.position.to_vec3(),
-			left_controller.0.pose.orientation.to_quat(),
-			Vec2::new(0.05, 0.2),
-			Color::YELLOW_GREEN,
-		);
-		Ok(())
-	};
-
-	let _ = func();
+	}
 }
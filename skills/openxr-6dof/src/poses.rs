@@ -0,0 +1,107 @@
+//! Samples every tracked space once per frame, so that all systems agree on where
+//! things are.
+//!
+//! Calling `relate` from each system that needs a pose is redundant, and since each
+//! call can see newer tracking data, systems can end up disagreeing about where the
+//! head or hands are within the same frame.
+
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+use bevy_oxr::input::XrInput;
+use bevy_oxr::resources::XrFrameState;
+use bevy_oxr::xr_input::oculus_touch::OculusController;
+use bevy_oxr::xr_input::{Hand, QuatConv, Vec3Conv};
+
+/// `XR_SPACE_LOCATION_ORIENTATION_VALID_BIT | XR_SPACE_LOCATION_POSITION_VALID_BIT`
+const VALID_BITS: u64 = 0b11;
+
+pub struct XrPosePlugin;
+
+impl Plugin for XrPosePlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<XrPoses>()
+			.configure_sets(PreUpdate, XrPoseSet::Apply.after(XrPoseSet::Sample))
+			.configure_sets(
+				PostUpdate,
+				XrPoseSet::Apply.before(TransformSystem::TransformPropagate),
+			)
+			.add_systems(PreUpdate, sample_poses.in_set(XrPoseSet::Sample));
+	}
+}
+
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum XrPoseSet {
+	/// Fills in [`XrPoses`]. Runs in [`PreUpdate`], once the frame's predicted display
+	/// time is known.
+	Sample,
+	/// Systems that copy [`XrPoses`] onto entities, like IK targets, usually in
+	/// [`Update`]. In [`PreUpdate`] the set runs after [`Self::Sample`], and in
+	/// [`PostUpdate`] before transform propagation, so it always applies this frame's
+	/// sample before IK solves. Anything that reads those entities should run after
+	/// this set.
+	Apply,
+}
+
+/// Poses of the tracked spaces, relative to the stage, at the predicted display time
+/// of the current frame. A pose is `None` if the runtime could not locate it.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct XrPoses {
+	/// The predicted display time (in nanoseconds, on the runtime's clock) that these
+	/// poses were sampled for. Zero until the first sample.
+	pub predicted_display_time: i64,
+	pub head: Option<Transform>,
+	pub left_grip: Option<Transform>,
+	pub right_grip: Option<Transform>,
+	pub left_aim: Option<Transform>,
+	pub right_aim: Option<Transform>,
+}
+
+impl XrPoses {
+	pub fn grip(&self, hand: Hand) -> Option<Transform> {
+		match hand {
+			Hand::Left => self.left_grip,
+			Hand::Right => self.right_grip,
+		}
+	}
+
+	pub fn aim(&self, hand: Hand) -> Option<Transform> {
+		match hand {
+			Hand::Left => self.left_aim,
+			Hand::Right => self.right_aim,
+		}
+	}
+}
+
+fn sample_poses(
+	oculus_controller: Res<OculusController>,
+	frame_state: Res<XrFrameState>,
+	xr_input: Res<XrInput>,
+	mut poses: ResMut<XrPoses>,
+) {
+	let frame_state = *frame_state.lock().unwrap();
+	let time = frame_state.predicted_display_time;
+	macro_rules! locate {
+		($space:expr) => {
+			$space
+				.relate(&xr_input.stage, time)
+				.ok()
+				.filter(|(location, _)| {
+					location.location_flags.into_raw() & VALID_BITS == VALID_BITS
+				})
+				.map(|(location, _)| Transform {
+					translation: location.pose.position.to_vec3(),
+					rotation: location.pose.orientation.to_quat(),
+					scale: Vec3::ONE,
+				})
+		};
+	}
+
+	*poses = XrPoses {
+		predicted_display_time: time.as_nanos(),
+		head: locate!(xr_input.head),
+		left_grip: locate!(oculus_controller.grip_space.left),
+		right_grip: locate!(oculus_controller.grip_space.right),
+		left_aim: locate!(oculus_controller.aim_space.left),
+		right_aim: locate!(oculus_controller.aim_space.right),
+	};
+}
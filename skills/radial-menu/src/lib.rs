@@ -0,0 +1,425 @@
+//! A pie menu for quick access to emotes and tools, opened by holding a button.
+//!
+//! Each [`RadialMenu`] is driven by its [`RadialMenuInput`]: it opens while `held`,
+//! highlights the wedge that `direction` points at, and sends a
+//! [`RadialMenuSelected`] event for it when let go. Where that input comes from, and
+//! where the menu is drawn, is up to the other modules:
+//!
+//! - [`screen`] opens menus under the mouse cursor on a flatscreen.
+//! - [`panel`] draws menus on a [`WorldspaceUi`](worldspace_ui::WorldspaceUi) panel,
+//!   and lets pointers choose wedges on it.
+//! - [`xr`] opens menus with a controller button and chooses with its thumbstick.
+
+mod paint;
+pub mod panel;
+pub mod screen;
+pub mod xr;
+
+use bevy::prelude::*;
+
+pub struct RadialMenuPlugin;
+
+impl Plugin for RadialMenuPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_event::<RadialMenuSelected>()
+			.add_systems(Update, update_menus.in_set(RadialMenuSet));
+	}
+}
+
+/// Opens, closes and highlights the [`RadialMenu`]s, and sends [`RadialMenuSelected`].
+/// Systems that write [`RadialMenuInput`] should run before this set, and systems
+/// that draw the menus after it.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RadialMenuSet;
+
+/// Sent when a menu is let go of while an item without children is highlighted.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct RadialMenuSelected {
+	pub menu: Entity,
+	/// The [`RadialItem::id`] of the item.
+	pub id: String,
+}
+
+/// Input for a [`RadialMenu`] this frame.
+#[derive(Component, Debug, Default, Clone, PartialEq)]
+pub struct RadialMenuInput {
+	/// Whether the button that opens the menu is held.
+	pub held: bool,
+	/// Where the thumbstick or pointer is, relative to the middle of the menu. `x` is
+	/// right and `y` is up, and a length of 1 is the outside of the ring.
+	pub direction: Vec2,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RadialItem {
+	/// Sent in [`RadialMenuSelected`] when the item is chosen.
+	pub id: String,
+	pub label: String,
+	/// Shown above the label.
+	pub icon: Option<Handle<Image>>,
+	/// If there are any, highlighting the item opens them as a submenu, rather than
+	/// selecting it.
+	pub children: Vec<RadialItem>,
+}
+
+impl RadialItem {
+	pub fn new(id: impl Into<String>, label: impl Into<String>) -> Self {
+		Self {
+			id: id.into(),
+			label: label.into(),
+			..default()
+		}
+	}
+
+	pub fn with_icon(mut self, icon: Handle<Image>) -> Self {
+		self.icon = Some(icon);
+		self
+	}
+
+	pub fn with_children(mut self, children: Vec<RadialItem>) -> Self {
+		self.children = children;
+		self
+	}
+}
+
+/// A ring of [`RadialItem`]s, starting at the top and going clockwise. Needs a
+/// [`RadialMenuInput`], which the plugin adds if it is missing.
+#[derive(Component, Debug, Clone)]
+pub struct RadialMenu {
+	pub items: Vec<RadialItem>,
+	/// How far [`RadialMenuInput::direction`] has to be from the middle to highlight
+	/// a wedge. Letting go inside it closes the menu without selecting anything.
+	pub dead_zone: f32,
+	/// How long a wedge with children has to be highlighted to open them, in
+	/// seconds.
+	pub submenu_delay: f32,
+	open: bool,
+	just_opened: bool,
+	/// The index of each submenu that was opened, from the top.
+	path: Vec<usize>,
+	highlighted: Option<usize>,
+	/// How long `highlighted` has been highlighted, in seconds.
+	dwell: f32,
+	/// The wedge that was pointed at when a submenu opened or closed, which isn't
+	/// highlighted until something else is pointed at first.
+	blocked: Option<usize>,
+}
+
+impl RadialMenu {
+	pub fn new(items: Vec<RadialItem>) -> Self {
+		Self {
+			items,
+			dead_zone: 0.3,
+			submenu_delay: 0.4,
+			open: false,
+			just_opened: false,
+			path: Vec::new(),
+			highlighted: None,
+			dwell: 0.0,
+			blocked: None,
+		}
+	}
+
+	pub fn is_open(&self) -> bool {
+		self.open
+	}
+
+	/// Whether the menu opened this frame, for placing it.
+	pub fn just_opened(&self) -> bool {
+		self.just_opened
+	}
+
+	/// The items of the submenu being shown. In a submenu, the first wedge goes back
+	/// to the menu above, and isn't one of these.
+	pub fn current_items(&self) -> &[RadialItem] {
+		self.submenu().map_or(&self.items, |item| &item.children)
+	}
+
+	/// The item whose children are being shown, if any.
+	pub fn submenu(&self) -> Option<&RadialItem> {
+		let (first, rest) = self.path.split_first()?;
+		let mut item = &self.items[*first];
+		for index in rest {
+			item = &item.children[*index];
+		}
+		Some(item)
+	}
+
+	/// Which wedge is highlighted. In a submenu, wedge 0 is the one that goes back.
+	pub fn highlighted(&self) -> Option<usize> {
+		self.highlighted
+	}
+
+	/// How many wedges the ring has, including the one that goes back.
+	pub fn wedge_count(&self) -> usize {
+		self.current_items().len() + usize::from(!self.path.is_empty())
+	}
+
+	/// The item in a wedge, or `None` for the wedge that goes back.
+	pub fn wedge_item(&self, wedge: usize) -> Option<&RadialItem> {
+		let offset = usize::from(!self.path.is_empty());
+		wedge
+			.checked_sub(offset)
+			.and_then(|index| self.current_items().get(index))
+	}
+
+	/// The wedge that `direction` points at, if it is outside the dead zone.
+	pub fn wedge_at(&self, direction: Vec2) -> Option<usize> {
+		let count = self.wedge_count();
+		if count == 0 || direction.length() < self.dead_zone {
+			return None;
+		}
+		let wedge_angle = std::f32::consts::TAU / count as f32;
+		// Clockwise from the top, with the top wedge centred on it.
+		let angle = direction.x.atan2(direction.y) + wedge_angle / 2.0;
+		Some((angle.rem_euclid(std::f32::consts::TAU) / wedge_angle) as usize % count)
+	}
+
+	fn close(&mut self) {
+		self.open = false;
+		self.path.clear();
+		self.highlighted = None;
+		self.dwell = 0.0;
+		self.blocked = None;
+	}
+}
+
+fn update_menus(
+	mut commands: Commands,
+	mut menus: Query<(Entity, &mut RadialMenu, Option<&RadialMenuInput>)>,
+	mut selected: EventWriter<RadialMenuSelected>,
+	time: Res<Time>,
+) {
+	for (entity, mut menu, input) in menus.iter_mut() {
+		let Some(input) = input else {
+			commands.entity(entity).insert(RadialMenuInput::default());
+			continue;
+		};
+		let menu = &mut *menu;
+		menu.just_opened = input.held && !menu.open;
+		if menu.just_opened {
+			menu.open = true;
+		}
+		if !menu.open {
+			continue;
+		}
+		if !input.held {
+			if let Some(item) =
+				menu.highlighted.and_then(|wedge| menu.wedge_item(wedge))
+			{
+				if item.children.is_empty() {
+					selected.send(RadialMenuSelected {
+						menu: entity,
+						id: item.id.clone(),
+					});
+				}
+			}
+			menu.close();
+			continue;
+		}
+
+		let mut highlighted = menu.wedge_at(input.direction);
+		if highlighted == menu.blocked {
+			highlighted = None;
+		} else {
+			menu.blocked = None;
+		}
+		if highlighted != menu.highlighted {
+			menu.highlighted = highlighted;
+			menu.dwell = 0.0;
+			continue;
+		}
+		let Some(wedge) = highlighted else {
+			continue;
+		};
+		menu.dwell += time.delta_seconds();
+		if menu.dwell < menu.submenu_delay {
+			continue;
+		}
+		match menu.wedge_item(wedge) {
+			Some(item) if !item.children.is_empty() => {
+				let index = wedge - usize::from(!menu.path.is_empty());
+				menu.path.push(index);
+			}
+			None => {
+				menu.path.pop();
+			}
+			Some(_) => continue,
+		}
+		// Otherwise the stick or pointer would choose from the new ring before it
+		// moved.
+		menu.blocked = menu.wedge_at(input.direction);
+		menu.highlighted = None;
+		menu.dwell = 0.0;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+
+	use bevy::time::TimeUpdateStrategy;
+
+	use super::*;
+
+	/// Points `degrees` clockwise from the top, at `length` from the middle.
+	fn towards(degrees: f32, length: f32) -> Vec2 {
+		let radians = degrees.to_radians();
+		Vec2::new(radians.sin(), radians.cos()) * length
+	}
+
+	fn items(count: usize) -> Vec<RadialItem> {
+		(0..count)
+			.map(|i| RadialItem::new(i.to_string(), i.to_string()))
+			.collect()
+	}
+
+	#[test]
+	fn wedges_start_at_the_top_and_go_clockwise() {
+		let menu = RadialMenu::new(items(4));
+		assert_eq!(menu.wedge_at(towards(0.0, 1.0)), Some(0));
+		assert_eq!(menu.wedge_at(towards(90.0, 1.0)), Some(1));
+		assert_eq!(menu.wedge_at(towards(180.0, 1.0)), Some(2));
+		assert_eq!(menu.wedge_at(towards(270.0, 1.0)), Some(3));
+		// The top wedge is centred on the top, so its edges are 45 degrees either
+		// side.
+		assert_eq!(menu.wedge_at(towards(44.0, 1.0)), Some(0));
+		assert_eq!(menu.wedge_at(towards(46.0, 1.0)), Some(1));
+		assert_eq!(menu.wedge_at(towards(-44.0, 1.0)), Some(0));
+		assert_eq!(menu.wedge_at(towards(-46.0, 1.0)), Some(3));
+		// Past the outside of the ring still counts.
+		assert_eq!(menu.wedge_at(towards(180.0, 3.0)), Some(2));
+	}
+
+	#[test]
+	fn dead_zone_highlights_nothing() {
+		let menu = RadialMenu::new(items(3));
+		assert_eq!(menu.wedge_at(Vec2::ZERO), None);
+		assert_eq!(menu.wedge_at(towards(0.0, menu.dead_zone - 0.01)), None);
+		assert_eq!(menu.wedge_at(towards(0.0, menu.dead_zone + 0.01)), Some(0));
+		assert_eq!(
+			RadialMenu::new(Vec::new()).wedge_at(towards(0.0, 1.0)),
+			None
+		);
+	}
+
+	const STEP: Duration = Duration::from_millis(100);
+
+	fn menu_app(menu: RadialMenu) -> (App, Entity) {
+		let mut app = App::new();
+		app.add_plugins((MinimalPlugins, RadialMenuPlugin))
+			.insert_resource(TimeUpdateStrategy::ManualDuration(STEP));
+		let menu = app.world.spawn((menu, RadialMenuInput::default())).id();
+		// The first frame has no delta.
+		app.update();
+		(app, menu)
+	}
+
+	fn step(app: &mut App, entity: Entity, held: bool, direction: Vec2) -> Vec<String> {
+		*app.world.get_mut::<RadialMenuInput>(entity).unwrap() =
+			RadialMenuInput { held, direction };
+		app.update();
+		let events = app.world.resource::<Events<RadialMenuSelected>>();
+		events
+			.iter_current_update_events()
+			.map(|event| event.id.clone())
+			.collect()
+	}
+
+	fn menu(app: &App, entity: Entity) -> &RadialMenu {
+		app.world.get::<RadialMenu>(entity).unwrap()
+	}
+
+	/// Two items, where the top one has a submenu of two more.
+	fn nested_menu() -> RadialMenu {
+		let mut menu = RadialMenu::new(vec![
+			RadialItem::new("emotes", "Emotes").with_children(vec![
+				RadialItem::new("wave", "Wave"),
+				RadialItem::new("dance", "Dance"),
+			]),
+			RadialItem::new("tool", "Tool"),
+		]);
+		menu.submenu_delay = 0.35;
+		menu
+	}
+
+	#[test]
+	fn releasing_selects_the_highlighted_item() {
+		let (mut app, entity) = menu_app(nested_menu());
+		assert!(step(&mut app, entity, true, Vec2::ZERO).is_empty());
+		assert!(menu(&app, entity).just_opened());
+		assert!(step(&mut app, entity, true, towards(180.0, 1.0)).is_empty());
+		assert_eq!(menu(&app, entity).highlighted(), Some(1));
+		assert!(!menu(&app, entity).just_opened());
+		assert_eq!(step(&mut app, entity, false, towards(180.0, 1.0)), ["tool"]);
+		assert!(!menu(&app, entity).is_open());
+	}
+
+	#[test]
+	fn releasing_in_the_dead_zone_selects_nothing() {
+		let (mut app, entity) = menu_app(nested_menu());
+		step(&mut app, entity, true, towards(180.0, 1.0));
+		step(&mut app, entity, true, towards(180.0, 0.1));
+		assert_eq!(menu(&app, entity).highlighted(), None);
+		assert!(step(&mut app, entity, false, towards(180.0, 0.1)).is_empty());
+		assert!(!menu(&app, entity).is_open());
+	}
+
+	#[test]
+	fn dwelling_opens_and_closes_submenus() {
+		let (mut app, entity) = menu_app(nested_menu());
+		let up = towards(0.0, 1.0);
+		step(&mut app, entity, true, up);
+		assert_eq!(menu(&app, entity).highlighted(), Some(0));
+		// The submenu opens once the wedge was highlighted for `submenu_delay`.
+		for _ in 0..3 {
+			step(&mut app, entity, true, up);
+			assert!(menu(&app, entity).submenu().is_none());
+		}
+		step(&mut app, entity, true, up);
+		assert_eq!(menu(&app, entity).submenu().unwrap().id, "emotes");
+		assert_eq!(menu(&app, entity).wedge_count(), 3);
+
+		// The back wedge is at the top now, where the stick still points, but it isn't
+		// highlighted until the stick moves somewhere else first.
+		for _ in 0..5 {
+			step(&mut app, entity, true, up);
+			assert_eq!(menu(&app, entity).highlighted(), None);
+		}
+		step(&mut app, entity, true, towards(120.0, 1.0));
+		assert_eq!(menu(&app, entity).highlighted(), Some(1));
+		step(&mut app, entity, true, up);
+		assert_eq!(menu(&app, entity).highlighted(), Some(0));
+		for _ in 0..4 {
+			step(&mut app, entity, true, up);
+		}
+		assert!(menu(&app, entity).submenu().is_none());
+		assert_eq!(menu(&app, entity).highlighted(), None);
+	}
+
+	#[test]
+	fn releasing_in_a_submenu_selects_from_it() {
+		let (mut app, entity) = menu_app(nested_menu());
+		let up = towards(0.0, 1.0);
+		for _ in 0..5 {
+			step(&mut app, entity, true, up);
+		}
+		assert!(menu(&app, entity).submenu().is_some());
+		// Wedge 0 goes back, so the items follow it.
+		let dance = towards(240.0, 1.0);
+		step(&mut app, entity, true, dance);
+		assert_eq!(menu(&app, entity).wedge_item(2).unwrap().id, "dance");
+		assert_eq!(step(&mut app, entity, false, dance), ["dance"]);
+		assert!(menu(&app, entity).submenu().is_none());
+	}
+
+	#[test]
+	fn releasing_a_parent_early_selects_nothing() {
+		let (mut app, entity) = menu_app(nested_menu());
+		let up = towards(0.0, 1.0);
+		step(&mut app, entity, true, up);
+		step(&mut app, entity, true, up);
+		assert!(step(&mut app, entity, false, up).is_empty());
+		assert!(!menu(&app, entity).is_open());
+	}
+}
@@ -0,0 +1,102 @@
+use bevy::prelude::*;
+use bevy_oxr::xr_input::Hand;
+use bevy_oxr::DefaultXrPlugins;
+use openxr_6dof::poses::XrPosePlugin;
+use radial_menu::panel::PanelRadialMenuPlugin;
+use radial_menu::xr::{XrRadialMenu, XrRadialMenuPlugin};
+use radial_menu::{RadialItem, RadialMenu, RadialMenuPlugin, RadialMenuSelected};
+use worldspace_ui::{WorldspaceUi, WorldspaceUiPlugin};
+
+fn main() {
+	color_eyre::install().unwrap();
+
+	info!("Running `radial-menu` skill");
+	App::new()
+		.add_plugins(DefaultXrPlugins)
+		.add_plugins(XrPosePlugin)
+		.add_plugins((
+			WorldspaceUiPlugin,
+			RadialMenuPlugin,
+			PanelRadialMenuPlugin,
+			XrRadialMenuPlugin,
+		))
+		.add_systems(Startup, setup)
+		.add_systems(Update, apply_selection)
+		.run();
+}
+
+/// The cube that the menu changes.
+#[derive(Component)]
+struct Cube;
+
+fn setup(
+	mut commands: Commands,
+	mut meshes: ResMut<Assets<Mesh>>,
+	mut materials: ResMut<Assets<StandardMaterial>>,
+	mut images: ResMut<Assets<Image>>,
+) {
+	// the menu, opened with the right controller
+	let menu = RadialMenu::new(vec![
+		RadialItem::new("emotes", "Emotes").with_children(vec![
+			RadialItem::new("wave", "Wave"),
+			RadialItem::new("clap", "Clap"),
+			RadialItem::new("cheer", "Cheer"),
+		]),
+		RadialItem::new("red", "Red"),
+		RadialItem::new("green", "Green"),
+		RadialItem::new("blue", "Blue"),
+	]);
+	commands.spawn((
+		SpatialBundle::HIDDEN_IDENTITY,
+		WorldspaceUi::new(&mut images, Vec2::splat(0.25)),
+		menu,
+		XrRadialMenu { hand: Hand::Right },
+	));
+	// cube
+	commands.spawn((
+		PbrBundle {
+			mesh: meshes.add(shape::Cube::new(0.3).into()),
+			material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
+			transform: Transform::from_xyz(0.0, 1.0, -1.0),
+			..default()
+		},
+		Cube,
+	));
+	// floor
+	commands.spawn(PbrBundle {
+		mesh: meshes.add(shape::Plane::from_size(5.0).into()),
+		material: materials.add(Color::rgb(0.3, 0.5, 0.3).into()),
+		..default()
+	});
+	// light
+	commands.spawn(PointLightBundle {
+		point_light: PointLight {
+			intensity: 1500.0,
+			shadows_enabled: true,
+			..default()
+		},
+		transform: Transform::from_xyz(4.0, 8.0, 4.0),
+		..default()
+	});
+}
+
+fn apply_selection(
+	mut selected: EventReader<RadialMenuSelected>,
+	cubes: Query<&Handle<StandardMaterial>, With<Cube>>,
+	mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+	for selected in selected.read() {
+		let color = match selected.id.as_str() {
+			"red" => Color::RED,
+			"green" => Color::GREEN,
+			"blue" => Color::BLUE,
+			emote => {
+				info!("emote: {emote}");
+				continue;
+			}
+		};
+		for material in cubes.iter() {
+			materials.get_mut(material).unwrap().base_color = color;
+		}
+	}
+}
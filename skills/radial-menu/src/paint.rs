@@ -0,0 +1,102 @@
+//! Draws a [`RadialMenu`] with egui, so that the same menu looks the same on the
+//! screen and on a world-space panel.
+
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::RadialMenu;
+
+/// Segments in the arc of each wedge.
+const ARC_SEGMENTS: usize = 16;
+/// Gap between wedges, in radians.
+const WEDGE_GAP: f32 = 0.03;
+/// The ring's thickness, as a fraction of its radius.
+const RING_WIDTH: f32 = 0.5;
+
+/// Draws `menu` as a ring of `radius` points around `center`. `icon` gives the egui
+/// texture of an item's icon, which depends on where the menu is drawn.
+pub(crate) fn paint_menu(
+	painter: &egui::Painter,
+	menu: &RadialMenu,
+	center: egui::Pos2,
+	radius: f32,
+	mut icon: impl FnMut(&Handle<Image>) -> Option<egui::TextureId>,
+) {
+	let visuals = painter.ctx().style().visuals.clone();
+	let count = menu.wedge_count();
+	let ring_width = radius * RING_WIDTH;
+	let middle = radius - ring_width / 2.0;
+	let wedge_angle = TAU / count.max(1) as f32;
+	// Where the wedge's angle points, clockwise from the top. Screen y is down.
+	let at = |angle: f32, distance: f32| {
+		center + egui::vec2(angle.sin(), -angle.cos()) * distance
+	};
+
+	for wedge in 0..count {
+		let highlighted = menu.highlighted() == Some(wedge);
+		let fill = if highlighted {
+			visuals.selection.bg_fill
+		} else {
+			visuals.window_fill
+		};
+		let start = (wedge as f32 - 0.5) * wedge_angle + WEDGE_GAP / 2.0;
+		let end = (wedge as f32 + 0.5) * wedge_angle - WEDGE_GAP / 2.0;
+		// A thick arc, since egui can only fill convex shapes.
+		let arc = (0..=ARC_SEGMENTS)
+			.map(|i| {
+				let t = i as f32 / ARC_SEGMENTS as f32;
+				at(start + (end - start) * t, middle)
+			})
+			.collect();
+		painter.add(egui::Shape::line(arc, egui::Stroke::new(ring_width, fill)));
+
+		let item = menu.wedge_item(wedge);
+		let label = item.map_or("Back", |item| item.label.as_str());
+		let text_color = if highlighted {
+			visuals.selection.stroke.color
+		} else {
+			visuals.text_color()
+		};
+		let position = at(wedge as f32 * wedge_angle, middle);
+		let texture = item.and_then(|item| item.icon.as_ref()).and_then(&mut icon);
+		let label_position = if let Some(texture) = texture {
+			let size = egui::Vec2::splat(ring_width * 0.45);
+			let rect = egui::Rect::from_center_size(
+				position - egui::vec2(0.0, size.y * 0.3),
+				size,
+			);
+			painter.image(
+				texture,
+				rect,
+				egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+				egui::Color32::WHITE,
+			);
+			position + egui::vec2(0.0, size.y * 0.45)
+		} else {
+			position
+		};
+		painter.text(
+			label_position,
+			egui::Align2::CENTER_CENTER,
+			label,
+			egui::FontId::proportional(ring_width * 0.2),
+			text_color,
+		);
+	}
+
+	// The middle names the submenu, or the item that would be chosen.
+	let title = match menu.highlighted() {
+		Some(wedge) => menu.wedge_item(wedge).map_or("Back", |item| &item.label),
+		None => menu.submenu().map_or("", |item| &item.label),
+	};
+	painter.circle_filled(center, radius - ring_width - 4.0, visuals.window_fill);
+	painter.text(
+		center,
+		egui::Align2::CENTER_CENTER,
+		title,
+		egui::FontId::proportional(ring_width * 0.25),
+		visuals.text_color(),
+	);
+}
@@ -0,0 +1,79 @@
+//! Radial menus on world-space panels, for VR.
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use worldspace_ui::pointer::WorldspacePointer;
+use worldspace_ui::WorldspaceUi;
+
+use crate::paint::paint_menu;
+use crate::{RadialMenu, RadialMenuInput, RadialMenuSet};
+
+/// Space between the ring and the edge of the panel, in points.
+const MARGIN: f32 = 4.0;
+
+/// Draws the [`RadialMenu`] of each entity with a [`WorldspaceUi`] on its panel, which
+/// is only visible while the menu is open. A [`WorldspacePointer`] on the panel
+/// chooses the wedge it points at.
+pub struct PanelRadialMenuPlugin;
+
+impl Plugin for PanelRadialMenuPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_systems(
+			Update,
+			(
+				pointer_input.before(RadialMenuSet),
+				draw_menus.after(RadialMenuSet),
+			),
+		);
+	}
+}
+
+/// The ring's centre and outer radius on the panel, in points.
+fn ring(panel: &WorldspaceUi) -> (egui::Pos2, f32) {
+	let size = panel.size_in_points();
+	((size / 2.0).to_pos2(), size.min_elem() / 2.0 - MARGIN)
+}
+
+fn pointer_input(
+	pointers: Query<&WorldspacePointer>,
+	mut menus: Query<(Entity, &WorldspaceUi, &mut RadialMenuInput), With<RadialMenu>>,
+) {
+	for pointer in pointers.iter() {
+		let Some((entity, pos)) = pointer.hovered else {
+			continue;
+		};
+		let Ok((_, panel, mut input)) = menus.get_mut(entity) else {
+			continue;
+		};
+		let (center, radius) = ring(panel);
+		let offset = (pos - center) / radius;
+		input.direction = Vec2::new(offset.x, -offset.y);
+	}
+}
+
+fn draw_menus(
+	mut menus: Query<(Entity, &RadialMenu, &mut WorldspaceUi, &mut Visibility)>,
+) {
+	for (entity, menu, mut panel, mut visibility) in menus.iter_mut() {
+		let wanted = if menu.is_open() {
+			Visibility::Inherited
+		} else {
+			Visibility::Hidden
+		};
+		if *visibility != wanted {
+			*visibility = wanted;
+		}
+		if !menu.is_open() {
+			continue;
+		}
+		let (center, radius) = ring(&panel);
+		let ctx = panel.ctx_mut().clone();
+		let painter = ctx.layer_painter(egui::LayerId::new(
+			egui::Order::Foreground,
+			egui::Id::new(("radial menu", entity)),
+		));
+		paint_menu(&painter, menu, center, radius, |icon| {
+			Some(panel.add_image(icon.clone()))
+		});
+	}
+}
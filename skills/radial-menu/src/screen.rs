@@ -0,0 +1,103 @@
+//! Radial menus on a flatscreen, opened under the mouse cursor and chosen from by
+//! moving it.
+
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::paint::paint_menu;
+use crate::{RadialMenu, RadialMenuInput, RadialMenuSet};
+
+/// Drives and draws the [`RadialMenu`]s of [`ScreenRadialMenu`] entities. Needs the
+/// `EguiPlugin`.
+pub struct ScreenRadialMenuPlugin;
+
+impl Plugin for ScreenRadialMenuPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_systems(
+			Update,
+			(
+				cursor_input.before(RadialMenuSet),
+				draw_menus.after(RadialMenuSet),
+			),
+		);
+	}
+}
+
+/// Opens the entity's [`RadialMenu`] where the cursor is while `trigger` is held.
+/// Moving the cursor away from there chooses a wedge.
+#[derive(Component, Debug, Clone)]
+pub struct ScreenRadialMenu {
+	pub trigger: ScreenTrigger,
+	/// Outer radius of the ring, in logical pixels.
+	pub radius: f32,
+	/// Where the menu opened, in logical pixels from the top left of the window.
+	center: Option<Vec2>,
+}
+
+impl ScreenRadialMenu {
+	pub fn new(trigger: ScreenTrigger) -> Self {
+		Self {
+			trigger,
+			radius: 150.0,
+			center: None,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenTrigger {
+	Key(KeyCode),
+	Mouse(MouseButton),
+}
+
+fn cursor_input(
+	keys: Res<Input<KeyCode>>,
+	buttons: Res<Input<MouseButton>>,
+	windows: Query<&Window, With<PrimaryWindow>>,
+	mut menus: Query<(&mut ScreenRadialMenu, &mut RadialMenuInput)>,
+) {
+	let Ok(window) = windows.get_single() else {
+		return;
+	};
+	// The middle of the window, when the cursor is grabbed or outside of it.
+	let cursor = window
+		.cursor_position()
+		.unwrap_or(Vec2::new(window.width(), window.height()) / 2.0);
+	for (mut screen, mut input) in menus.iter_mut() {
+		input.held = match screen.trigger {
+			ScreenTrigger::Key(key) => keys.pressed(key),
+			ScreenTrigger::Mouse(button) => buttons.pressed(button),
+		};
+		if !input.held {
+			screen.center = None;
+			continue;
+		}
+		let center = *screen.center.get_or_insert(cursor);
+		let offset = (cursor - center) / screen.radius;
+		input.direction = Vec2::new(offset.x, -offset.y);
+	}
+}
+
+fn draw_menus(
+	mut contexts: EguiContexts,
+	menus: Query<(Entity, &RadialMenu, &ScreenRadialMenu)>,
+) {
+	let ctx = contexts.ctx_mut().clone();
+	for (entity, menu, screen) in menus.iter() {
+		let Some(center) = screen.center.filter(|_| menu.is_open()) else {
+			continue;
+		};
+		let painter = ctx.layer_painter(egui::LayerId::new(
+			egui::Order::Foreground,
+			egui::Id::new(("radial menu", entity)),
+		));
+		paint_menu(
+			&painter,
+			menu,
+			egui::pos2(center.x, center.y),
+			screen.radius,
+			|icon| Some(contexts.add_image(icon.clone_weak())),
+		);
+	}
+}
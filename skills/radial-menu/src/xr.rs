@@ -0,0 +1,83 @@
+//! Opens radial menus from OpenXR controllers. This is the only part of the crate
+//! that needs a headset.
+
+use bevy::prelude::*;
+use bevy_oxr::input::XrInput;
+use bevy_oxr::resources::{XrFrameState, XrInstance, XrSession};
+use bevy_oxr::xr_input::oculus_touch::OculusController;
+use bevy_oxr::xr_input::trackers::OpenXRTrackingRoot;
+use bevy_oxr::xr_input::Hand;
+use openxr_6dof::poses::XrPoses;
+
+use crate::{RadialMenu, RadialMenuInput, RadialMenuSet};
+
+/// How far in front of the controller a menu opens, in metres.
+const MENU_DISTANCE: f32 = 0.2;
+
+/// Drives the [`RadialMenu`]s of [`XrRadialMenu`] entities from the Oculus Touch
+/// controllers, and opens them in front of the controller, facing the head. Draw them
+/// with the [`PanelRadialMenuPlugin`](crate::panel::PanelRadialMenuPlugin). Needs the
+/// [`XrPosePlugin`](openxr_6dof::poses::XrPosePlugin).
+///
+/// - Y (left hand) or B (right hand): hold to open, let go to choose
+/// - Thumbstick of the same hand: choose a wedge
+pub struct XrRadialMenuPlugin;
+
+impl Plugin for XrRadialMenuPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_systems(PreUpdate, controller_input)
+			.add_systems(Update, place_menus.after(RadialMenuSet));
+	}
+}
+
+/// Opens the entity's [`RadialMenu`] with a controller.
+#[derive(Component, Debug, Clone)]
+pub struct XrRadialMenu {
+	pub hand: Hand,
+}
+
+fn controller_input(
+	oculus_controller: Res<OculusController>,
+	frame_state: Res<XrFrameState>,
+	xr_input: Res<XrInput>,
+	instance: Res<XrInstance>,
+	session: Res<XrSession>,
+	mut menus: Query<(&XrRadialMenu, &mut RadialMenuInput)>,
+) {
+	let frame_state = *frame_state.lock().unwrap();
+	let controller =
+		oculus_controller.get_ref(&instance, &session, &frame_state, &xr_input);
+	for (xr_menu, mut input) in menus.iter_mut() {
+		input.held = match xr_menu.hand {
+			Hand::Left => controller.y_button(),
+			Hand::Right => controller.b_button(),
+		};
+		let thumbstick = controller.thumbstick(xr_menu.hand);
+		input.direction = Vec2::new(thumbstick.x, thumbstick.y);
+	}
+}
+
+/// Poses are relative to the stage, so the menus are moved into the tracking root.
+fn place_menus(
+	mut commands: Commands,
+	poses: Res<XrPoses>,
+	roots: Query<Entity, With<OpenXRTrackingRoot>>,
+	mut menus: Query<(Entity, &RadialMenu, &XrRadialMenu, &mut Transform)>,
+) {
+	let Ok(root) = roots.get_single() else {
+		return;
+	};
+	for (entity, menu, xr_menu, mut transform) in menus.iter_mut() {
+		if !menu.just_opened() {
+			continue;
+		}
+		let (Some(aim), Some(head)) = (poses.aim(xr_menu.hand), poses.head) else {
+			continue;
+		};
+		commands.entity(root).add_child(entity);
+		let position = aim.translation + aim.forward() * MENU_DISTANCE;
+		// Panels face +Z, so look directly away from the head.
+		*transform = Transform::from_translation(position)
+			.looking_at(position * 2.0 - head.translation, Vec3::Y);
+	}
+}
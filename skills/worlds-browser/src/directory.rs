@@ -0,0 +1,232 @@
+//! Where the list of worlds comes from.
+
+use std::path::{Path, PathBuf};
+
+use bevy::log::warn;
+use bevy::utils::HashMap;
+use eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize};
+
+/// The extension of world manifest files, which are JSON.
+pub const MANIFEST_EXTENSION: &str = "world.json";
+
+/// Describes a world: what it is called, what it looks like, and where its scene
+/// is. Paths in it are relative to the manifest file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorldManifest {
+	pub id: String,
+	pub name: String,
+	#[serde(default)]
+	pub description: String,
+	/// An image shown in the browser.
+	#[serde(default)]
+	pub thumbnail: Option<PathBuf>,
+	/// The Bevy scene (`.scn.ron`) or glTF to load for the world.
+	pub scene: PathBuf,
+	/// How many players fit in one instance of the world.
+	pub capacity: u32,
+	#[serde(default)]
+	pub tags: Vec<String>,
+}
+
+impl WorldManifest {
+	pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+		let path = path.as_ref();
+		let json = std::fs::read_to_string(path)
+			.wrap_err_with(|| format!("failed to read {}", path.display()))?;
+		serde_json::from_str(&json)
+			.wrap_err_with(|| format!("failed to parse {}", path.display()))
+	}
+}
+
+/// A world that can be joined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorldListing {
+	pub manifest: WorldManifest,
+	pub source: WorldSource,
+	/// How many players are in the world right now.
+	pub players: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorldSource {
+	/// From a manifest on disk, in this directory.
+	Local { dir: PathBuf },
+	/// Advertised by a server, at this address.
+	Advertised { address: String },
+}
+
+impl WorldListing {
+	pub fn is_full(&self) -> bool {
+		self.players >= self.manifest.capacity
+	}
+
+	/// Where a path from the manifest is on disk, for local worlds.
+	pub fn local_path(&self, path: &Path) -> Option<PathBuf> {
+		match &self.source {
+			WorldSource::Local { dir } => Some(dir.join(path)),
+			WorldSource::Advertised { .. } => None,
+		}
+	}
+}
+
+/// Lists the worlds that can be joined, such as a matchmaking server or a LAN
+/// discovery service.
+///
+/// [`Worlds`](crate::Worlds) asks for the whole list every
+/// [`refresh_timer`](crate::Worlds::refresh_timer), on the main thread. Player counts
+/// change all the time, so a directory backed by a server should poll it in the
+/// background and return the latest list it got.
+pub trait WorldDirectory: Send + Sync + 'static {
+	fn worlds(&self) -> Result<Vec<WorldListing>>;
+}
+
+/// Lists every `*.world.json` manifest in one directory as a local world, with
+/// player counts set by hand in [`Self::players`]. Worlds passed to
+/// [`Self::advertise`] are listed too, standing in for a matchmaking server.
+/// Manifests that can't be read are skipped with a warning.
+#[derive(Debug, Default, Clone)]
+pub struct LocalWorldDirectory {
+	pub dir: PathBuf,
+	/// How many players are in each local world, by id. Zero if missing.
+	pub players: HashMap<String, u32>,
+	pub advertised: Vec<WorldListing>,
+}
+
+impl LocalWorldDirectory {
+	pub fn new(dir: impl Into<PathBuf>) -> Self {
+		Self {
+			dir: dir.into(),
+			..Default::default()
+		}
+	}
+
+	/// Lists a world as if a server advertised it.
+	pub fn advertise(&mut self, listing: WorldListing) {
+		self.advertised.push(listing);
+	}
+}
+
+impl WorldDirectory for LocalWorldDirectory {
+	fn worlds(&self) -> Result<Vec<WorldListing>> {
+		let entries = std::fs::read_dir(&self.dir)
+			.wrap_err_with(|| format!("failed to read {}", self.dir.display()))?;
+		let mut worlds = Vec::new();
+		for entry in entries {
+			let path = entry?.path();
+			let is_manifest = path
+				.file_name()
+				.and_then(|name| name.to_str())
+				.is_some_and(|name| name.ends_with(&format!(".{MANIFEST_EXTENSION}")));
+			if !is_manifest {
+				continue;
+			}
+			let manifest = match WorldManifest::load(&path) {
+				Ok(manifest) => manifest,
+				Err(err) => {
+					warn!("skipping {}: {err:#}", path.display());
+					continue;
+				}
+			};
+			worlds.push(WorldListing {
+				players: self.players.get(&manifest.id).copied().unwrap_or(0),
+				manifest,
+				source: WorldSource::Local {
+					dir: self.dir.clone(),
+				},
+			});
+		}
+		worlds.extend(self.advertised.iter().cloned());
+		Ok(worlds)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// An empty directory of its own for each test, since tests run in parallel.
+	fn temp_dir(name: &str) -> PathBuf {
+		let dir = std::env::temp_dir()
+			.join(format!("worlds-browser-{}-{name}", std::process::id()));
+		let _ = std::fs::remove_dir_all(&dir);
+		std::fs::create_dir_all(&dir).unwrap();
+		dir
+	}
+
+	#[test]
+	fn parses_manifests_with_defaults() {
+		let manifest: WorldManifest = serde_json::from_str(
+			r#"{ "id": "grove", "name": "The Grove", "scene": "grove.scn.ron", "capacity": 12 }"#,
+		)
+		.unwrap();
+		assert_eq!(
+			manifest,
+			WorldManifest {
+				id: "grove".to_owned(),
+				name: "The Grove".to_owned(),
+				description: String::new(),
+				thumbnail: None,
+				scene: "grove.scn.ron".into(),
+				capacity: 12,
+				tags: Vec::new(),
+			}
+		);
+		assert!(serde_json::from_str::<WorldManifest>(r#"{ "id": "grove" }"#).is_err());
+	}
+
+	#[test]
+	fn loads_the_example_manifests() {
+		let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("worlds");
+		let manifest = WorldManifest::load(dir.join("grove.world.json")).unwrap();
+		assert_eq!(manifest.id, "grove");
+		assert_eq!(manifest.tags, ["chill", "nature"]);
+	}
+
+	#[test]
+	fn lists_local_and_advertised_worlds() {
+		let dir = temp_dir("listing");
+		let manifest = |id: &str| {
+			format!(
+				r#"{{ "id": "{id}", "name": "{id}", "scene": "{id}.glb", "capacity": 4 }}"#
+			)
+		};
+		std::fs::write(dir.join("a.world.json"), manifest("a")).unwrap();
+		std::fs::write(dir.join("b.world.json"), manifest("b")).unwrap();
+		std::fs::write(dir.join("broken.world.json"), "{").unwrap();
+		std::fs::write(dir.join("notes.json"), manifest("notes")).unwrap();
+
+		let mut directory = LocalWorldDirectory::new(&dir);
+		directory.players.insert("a".to_owned(), 3);
+		let mut advertised = WorldManifest::load(dir.join("b.world.json")).unwrap();
+		advertised.id = "c".to_owned();
+		directory.advertise(WorldListing {
+			manifest: advertised,
+			source: WorldSource::Advertised {
+				address: "c.example.com:7777".to_owned(),
+			},
+			players: 1,
+		});
+		let mut worlds = directory.worlds().unwrap();
+		std::fs::remove_dir_all(&dir).unwrap();
+
+		worlds.sort_by(|a, b| a.manifest.id.cmp(&b.manifest.id));
+		let found: Vec<(&str, u32)> = worlds
+			.iter()
+			.map(|world| (world.manifest.id.as_str(), world.players))
+			.collect();
+		assert_eq!(found, [("a", 3), ("b", 0), ("c", 1)]);
+		assert_eq!(worlds[0].source, WorldSource::Local { dir: dir.clone() });
+		assert_eq!(
+			worlds[0].local_path(Path::new("a.glb")),
+			Some(dir.join("a.glb"))
+		);
+		assert_eq!(worlds[2].local_path(Path::new("c.glb")), None);
+	}
+
+	#[test]
+	fn missing_directory_is_an_error() {
+		let directory = LocalWorldDirectory::new(temp_dir("missing").join("nope"));
+		assert!(directory.worlds().is_err());
+	}
+}
@@ -0,0 +1,175 @@
+//! A browser for the worlds that can be joined, backed by any [`WorldDirectory`].
+//!
+//! Worlds are described by a [`WorldManifest`]. The [`Worlds`] resource holds the
+//! directory and a cached copy of its list, which is refreshed on a timer. A
+//! [`WorldsBrowser`] draws it with egui, either on a [`WorldspaceUi`] panel that it
+//! is on, or in any other egui `Ui` with [`WorldsBrowser::ui`]. Joining a world sends
+//! [`JoinWorld`].
+
+mod directory;
+mod ui;
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_egui::egui;
+use worldspace_ui::WorldspaceUi;
+
+pub use crate::directory::{
+	LocalWorldDirectory, WorldDirectory, WorldListing, WorldManifest, WorldSource,
+	MANIFEST_EXTENSION,
+};
+pub use crate::ui::{SourceFilter, WorldsBrowser};
+
+/// Refreshes [`Worlds`], loads their thumbnails, and draws the [`WorldsBrowser`]s on
+/// world-space panels. Nothing happens until the app inserts the [`Worlds`]
+/// resource.
+pub struct WorldsBrowserPlugin;
+
+impl Plugin for WorldsBrowserPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_event::<JoinWorld>().add_systems(
+			Update,
+			(refresh_worlds, load_thumbnails, panel_ui)
+				.chain()
+				.run_if(resource_exists::<Worlds>()),
+		);
+	}
+}
+
+/// Sent when the user asks to join a world. Loading it is up to the app.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct JoinWorld {
+	pub world: WorldListing,
+}
+
+/// The worlds that can be joined, from a [`WorldDirectory`].
+#[derive(Resource)]
+pub struct Worlds {
+	directory: Box<dyn WorldDirectory>,
+	/// Sorted by how many players are in them, then name.
+	list: Vec<WorldListing>,
+	/// The last thing that went wrong with the directory, shown in the browser.
+	error: Option<String>,
+	/// Thumbnails of local worlds, by path.
+	thumbnails: HashMap<PathBuf, Handle<Image>>,
+	/// Asks the directory for the list again each time it finishes, to keep player
+	/// counts current. Repeats every 10 seconds by default. The list is also fetched
+	/// as soon as the resource is added.
+	pub refresh_timer: Timer,
+}
+
+impl Worlds {
+	pub fn new(directory: impl WorldDirectory) -> Self {
+		Self {
+			directory: Box::new(directory),
+			list: Vec::new(),
+			error: None,
+			thumbnails: HashMap::default(),
+			refresh_timer: Timer::new(Duration::from_secs(10), TimerMode::Repeating),
+		}
+	}
+
+	pub fn list(&self) -> &[WorldListing] {
+		&self.list
+	}
+
+	pub fn error(&self) -> Option<&str> {
+		self.error.as_deref()
+	}
+
+	/// The world's thumbnail, once it has started loading.
+	pub fn thumbnail(&self, world: &WorldListing) -> Option<&Handle<Image>> {
+		let path = world.local_path(world.manifest.thumbnail.as_ref()?)?;
+		self.thumbnails.get(&path)
+	}
+
+	/// Every tag of every world, sorted and without duplicates.
+	pub fn tags(&self) -> Vec<&str> {
+		let mut tags: Vec<&str> = self
+			.list
+			.iter()
+			.flat_map(|world| world.manifest.tags.iter().map(String::as_str))
+			.collect();
+		tags.sort_unstable();
+		tags.dedup();
+		tags
+	}
+
+	pub fn directory_mut(&mut self) -> &mut dyn WorldDirectory {
+		self.directory.as_mut()
+	}
+
+	/// Asks the directory for the list now, rather than waiting for the next
+	/// refresh.
+	pub fn refresh(&mut self) {
+		match self.directory.worlds() {
+			Ok(mut list) => {
+				list.sort_by(|a, b| {
+					b.players
+						.cmp(&a.players)
+						.then_with(|| a.manifest.name.cmp(&b.manifest.name))
+				});
+				self.list = list;
+				self.error = None;
+			}
+			Err(err) => {
+				warn!("failed to get worlds: {err:#}");
+				self.error = Some(format!("{err:#}"));
+			}
+		}
+	}
+}
+
+fn refresh_worlds(mut worlds: ResMut<Worlds>, time: Res<Time>) {
+	// Ticking shouldn't count as the list changing.
+	let due = worlds
+		.bypass_change_detection()
+		.refresh_timer
+		.tick(time.delta())
+		.just_finished();
+	if due || worlds.is_added() {
+		worlds.refresh();
+	}
+}
+
+fn load_thumbnails(mut worlds: ResMut<Worlds>, assets: Res<AssetServer>) {
+	if !worlds.is_changed() {
+		return;
+	}
+	// Only the list changing matters, not the thumbnails.
+	let worlds = worlds.bypass_change_detection();
+	for world in worlds.list.iter() {
+		let Some(path) = world
+			.manifest
+			.thumbnail
+			.as_ref()
+			.and_then(|thumbnail| world.local_path(thumbnail))
+		else {
+			continue;
+		};
+		worlds
+			.thumbnails
+			.entry(path)
+			.or_insert_with_key(|path| assets.load(path.clone()));
+	}
+}
+
+fn panel_ui(
+	mut panels: Query<(&mut WorldspaceUi, &mut WorldsBrowser)>,
+	worlds: Res<Worlds>,
+	mut join: EventWriter<JoinWorld>,
+) {
+	for (mut panel, mut browser) in panels.iter_mut() {
+		let ctx = panel.ctx_mut().clone();
+		egui::CentralPanel::default().show(&ctx, |ui| {
+			let joined =
+				browser.ui(ui, &worlds, |image| Some(panel.add_image(image.clone())));
+			if let Some(world) = joined {
+				join.send(JoinWorld { world });
+			}
+		});
+	}
+}
@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use bevy_flycam::FlyCam;
+use color_eyre::Result;
+use worlds_browser::{
+	JoinWorld, LocalWorldDirectory, WorldListing, WorldManifest, WorldSource, Worlds,
+	WorldsBrowser, WorldsBrowserPlugin,
+};
+use worldspace_ui::pointer::add_mouse_pointer;
+use worldspace_ui::{WorldspaceUi, WorldspaceUiPlugin};
+
+const WORLDS_FOLDER: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/worlds");
+
+fn main() -> Result<()> {
+	color_eyre::install()?;
+
+	let mut directory = LocalWorldDirectory::new(WORLDS_FOLDER);
+	directory.players.insert("grove".to_owned(), 3);
+	// Made up servers, since there is no matchmaking server to ask.
+	directory.advertise(advertised("arena", "Arena", 16, 16, &["games", "pvp"]));
+	directory.advertise(advertised(
+		"rooftop",
+		"Rooftop Bar",
+		6,
+		20,
+		&["chill", "music"],
+	));
+	directory.advertise(advertised("gallery", "Avatar Gallery", 0, 10, &["avatars"]));
+	App::new()
+		.add_plugins(DefaultPlugins)
+		.add_plugins(bevy_flycam::PlayerPlugin)
+		.add_plugins(bevy_egui::EguiPlugin)
+		.add_plugins((WorldspaceUiPlugin, WorldsBrowserPlugin))
+		.insert_resource(Worlds::new(directory))
+		.add_systems(Startup, setup)
+		.add_systems(
+			Update,
+			(screenspace_ui, join_worlds, add_mouse_pointer::<FlyCam>),
+		)
+		.run();
+
+	Ok(())
+}
+
+fn advertised(
+	id: &str,
+	name: &str,
+	players: u32,
+	capacity: u32,
+	tags: &[&str],
+) -> WorldListing {
+	WorldListing {
+		manifest: WorldManifest {
+			id: id.to_owned(),
+			name: name.to_owned(),
+			description: String::new(),
+			thumbnail: None,
+			scene: format!("{id}.scn.ron").into(),
+			capacity,
+			tags: tags.iter().map(|tag| tag.to_string()).collect(),
+		},
+		source: WorldSource::Advertised {
+			address: format!("{id}.example.com:7777"),
+		},
+		players,
+	}
+}
+
+fn setup(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+	let panel = WorldspaceUi::menu(&mut images, Vec2::new(0.6, 0.5));
+	commands.spawn((
+		SpatialBundle::from_transform(Transform::from_xyz(0.0, 0.0, -0.5)),
+		panel,
+		WorldsBrowser::default(),
+	));
+}
+
+/// The same browser, on the screen.
+fn screenspace_ui(
+	mut contexts: EguiContexts,
+	mut browser: Local<WorldsBrowser>,
+	worlds: Res<Worlds>,
+	mut join: EventWriter<JoinWorld>,
+) {
+	let ctx = contexts.ctx_mut().clone();
+	egui::Window::new("Worlds").show(&ctx, |ui| {
+		let joined = browser.ui(ui, &worlds, |image| {
+			Some(contexts.add_image(image.clone_weak()))
+		});
+		if let Some(world) = joined {
+			join.send(JoinWorld { world });
+		}
+	});
+}
+
+fn join_worlds(mut join: EventReader<JoinWorld>) {
+	for JoinWorld { world } in join.read() {
+		let scene = world.manifest.scene.display();
+		match &world.source {
+			WorldSource::Local { dir } => {
+				info!("loading {scene} from {}", dir.display())
+			}
+			WorldSource::Advertised { address } => {
+				info!("connecting to {address} for {scene}")
+			}
+		}
+	}
+}
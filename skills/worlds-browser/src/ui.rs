@@ -0,0 +1,249 @@
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_egui::egui;
+
+use crate::{WorldListing, WorldSource, Worlds};
+
+/// The search box and filters of one world list, which [`Self::shows`] applies to
+/// the [`Worlds`]. With a [`WorldspaceUi`](worldspace_ui::WorldspaceUi) on the same
+/// entity, the panel shows the list and its join buttons send
+/// [`JoinWorld`](crate::JoinWorld). Elsewhere, call [`Self::ui`] and send the event
+/// yourself.
+#[derive(Component, Debug, Default, Clone)]
+pub struct WorldsBrowser {
+	/// Only worlds whose name or description contains this are shown, ignoring case.
+	pub search: String,
+	/// Only worlds with all of these tags are shown.
+	pub tags: HashSet<String>,
+	pub sources: SourceFilter,
+	pub hide_full: bool,
+	pub hide_empty: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SourceFilter {
+	#[default]
+	All,
+	Local,
+	Advertised,
+}
+
+impl WorldsBrowser {
+	/// Whether the world passes the search and filters.
+	pub fn shows(&self, world: &WorldListing) -> bool {
+		let search = self.search.to_lowercase();
+		let manifest = &world.manifest;
+		let source = matches!(
+			(self.sources, &world.source),
+			(SourceFilter::All, _)
+				| (SourceFilter::Local, WorldSource::Local { .. })
+				| (SourceFilter::Advertised, WorldSource::Advertised { .. })
+		);
+		source
+			&& (manifest.name.to_lowercase().contains(&search)
+				|| manifest.description.to_lowercase().contains(&search))
+			&& self.tags.iter().all(|tag| manifest.tags.contains(tag))
+			&& !(self.hide_full && world.is_full())
+			&& !(self.hide_empty && world.players == 0)
+	}
+
+	/// Draws the browser, returning the world that the user asked to join, if any.
+	/// `image_id` gives the egui texture of a thumbnail, which depends on where the
+	/// browser is drawn.
+	pub fn ui(
+		&mut self,
+		ui: &mut egui::Ui,
+		worlds: &Worlds,
+		mut image_id: impl FnMut(&Handle<Image>) -> Option<egui::TextureId>,
+	) -> Option<WorldListing> {
+		let mut joined = None;
+		ui.horizontal(|ui| {
+			ui.heading("Worlds");
+			let players: u32 = worlds.list().iter().map(|world| world.players).sum();
+			ui.label(format!("{players} players"));
+		});
+		ui.horizontal(|ui| {
+			ui.label("Search:");
+			ui.text_edit_singleline(&mut self.search);
+		});
+		ui.horizontal(|ui| {
+			for (filter, label) in [
+				(SourceFilter::All, "All"),
+				(SourceFilter::Local, "Local"),
+				(SourceFilter::Advertised, "Online"),
+			] {
+				ui.selectable_value(&mut self.sources, filter, label);
+			}
+			ui.separator();
+			ui.checkbox(&mut self.hide_full, "Hide full");
+			ui.checkbox(&mut self.hide_empty, "Hide empty");
+		});
+		ui.horizontal_wrapped(|ui| {
+			for tag in worlds.tags() {
+				let mut selected = self.tags.contains(tag);
+				if ui.toggle_value(&mut selected, tag).changed() {
+					if selected {
+						self.tags.insert(tag.to_owned());
+					} else {
+						self.tags.remove(tag);
+					}
+				}
+			}
+		});
+		if let Some(error) = worlds.error() {
+			ui.colored_label(ui.visuals().error_fg_color, error);
+		}
+		ui.separator();
+
+		egui::ScrollArea::vertical().show(ui, |ui| {
+			for world in worlds.list().iter().filter(|world| self.shows(world)) {
+				let manifest = &world.manifest;
+				ui.horizontal(|ui| {
+					let size = egui::Vec2::splat(48.0);
+					match worlds.thumbnail(world).and_then(&mut image_id) {
+						Some(texture) => {
+							ui.image((texture, size));
+						}
+						None => {
+							let (rect, _) =
+								ui.allocate_exact_size(size, egui::Sense::hover());
+							ui.painter().rect_filled(
+								rect,
+								4.0,
+								ui.visuals().extreme_bg_color,
+							);
+						}
+					}
+					ui.vertical(|ui| {
+						ui.horizontal(|ui| {
+							ui.strong(&manifest.name);
+							if let WorldSource::Advertised { address } = &world.source {
+								ui.small(address);
+							}
+						});
+						if !manifest.description.is_empty() {
+							ui.label(&manifest.description);
+						}
+						ui.small(manifest.tags.join(" · "));
+					});
+					ui.with_layout(
+						egui::Layout::right_to_left(egui::Align::Center),
+						|ui| {
+							let join = egui::Button::new("Join");
+							if ui.add_enabled(!world.is_full(), join).clicked() {
+								joined = Some(world.clone());
+							}
+							ui.label(format!(
+								"{}/{}",
+								world.players, manifest.capacity
+							));
+						},
+					);
+				});
+				ui.separator();
+			}
+		});
+		joined
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::path::PathBuf;
+
+	use super::*;
+	use crate::WorldManifest;
+
+	fn world(
+		name: &str,
+		source: WorldSource,
+		players: u32,
+		tags: &[&str],
+	) -> WorldListing {
+		WorldListing {
+			manifest: WorldManifest {
+				id: name.to_lowercase(),
+				name: name.to_owned(),
+				description: format!("{name} is a world"),
+				thumbnail: None,
+				scene: PathBuf::from("world.glb"),
+				capacity: 4,
+				tags: tags.iter().map(|tag| tag.to_string()).collect(),
+			},
+			source,
+			players,
+		}
+	}
+
+	fn worlds() -> Vec<WorldListing> {
+		let local = || WorldSource::Local {
+			dir: PathBuf::from("worlds"),
+		};
+		let advertised = || WorldSource::Advertised {
+			address: "example.com:7777".to_owned(),
+		};
+		vec![
+			world("Grove", local(), 0, &["chill", "nature"]),
+			world("Arena", advertised(), 4, &["games"]),
+			world("Rooftop", advertised(), 2, &["chill", "music"]),
+		]
+	}
+
+	fn shown(browser: &WorldsBrowser) -> Vec<String> {
+		worlds()
+			.into_iter()
+			.filter(|world| browser.shows(world))
+			.map(|world| world.manifest.name)
+			.collect()
+	}
+
+	#[test]
+	fn shows_everything_by_default() {
+		assert_eq!(
+			shown(&WorldsBrowser::default()),
+			["Grove", "Arena", "Rooftop"]
+		);
+	}
+
+	#[test]
+	fn searches_names_and_descriptions() {
+		let mut browser = WorldsBrowser {
+			search: "ROOF".to_owned(),
+			..default()
+		};
+		assert_eq!(shown(&browser), ["Rooftop"]);
+		browser.search = "is a world".to_owned();
+		assert_eq!(shown(&browser), ["Grove", "Arena", "Rooftop"]);
+	}
+
+	#[test]
+	fn needs_every_tag() {
+		let mut browser = WorldsBrowser::default();
+		browser.tags.insert("chill".to_owned());
+		assert_eq!(shown(&browser), ["Grove", "Rooftop"]);
+		browser.tags.insert("music".to_owned());
+		assert_eq!(shown(&browser), ["Rooftop"]);
+	}
+
+	#[test]
+	fn filters_by_source() {
+		let mut browser = WorldsBrowser {
+			sources: SourceFilter::Local,
+			..default()
+		};
+		assert_eq!(shown(&browser), ["Grove"]);
+		browser.sources = SourceFilter::Advertised;
+		assert_eq!(shown(&browser), ["Arena", "Rooftop"]);
+	}
+
+	#[test]
+	fn hides_full_and_empty_worlds() {
+		let mut browser = WorldsBrowser {
+			hide_full: true,
+			..default()
+		};
+		assert_eq!(shown(&browser), ["Grove", "Rooftop"]);
+		browser.hide_empty = true;
+		assert_eq!(shown(&browser), ["Rooftop"]);
+	}
+}
@@ -0,0 +1,401 @@
+//! A keyboard panel for typing into other panels, for when there is no physical
+//! keyboard to hand, such as in VR.
+//!
+//! The keyboard shows up under whichever panel has a focused text field, and sends
+//! what is typed on it to that panel as egui events. Its keys are pressed like any
+//! other panel's buttons, with a laser or a fingertip
+//! [`WorldspacePointer`](crate::pointer::WorldspacePointer).
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_egui::egui;
+
+use crate::{WorldspaceUi, WorldspaceUiSet};
+
+/// Spawns a [`VirtualKeyboard`] and shows it whenever a panel wants text input.
+pub struct VirtualKeyboardPlugin;
+
+impl Plugin for VirtualKeyboardPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_systems(Startup, spawn_keyboard)
+			.add_systems(Update, keyboard_ui)
+			.add_systems(PostUpdate, follow_focus.after(WorldspaceUiSet::EndFrame));
+	}
+}
+
+/// Size of the keyboard panel, in metres.
+const KEYBOARD_SIZE: Vec2 = Vec2::new(0.5, 0.2);
+/// Space between the bottom of the focused panel and the top of the keyboard.
+const KEYBOARD_GAP: f32 = 0.02;
+/// How far the keyboard is tilted back, like a keyboard on a desk.
+const KEYBOARD_TILT: f32 = 0.5;
+
+#[derive(Component, Debug, Clone)]
+pub struct VirtualKeyboard {
+	pub layout: KeyboardLayout,
+	/// The panel being typed into.
+	pub target: Option<Entity>,
+	pub shift: Shift,
+	/// Whether the symbols page of the layout is showing.
+	pub symbols: bool,
+}
+
+impl Default for VirtualKeyboard {
+	fn default() -> Self {
+		Self {
+			layout: KeyboardLayout::qwerty(),
+			target: None,
+			shift: Shift::Off,
+			symbols: false,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Shift {
+	#[default]
+	Off,
+	/// Only for the next character.
+	Once,
+	/// Until shift is pressed again.
+	Locked,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Key {
+	/// Types the character, upper cased when shifted.
+	Char(char),
+	/// Types a whole string, for things like emoji or `.com`.
+	Text(String),
+	Shift,
+	Backspace,
+	Enter,
+	Space,
+	/// Switches between the letters and symbols pages.
+	Symbols,
+}
+
+impl Key {
+	fn label(&self, shift: Shift, symbols: bool) -> String {
+		match self {
+			Key::Char(c) if shift != Shift::Off => c.to_uppercase().collect(),
+			Key::Char(c) => c.to_string(),
+			Key::Text(text) => text.clone(),
+			Key::Shift if shift == Shift::Locked => "SHIFT".to_owned(),
+			Key::Shift => "shift".to_owned(),
+			Key::Backspace => "delete".to_owned(),
+			Key::Enter => "enter".to_owned(),
+			Key::Space => "space".to_owned(),
+			Key::Symbols if symbols => "abc".to_owned(),
+			Key::Symbols => "?123".to_owned(),
+		}
+	}
+
+	/// How many normal keys wide the key is.
+	fn width(&self) -> f32 {
+		match self {
+			Key::Space => 5.0,
+			Key::Shift | Key::Backspace | Key::Enter | Key::Symbols => 1.5,
+			Key::Char(_) | Key::Text(_) => 1.0,
+		}
+	}
+}
+
+/// The rows of keys on each page of the keyboard. Characters can be any unicode,
+/// since they are sent as text rather than key codes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyboardLayout {
+	pub letters: Vec<Vec<Key>>,
+	pub symbols: Vec<Vec<Key>>,
+}
+
+impl KeyboardLayout {
+	pub fn qwerty() -> Self {
+		Self::with_letters(["1234567890", "qwertyuiop", "asdfghjkl'", "zxcvbnm,."])
+	}
+
+	pub fn qwertz() -> Self {
+		Self::with_letters(["1234567890ß", "qwertzuiopü", "asdfghjklöä", "yxcvbnm,.-"])
+	}
+
+	pub fn azerty() -> Self {
+		Self::with_letters(["1234567890", "azertyuiop", "qsdfghjklm", "wxcvbn,.'é"])
+	}
+
+	/// A layout with the given rows of letters, the usual symbols, and shift,
+	/// backspace, space and enter around them.
+	pub fn with_letters(rows: [&str; 4]) -> Self {
+		let symbols = ["1234567890", "@#$%&*-+=/", "()[]{}<>\\|", "!?:;\"_~€£°"];
+		Self {
+			letters: Self::page(rows),
+			symbols: Self::page(symbols),
+		}
+	}
+
+	fn page(rows: [&str; 4]) -> Vec<Vec<Key>> {
+		let mut page: Vec<Vec<Key>> = rows
+			.iter()
+			.map(|row| row.chars().map(Key::Char).collect())
+			.collect();
+		page[2].push(Key::Enter);
+		page[3].insert(0, Key::Shift);
+		page[3].push(Key::Backspace);
+		page.push(vec![Key::Symbols, Key::Space, Key::Symbols]);
+		page
+	}
+}
+
+fn spawn_keyboard(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+	let mut panel = WorldspaceUi::menu(&mut images, KEYBOARD_SIZE);
+	panel.corner_radius = 0.01;
+	commands.spawn((
+		SpatialBundle {
+			visibility: Visibility::Hidden,
+			..default()
+		},
+		panel,
+		VirtualKeyboard::default(),
+		Name::new("Virtual keyboard"),
+	));
+}
+
+/// Moves the keyboard under the panel with a focused text field, or hides it if
+/// there is none.
+///
+/// Each panel has its own egui context, so focusing a text field on one doesn't
+/// unfocus the others. The panel that focused a text field most recently gets the
+/// keyboard, and the other panels give up their focus.
+fn follow_focus(
+	mut keyboards: Query<(&mut VirtualKeyboard, &mut Transform, &mut Visibility)>,
+	panels: Query<(Entity, &WorldspaceUi, &GlobalTransform), Without<VirtualKeyboard>>,
+	mut focused_widgets: Local<HashMap<Entity, egui::Id>>,
+) {
+	let previous = std::mem::take(&mut *focused_widgets);
+	let mut newest = None;
+	for (entity, panel, _) in panels.iter() {
+		let Some(id) = panel
+			.ctx
+			.memory(|memory| memory.focus())
+			.filter(|_| panel.ctx.wants_keyboard_input())
+		else {
+			continue;
+		};
+		if previous.get(&entity) != Some(&id) {
+			newest = Some(entity);
+		}
+		focused_widgets.insert(entity, id);
+	}
+	if let Some(newest) = newest {
+		focused_widgets.retain(|&entity, &mut id| {
+			if entity != newest {
+				if let Ok((_, panel, _)) = panels.get(entity) {
+					panel.ctx.memory_mut(|memory| memory.surrender_focus(id));
+				}
+			}
+			entity == newest
+		});
+	}
+
+	let focused = focused_widgets
+		.keys()
+		.next()
+		.and_then(|&entity| panels.get(entity).ok());
+	for (mut keyboard, mut transform, mut visibility) in keyboards.iter_mut() {
+		let target = focused.map(|(entity, ..)| entity);
+		if keyboard.target == target {
+			continue;
+		}
+		keyboard.target = target;
+		keyboard.shift = Shift::Off;
+		let Some((_, panel, panel_transform)) = focused else {
+			*visibility = Visibility::Hidden;
+			continue;
+		};
+		let panel_transform = panel_transform.compute_transform();
+		let offset = panel.size.y / 2.0 + KEYBOARD_GAP + KEYBOARD_SIZE.y / 2.0;
+		*transform = Transform {
+			translation: panel_transform
+				.transform_point(Vec3::NEG_Y * offset / panel_transform.scale),
+			rotation: panel_transform.rotation * Quat::from_rotation_x(-KEYBOARD_TILT),
+			scale: Vec3::ONE,
+		};
+		*visibility = Visibility::Visible;
+	}
+}
+
+fn keyboard_ui(
+	mut keyboards: Query<(&mut WorldspaceUi, &mut VirtualKeyboard)>,
+	mut panels: Query<&mut WorldspaceUi, Without<VirtualKeyboard>>,
+) {
+	for (mut keyboard_panel, mut keyboard) in keyboards.iter_mut() {
+		let Some(target) = keyboard.target else {
+			continue;
+		};
+		let mut pressed = Vec::new();
+		let keyboard = &mut *keyboard;
+		let rows = if keyboard.symbols {
+			&keyboard.layout.symbols
+		} else {
+			&keyboard.layout.letters
+		};
+		egui::CentralPanel::default()
+			.frame(egui::Frame::none().inner_margin(8.0))
+			.show(keyboard_panel.ctx_mut(), |ui| {
+				let spacing = ui.spacing().item_spacing;
+				let widest = rows
+					.iter()
+					.map(|row| row.iter().map(Key::width).sum::<f32>())
+					.fold(1.0, f32::max);
+				let key_width = (ui.available_width() + spacing.x) / widest - spacing.x;
+				let key_height =
+					(ui.available_height() + spacing.y) / rows.len() as f32 - spacing.y;
+				for row in rows {
+					ui.horizontal(|ui| {
+						let row_width = row.iter().map(Key::width).sum::<f32>();
+						ui.add_space(
+							(widest - row_width) * (key_width + spacing.x) / 2.0,
+						);
+						for key in row {
+							let label = key.label(keyboard.shift, keyboard.symbols);
+							let size = egui::vec2(
+								key.width() * (key_width + spacing.x) - spacing.x,
+								key_height,
+							);
+							let button = egui::Button::new(
+								egui::RichText::new(label).size(18.0),
+							)
+							.selected(
+								*key == Key::Shift && keyboard.shift != Shift::Off,
+							);
+							if ui.add_sized(size, button).clicked() {
+								pressed.push(key.clone());
+							}
+						}
+					});
+				}
+			});
+
+		let Ok(mut target) = panels.get_mut(target) else {
+			continue;
+		};
+		for key in pressed {
+			press(keyboard, &key, &mut target);
+		}
+	}
+}
+
+fn press(keyboard: &mut VirtualKeyboard, key: &Key, target: &mut WorldspaceUi) {
+	let mut tap = |key: egui::Key| {
+		for pressed in [true, false] {
+			target.push_event(egui::Event::Key {
+				key,
+				pressed,
+				repeat: false,
+				modifiers: egui::Modifiers::default(),
+			});
+		}
+	};
+	match key {
+		Key::Char(c) => {
+			let text = if keyboard.shift == Shift::Off {
+				c.to_string()
+			} else {
+				c.to_uppercase().collect()
+			};
+			target.push_event(egui::Event::Text(text));
+			if keyboard.shift == Shift::Once {
+				keyboard.shift = Shift::Off;
+			}
+		}
+		Key::Text(text) => target.push_event(egui::Event::Text(text.clone())),
+		Key::Space => target.push_event(egui::Event::Text(" ".to_owned())),
+		Key::Backspace => tap(egui::Key::Backspace),
+		Key::Enter => tap(egui::Key::Enter),
+		Key::Shift => {
+			keyboard.shift = match keyboard.shift {
+				Shift::Off => Shift::Once,
+				Shift::Once => Shift::Locked,
+				Shift::Locked => Shift::Off,
+			}
+		}
+		Key::Symbols => keyboard.symbols = !keyboard.symbols,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn focus(app: &mut App, panel: Entity, id: egui::Id) {
+		let panel = app.world.get::<WorldspaceUi>(panel).unwrap();
+		panel.ctx.memory_mut(|memory| memory.request_focus(id));
+	}
+
+	fn focused(app: &App, panel: Entity) -> Option<egui::Id> {
+		let panel = app.world.get::<WorldspaceUi>(panel).unwrap();
+		panel.ctx.memory(|memory| memory.focus())
+	}
+
+	fn keyboard(app: &mut App) -> (Option<Entity>, Visibility) {
+		let (keyboard, visibility) = app
+			.world
+			.query::<(&VirtualKeyboard, &Visibility)>()
+			.single(&app.world);
+		(keyboard.target, *visibility)
+	}
+
+	#[test]
+	fn follows_the_most_recently_focused_panel() {
+		let mut app = App::new();
+		app.add_plugins(MinimalPlugins)
+			.add_systems(Update, follow_focus);
+		let mut images = Assets::<Image>::default();
+		let mut spawn_panel = |x| {
+			app.world
+				.spawn((
+					WorldspaceUi::new(&mut images, Vec2::new(0.4, 0.3)),
+					GlobalTransform::from_xyz(x, 1.0, 0.0),
+				))
+				.id()
+		};
+		let a = spawn_panel(-0.5);
+		let b = spawn_panel(0.5);
+		app.world.spawn((
+			VirtualKeyboard::default(),
+			Transform::default(),
+			Visibility::Hidden,
+		));
+		let a_field = egui::Id::new("a field");
+		let b_field = egui::Id::new("b field");
+
+		focus(&mut app, a, a_field);
+		app.update();
+		assert_eq!(keyboard(&mut app), (Some(a), Visibility::Visible));
+
+		// Focusing a field on the other panel takes the keyboard, and unfocuses the
+		// first panel so that the two don't fight over it.
+		focus(&mut app, b, b_field);
+		app.update();
+		assert_eq!(keyboard(&mut app), (Some(b), Visibility::Visible));
+		assert_eq!(focused(&app, a), None);
+		assert_eq!(focused(&app, b), Some(b_field));
+
+		// And back again.
+		focus(&mut app, a, a_field);
+		app.update();
+		assert_eq!(keyboard(&mut app), (Some(a), Visibility::Visible));
+		assert_eq!(focused(&app, b), None);
+
+		// It stays there while nothing changes.
+		app.update();
+		assert_eq!(keyboard(&mut app), (Some(a), Visibility::Visible));
+
+		app.world
+			.get::<WorldspaceUi>(a)
+			.unwrap()
+			.ctx
+			.memory_mut(|memory| memory.surrender_focus(a_field));
+		app.update();
+		assert_eq!(keyboard(&mut app), (None, Visibility::Hidden));
+	}
+}
@@ -0,0 +1,377 @@
+//! egui panels that live in the world, as textures on meshes.
+//!
+//! Spawn a [`WorldspaceUi`] in a [`SpatialBundle`], and build its contents from an
+//! ordinary system in [`Update`], the same way you would with
+//! [`EguiContexts`](bevy_egui::EguiContexts) for the screen.
+
+pub mod keyboard;
+mod mesh;
+pub mod pointer;
+mod render_node;
+mod render_systems;
+mod resize;
+
+use std::time::Duration;
+
+use bevy::diagnostic::{Diagnostic, DiagnosticId, Diagnostics, RegisterDiagnostic};
+use bevy::prelude::*;
+use bevy::render::render_graph::RenderGraph;
+use bevy::render::RenderApp;
+use bevy::utils::HashMap;
+use bevy_egui::egui;
+
+use crate::pointer::PointerPlugin;
+
+pub struct WorldspaceUiPlugin;
+
+impl Plugin for WorldspaceUiPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_plugins(PointerPlugin)
+			.register_diagnostic(Diagnostic::new(
+				Self::PANEL_REDRAWS,
+				"worldspace_ui_panel_redraws",
+				20,
+			))
+			.configure_sets(PreUpdate, WorldspaceUiSet::BeginFrame)
+			.configure_sets(PostUpdate, WorldspaceUiSet::EndFrame)
+			.add_systems(
+				PreUpdate,
+				(mesh::sync_panel_meshes, resize::resize_panels, begin_frame)
+					.chain()
+					.in_set(WorldspaceUiSet::BeginFrame),
+			)
+			.add_systems(PostUpdate, end_frame.in_set(WorldspaceUiSet::EndFrame));
+
+		let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+			panic!("The render plugin should have added this subapp");
+		};
+		render_app
+			.init_resource::<render_systems::ExtractedPanels>()
+			.add_systems(ExtractSchedule, render_systems::extract_panels);
+		// A single node renders all of the panels, so there are no per-panel labels.
+		let mut render_graph = render_app.world.resource_mut::<RenderGraph>();
+		render_graph.add_node(render_node::EGUI_NODE, render_node::EguiNode::default());
+		render_graph.add_node_edge(
+			bevy::render::main_graph::node::CAMERA_DRIVER,
+			render_node::EGUI_NODE,
+		);
+	}
+}
+
+impl WorldspaceUiPlugin {
+	/// How many panels were redrawn this frame. See [`WorldspaceUi::redraw_stats`]
+	/// for each panel's own.
+	pub const PANEL_REDRAWS: DiagnosticId =
+		DiagnosticId::from_u128(129580383146283641942357813498364519036);
+}
+
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WorldspaceUiSet {
+	/// Starts the egui frame of every panel, with the input gathered so far. Runs in
+	/// [`PreUpdate`]. Anything in [`Update`] can add to the panels after this.
+	BeginFrame,
+	/// Ends the egui frame and tessellates it, ready to be extracted. Runs in
+	/// [`PostUpdate`].
+	EndFrame,
+}
+
+/// A world-space egui panel, rendered to [`Self::output_texture`]. The entity's
+/// mesh is replaced with one of the panel's size and [`PanelShape`], facing +Z. Unless the entity
+/// already has a material, it gets an unlit one that shows the texture with
+/// premultiplied alpha, so that transparent parts of the panel blend with the world.
+///
+/// The panel is laid out in points, like egui on a screen, and its texture gets
+/// bigger when the panel or the entity's scale does, so that it stays sharp.
+#[derive(Component)]
+pub struct WorldspaceUi {
+	/// Width and height in metres, before the entity's scale is applied.
+	pub size: Vec2,
+	/// Resolution of the texture. Higher is sharper, but costs more memory and time
+	/// to render. The texture is never bigger than [`MAX_TEXTURE_SIZE`].
+	pub pixels_per_metre: f32,
+	/// How big egui's points are. At the default of 1000, 14pt text is 1.4cm tall.
+	pub points_per_metre: f32,
+	/// What the texture is cleared to before egui draws on it. Transparent by
+	/// default, so only egui's windows show.
+	pub background: Color,
+	/// Rounds the corners of the mesh, in metres.
+	pub corner_radius: f32,
+	pub shape: PanelShape,
+	/// Moves the panel towards the camera (positive) or away from it (negative) when
+	/// sorting it among other transparent things, and for depth testing. Useful to
+	/// keep a panel in front of something it sits on.
+	pub depth_bias: f32,
+	output_texture: Handle<Image>,
+	/// The size `output_texture` was last made, in pixels.
+	texture_size: UVec2,
+	/// How many pixels of `output_texture` each egui point covers, on both axes.
+	pixels_per_point: f32,
+	/// The size, corner radius and shape the mesh was last made with.
+	mesh_shape: Option<(Vec2, f32, PanelShape)>,
+	/// The material we made for the panel, if it didn't have one.
+	material: Option<Handle<StandardMaterial>>,
+	/// The background that was last drawn.
+	drawn_background: Option<Color>,
+	ctx: egui::Context,
+	/// Input for egui this frame, from [`pointer::WorldspacePointer`]s.
+	events: Vec<egui::Event>,
+	/// Panels spawned in the middle of a frame wait for the next one to start.
+	in_frame: bool,
+	/// What to render, from the last time the frame ended.
+	output: PanelOutput,
+	/// What was drawn last time, to tell whether anything changed.
+	shapes: Vec<egui::epaint::ClippedShape>,
+	/// Set when there was input or the texture was resized, which always redraws.
+	dirty: bool,
+	/// When egui asked to be repainted, as [`Time::elapsed`].
+	repaint_at: Option<Duration>,
+	redraw_stats: RedrawStats,
+	/// Bevy images that the panel can show, by their `egui::TextureId::User` id.
+	user_textures: HashMap<Handle<Image>, u64>,
+	next_user_texture: u64,
+}
+
+/// The surface a panel is drawn on.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PanelShape {
+	#[default]
+	Flat,
+	/// Bent around a vertical cylinder whose axis is `radius` metres in front of the
+	/// panel, so that every column of the panel is the same distance from someone
+	/// standing there. The panel's width is measured along the curve, and its middle
+	/// stays at the entity's origin.
+	Cylinder { radius: f32 },
+}
+
+#[derive(Default, Clone)]
+pub(crate) struct PanelOutput {
+	pub primitives: Vec<egui::ClippedPrimitive>,
+	pub textures_delta: egui::TexturesDelta,
+	pub pixels_per_point: f32,
+	/// In linear space, with premultiplied alpha.
+	pub background: [f32; 4],
+	/// Whether to render the panel again. If not, the texture keeps what it had, and
+	/// `primitives` is empty.
+	pub redraw: bool,
+}
+
+/// How often a panel has been redrawn. Panels are only redrawn when they had input,
+/// when what they show changed, or when egui asked for a repaint, such as during an
+/// animation.
+#[derive(Debug, Clone, Default)]
+pub struct RedrawStats {
+	pub frames: u64,
+	pub redraws: u64,
+	/// Smoothed over about a second.
+	pub redraws_per_second: f32,
+}
+
+impl RedrawStats {
+	fn record(&mut self, redraw: bool, delta_seconds: f32) {
+		self.frames += 1;
+		self.redraws += u64::from(redraw);
+		if delta_seconds > 0.0 {
+			let rate = if redraw { 1.0 / delta_seconds } else { 0.0 };
+			let smoothing = delta_seconds.min(1.0);
+			self.redraws_per_second += (rate - self.redraws_per_second) * smoothing;
+		}
+	}
+}
+
+/// The largest a panel's texture can be on either side, in pixels.
+pub const MAX_TEXTURE_SIZE: u32 = 4096;
+
+impl WorldspaceUi {
+	/// Creates a panel that is `size` metres big, with its own texture.
+	pub fn new(images: &mut Assets<Image>, size: Vec2) -> Self {
+		let mut panel = Self {
+			size,
+			pixels_per_metre: 2000.0,
+			points_per_metre: 1000.0,
+			background: Color::NONE,
+			corner_radius: 0.0,
+			shape: PanelShape::Flat,
+			depth_bias: 0.0,
+			output_texture: Handle::default(),
+			texture_size: UVec2::ZERO,
+			pixels_per_point: 1.0,
+			mesh_shape: None,
+			material: None,
+			drawn_background: None,
+			ctx: egui::Context::default(),
+			events: Vec::new(),
+			in_frame: false,
+			output: PanelOutput::default(),
+			shapes: Vec::new(),
+			dirty: true,
+			repaint_at: None,
+			redraw_stats: RedrawStats::default(),
+			user_textures: HashMap::default(),
+			next_user_texture: 0,
+		};
+		panel.pixels_per_point = panel.pixels_per_point_for(Vec2::ONE);
+		panel.texture_size = panel.texture_size_for(panel.pixels_per_point);
+		let size = wgpu::Extent3d {
+			width: panel.texture_size.x,
+			height: panel.texture_size.y,
+			depth_or_array_layers: 1,
+		};
+		let mut output_texture = Image {
+			data: vec![0; (size.width * size.height * 4) as usize],
+			..default()
+		};
+		output_texture.texture_descriptor.usage |=
+			wgpu::TextureUsages::RENDER_ATTACHMENT;
+		output_texture.texture_descriptor.size = size;
+		panel.output_texture = images.add(output_texture);
+		panel
+	}
+
+	/// A panel with a dark, nearly opaque background and rounded corners, for menus
+	/// that float on their own rather than sitting on something.
+	pub fn menu(images: &mut Assets<Image>, size: Vec2) -> Self {
+		let mut panel = Self::new(images, size);
+		panel.background = Color::rgba(0.1, 0.1, 0.1, 0.9);
+		panel.corner_radius = 0.02;
+		panel
+	}
+
+	/// Size of the panel in egui points.
+	pub fn size_in_points(&self) -> egui::Vec2 {
+		let size = self.size * self.points_per_metre;
+		egui::vec2(size.x, size.y)
+	}
+
+	/// The pixels per point that keep the panel sharp when the entity has `scale`.
+	///
+	/// Both axes share it, so egui's layout isn't stretched. If the entity is scaled
+	/// more along one axis, that axis decides, and the texture is shrunk as a whole
+	/// to keep both sides within [`MAX_TEXTURE_SIZE`].
+	fn pixels_per_point_for(&self, scale: Vec2) -> f32 {
+		let points = self.size_in_points();
+		let points = Vec2::new(points.x, points.y).max(Vec2::splat(f32::EPSILON));
+		let sharp =
+			scale.abs().max_element() * self.pixels_per_metre / self.points_per_metre;
+		let fits = (MAX_TEXTURE_SIZE as f32 / points).min_element();
+		// At least one pixel along the longest side, so egui has something to draw.
+		sharp.min(fits).max(1.0 / points.max_element())
+	}
+
+	/// The texture size that fits the panel's points at `pixels_per_point`.
+	fn texture_size_for(&self, pixels_per_point: f32) -> UVec2 {
+		let points = self.size_in_points();
+		(Vec2::new(points.x, points.y) * pixels_per_point)
+			.round()
+			.as_uvec2()
+			.clamp(UVec2::ONE, UVec2::splat(MAX_TEXTURE_SIZE))
+	}
+
+	pub fn output_texture(&self) -> &Handle<Image> {
+		&self.output_texture
+	}
+
+	/// Makes `image` available to show in the panel, for example with
+	/// `ui.image((texture_id, size))`. Adding the same image again returns the same id.
+	pub fn add_image(&mut self, image: Handle<Image>) -> egui::TextureId {
+		let id = *self.user_textures.entry(image).or_insert_with(|| {
+			self.next_user_texture += 1;
+			self.next_user_texture - 1
+		});
+		egui::TextureId::User(id)
+	}
+
+	/// Stops the panel from showing `image`, returning the id it had.
+	pub fn remove_image(&mut self, image: &Handle<Image>) -> Option<egui::TextureId> {
+		self.user_textures.remove(image).map(egui::TextureId::User)
+	}
+
+	/// The id of an image that was added with [`Self::add_image`].
+	pub fn image_id(&self, image: &Handle<Image>) -> Option<egui::TextureId> {
+		self.user_textures
+			.get(image)
+			.copied()
+			.map(egui::TextureId::User)
+	}
+
+	/// Gives egui an input event, such as text from a keyboard, at the start of the
+	/// next frame.
+	pub fn push_event(&mut self, event: egui::Event) {
+		self.events.push(event);
+	}
+
+	pub fn redraw_stats(&self) -> &RedrawStats {
+		&self.redraw_stats
+	}
+
+	/// The egui context to add this frame's UI to. Only use this in between
+	/// [`WorldspaceUiSet::BeginFrame`] and [`WorldspaceUiSet::EndFrame`].
+	pub fn ctx_mut(&mut self) -> &mut egui::Context {
+		&mut self.ctx
+	}
+}
+
+fn begin_frame(mut panels: Query<&mut WorldspaceUi>) {
+	for mut panel in panels.iter_mut() {
+		let size_in_points = panel.size_in_points();
+		let raw_input = egui::RawInput {
+			screen_rect: Some(egui::Rect::from_min_size(
+				egui::Pos2::ZERO,
+				size_in_points,
+			)),
+			pixels_per_point: Some(panel.pixels_per_point),
+			events: std::mem::take(&mut panel.events),
+			..default()
+		};
+		panel.dirty |= !raw_input.events.is_empty();
+		panel.ctx.begin_frame(raw_input);
+		panel.in_frame = true;
+	}
+}
+
+fn end_frame(
+	mut panels: Query<&mut WorldspaceUi>,
+	time: Res<Time>,
+	mut diagnostics: Diagnostics,
+) {
+	let now = time.elapsed();
+	let mut redraws = 0;
+	for mut panel in panels.iter_mut() {
+		if !std::mem::take(&mut panel.in_frame) {
+			continue;
+		}
+		let panel = &mut *panel;
+		let output = panel.ctx.end_frame();
+
+		let repaint_due = panel.repaint_at.is_some_and(|at| at <= now);
+		let redraw = std::mem::take(&mut panel.dirty)
+			|| repaint_due
+			|| !output.textures_delta.is_empty()
+			|| output.shapes != panel.shapes
+			|| panel.drawn_background != Some(panel.background);
+		// egui asks for `Duration::MAX` when it doesn't need repainting at all.
+		panel.repaint_at = now.checked_add(output.repaint_after);
+		panel.redraw_stats.record(redraw, time.delta_seconds());
+
+		let primitives = if redraw {
+			redraws += 1;
+			panel.drawn_background = Some(panel.background);
+			panel.shapes = output.shapes;
+			panel.ctx.tessellate(panel.shapes.clone())
+		} else {
+			Vec::new()
+		};
+		panel.output = PanelOutput {
+			primitives,
+			textures_delta: output.textures_delta,
+			pixels_per_point: panel.ctx.pixels_per_point(),
+			background: premultiplied(panel.background),
+			redraw,
+		};
+	}
+	diagnostics.add_measurement(WorldspaceUiPlugin::PANEL_REDRAWS, || redraws as f64);
+}
+
+fn premultiplied(color: Color) -> [f32; 4] {
+	let [r, g, b, a] = color.as_linear_rgba_f32();
+	[r * a, g * a, b * a, a]
+}
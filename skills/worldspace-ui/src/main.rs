@@ -1,84 +1,140 @@
-mod render_node;
-mod render_systems;
-
 use bevy::prelude::*;
 use bevy_egui::egui;
 use bevy_egui::EguiContexts;
+use bevy_flycam::FlyCam;
 use color_eyre::Result;
+use worldspace_ui::keyboard::VirtualKeyboardPlugin;
+use worldspace_ui::pointer::add_mouse_pointer;
+use worldspace_ui::{PanelShape, WorldspaceUi, WorldspaceUiPlugin};
+
+const ASSET_FOLDER: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../assets/");
 
 fn main() -> Result<()> {
 	color_eyre::install()?;
-	let mut app = App::new();
-	app.add_plugins(DefaultPlugins)
+	App::new()
+		.add_plugins(DefaultPlugins.set(AssetPlugin {
+			file_path: ASSET_FOLDER.to_string(),
+			..default()
+		}))
 		.add_plugins(bevy_flycam::PlayerPlugin)
 		.add_plugins(bevy_egui::EguiPlugin)
+		.add_plugins((WorldspaceUiPlugin, VirtualKeyboardPlugin))
 		.insert_resource(AmbientLight {
 			color: Color::WHITE,
 			brightness: 1.,
 		})
+		.init_resource::<Clicks>()
+		.init_resource::<Message>()
 		.add_systems(Startup, setup)
-		.add_systems(Update, screenspace_ui);
-
-	let Ok(render_app) = app.get_sub_app_mut(bevy::render::RenderApp) else {
-		panic!("The render plugin should have added this subapp");
-	};
-	render_app.add_systems(ExtractSchedule, crate::render_systems::add_render_node);
+		.add_systems(
+			Update,
+			(screenspace_ui, worldspace_ui, add_mouse_pointer::<FlyCam>),
+		)
+		.run();
 
-	app.run();
 	Ok(())
 }
 
-#[derive(Component, Clone)]
-pub struct EguiContext {
-	output_texture: Handle<Image>,
-	ctx: egui::Context,
-}
+#[derive(Resource, Default)]
+struct Clicks(u32);
+
+#[derive(Resource, Default)]
+struct Message(String);
+
+/// An ordinary Bevy image, shown inside the panels.
+#[derive(Resource)]
+struct TreeImage(Handle<Image>);
 
 fn setup(
 	mut commands: Commands,
-	mut meshes: ResMut<Assets<Mesh>>,
-	mut materials: ResMut<Assets<StandardMaterial>>,
 	mut images: ResMut<Assets<Image>>,
+	assets: Res<AssetServer>,
 ) {
-	let egui_thing = {
-		let size = wgpu::Extent3d {
-			width: 256,
-			height: 256,
-			depth_or_array_layers: 1,
-		};
-		let mut output_texture = Image {
-			data: vec![255; (size.width * size.height * 4) as usize],
-			..default()
-		};
-		output_texture.texture_descriptor.usage |=
-			wgpu::TextureUsages::RENDER_ATTACHMENT;
-		output_texture.texture_descriptor.size = size;
-		let output_texture = images.add(output_texture);
+	let tree_img: Handle<Image> = assets.load("tree.png");
 
-		EguiContext {
-			output_texture,
-			ctx: egui::Context::default(),
-		}
-	};
+	// Each panel gets its own texture and egui context, and they can be spawned and
+	// despawned freely. The left one is see-through apart from its window.
+	for (x, background) in [(-0.4, Color::NONE), (0.4, Color::rgb(0.1, 0.2, 0.3))] {
+		let mut panel = WorldspaceUi::new(&mut images, Vec2::new(0.6, 0.45));
+		panel.background = background;
+		panel.corner_radius = 0.03;
+		panel.add_image(tree_img.clone());
+		commands.spawn((
+			SpatialBundle::from_transform(
+				Transform::from_xyz(x, 0.0, 0.0)
+					.with_rotation(Quat::from_rotation_y(-x * 0.5)),
+			),
+			panel,
+		));
+	}
 
+	// A wide panel above them, curved so that its ends face the camera too.
+	let mut panel = WorldspaceUi::new(&mut images, Vec2::new(1.4, 0.3));
+	panel.background = Color::rgb(0.2, 0.1, 0.2);
+	panel.corner_radius = 0.03;
+	panel.shape = PanelShape::Cylinder { radius: 1.0 };
+	panel.add_image(tree_img.clone());
 	commands.spawn((
-		PbrBundle {
-			mesh: meshes.add(shape::Cube::default().into()),
-			material: materials.add(StandardMaterial {
-				base_color: Color::WHITE,
-				base_color_texture: Some(Handle::clone(&egui_thing.output_texture)),
-				// Remove this if you want it to use the world's lighting.
-				unlit: true,
-				..default()
-			}),
-			..default()
-		},
-		egui_thing,
+		SpatialBundle::from_transform(Transform::from_xyz(0.0, 0.45, -0.2)),
+		panel,
 	));
+	commands.insert_resource(TreeImage(tree_img));
+}
+
+/// Builds the panels' contents, like any other egui UI.
+fn worldspace_ui(
+	mut panels: Query<(&mut WorldspaceUi, &mut Transform)>,
+	mut clicks: ResMut<Clicks>,
+	mut message: ResMut<Message>,
+	time: Res<Time>,
+	tree_img: Res<TreeImage>,
+) {
+	for (mut panel, mut transform) in panels.iter_mut() {
+		let tree_id = panel.image_id(&tree_img.0);
+		let redraws_per_second = panel.redraw_stats().redraws_per_second;
+		let mut size = panel.size;
+		let mut scale = transform.scale.x;
+		egui::Window::new("Worldspace Window").show(panel.ctx_mut(), |ui| {
+			ui.label("I am rendering to a texture on a panel");
+			// Panels only redraw when something changes, so this one redraws about
+			// once a second when left alone.
+			ui.label(format!("Running for {:.0}s", time.elapsed_seconds()));
+			ui.label(format!("Redrawing {redraws_per_second:.0} times a second"));
+			if ui.button(format!("Clicked {} times", clicks.0)).clicked() {
+				clicks.0 += 1;
+			}
+			// Focusing this brings up the virtual keyboard.
+			ui.horizontal(|ui| {
+				ui.label("Message:");
+				ui.text_edit_singleline(&mut message.0);
+			});
+			if let Some(texture_id) = tree_id {
+				ui.image((texture_id, egui::vec2(32.0, 32.0)));
+			}
+			// Resizing reflows the UI, scaling zooms it. Both keep it sharp.
+			ui.add(egui::Slider::new(&mut size.x, 0.3..=1.2).text("Width (m)"));
+			ui.add(egui::Slider::new(&mut scale, 0.5..=2.0).text("Scale"));
+			egui::ScrollArea::vertical().show(ui, |ui| {
+				for i in 0..20 {
+					ui.label(format!("Scroll me {i}"));
+				}
+			});
+		});
+		if size != panel.size {
+			panel.size = size;
+		}
+		if scale != transform.scale.x {
+			transform.scale = Vec3::splat(scale);
+		}
+	}
 }
 
-fn screenspace_ui(mut contexts: EguiContexts) {
+fn screenspace_ui(mut contexts: EguiContexts, clicks: Res<Clicks>) {
 	egui::Window::new("Screenspace Window").show(contexts.ctx_mut(), |ui| {
 		ui.label("I am rendering to the screen!");
+		ui.label(format!(
+			"The worldspace button was clicked {} times",
+			clicks.0
+		));
 	});
 }
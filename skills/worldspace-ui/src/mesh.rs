@@ -0,0 +1,127 @@
+//! Gives each panel a mesh of its size and shape, and a material that shows it.
+
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+
+use crate::{PanelShape, WorldspaceUi};
+
+/// Segments in each rounded corner.
+const CORNER_SEGMENTS: usize = 8;
+/// Largest angle of the curve covered by one flat segment of a curved panel, in
+/// radians.
+const MAX_SEGMENT_ANGLE: f32 = 0.05;
+
+pub(crate) fn sync_panel_meshes(
+	mut commands: Commands,
+	mut panels: Query<(Entity, &mut WorldspaceUi)>,
+	panel_materials: Query<(), With<Handle<StandardMaterial>>>,
+	mut meshes: ResMut<Assets<Mesh>>,
+	mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+	for (entity, mut panel) in panels.iter_mut() {
+		let shape = (panel.size, panel.corner_radius, panel.shape);
+		if panel.mesh_shape != Some(shape) {
+			let mesh =
+				meshes.add(panel_mesh(panel.size, panel.corner_radius, panel.shape));
+			commands.entity(entity).insert(mesh);
+			panel.mesh_shape = Some(shape);
+		}
+
+		if let Some(material) = &panel.material {
+			let depth_bias = panel.depth_bias;
+			let outdated = materials
+				.get(material)
+				.is_some_and(|material| material.depth_bias != depth_bias);
+			if outdated {
+				materials.get_mut(material).unwrap().depth_bias = depth_bias;
+			}
+		} else if !panel_materials.contains(entity) {
+			// Otherwise the panel came with its own material, which we leave alone.
+			let material = materials.add(StandardMaterial {
+				base_color_texture: Some(panel.output_texture.clone()),
+				alpha_mode: AlphaMode::Premultiplied,
+				depth_bias: panel.depth_bias,
+				// Panels are lit by their own pixels, not the world's lights.
+				unlit: true,
+				double_sided: true,
+				cull_mode: None,
+				..default()
+			});
+			commands.entity(entity).insert(material.clone());
+			panel.material = Some(material);
+		}
+	}
+}
+
+/// A `size` rectangle facing +Z, with corners rounded by `radius`, bent into
+/// `shape`. The UVs cover the whole rectangle, as if the corners weren't cut off, and
+/// go evenly along the curve.
+fn panel_mesh(size: Vec2, radius: f32, shape: PanelShape) -> Mesh {
+	let half = size / 2.0;
+	let radius = radius.clamp(0.0, half.min_element());
+	let shape = match shape {
+		PanelShape::Cylinder { radius } if radius <= 0.0 => PanelShape::Flat,
+		shape => shape,
+	};
+
+	// Vertical strips from left to right, each column a little shorter near the
+	// rounded corners. Curved panels have enough strips in the middle to look round.
+	let middle_segments = match shape {
+		PanelShape::Flat => 1,
+		PanelShape::Cylinder { radius } => ((size.x / radius) / MAX_SEGMENT_ANGLE)
+			.ceil()
+			.clamp(1.0, 512.0) as usize,
+	};
+	let corner_segments = if radius == 0.0 { 0 } else { CORNER_SEGMENTS };
+	// Each column's x, and how far its ends are cut off by the corners.
+	let mut columns = Vec::new();
+	for segment in 0..corner_segments {
+		let angle = segment as f32 / corner_segments as f32 * FRAC_PI_2;
+		let x = -half.x + radius - radius * angle.cos();
+		columns.push((x, radius - radius * angle.sin()));
+	}
+	for segment in 0..=middle_segments {
+		let t = segment as f32 / middle_segments as f32;
+		columns.push((-half.x + radius + t * (size.x - 2.0 * radius), 0.0));
+	}
+	for segment in (0..corner_segments).rev() {
+		let (x, inset) = columns[segment];
+		columns.push((-x, inset));
+	}
+
+	let mut positions = Vec::new();
+	let mut normals = Vec::new();
+	let mut uvs = Vec::new();
+	for &(x, inset) in columns.iter() {
+		let (position, normal) = match shape {
+			PanelShape::Flat => (Vec3::new(x, 0.0, 0.0), Vec3::Z),
+			PanelShape::Cylinder { radius } => {
+				let (sin, cos) = (x / radius).sin_cos();
+				(
+					Vec3::new(radius * sin, 0.0, radius * (1.0 - cos)),
+					Vec3::new(-sin, 0.0, cos),
+				)
+			}
+		};
+		for y in [half.y - inset, -half.y + inset] {
+			positions.push((position + Vec3::Y * y).to_array());
+			normals.push(normal.to_array());
+			uvs.push([x / size.x + 0.5, 0.5 - y / size.y]);
+		}
+	}
+	let indices = (0..columns.len() as u32 - 1)
+		.flat_map(|i| {
+			let [top, bottom, next_top, next_bottom] =
+				[i * 2, i * 2 + 1, i * 2 + 2, i * 2 + 3];
+			[bottom, next_bottom, next_top, bottom, next_top, top]
+		})
+		.collect();
+
+	Mesh::new(PrimitiveTopology::TriangleList)
+		.with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+		.with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+		.with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+		.with_indices(Some(Indices::U32(indices)))
+}
@@ -0,0 +1,358 @@
+//! Lets the mouse and VR laser pointers click, hover and scroll world-space panels.
+//!
+//! Each [`WorldspacePointer`] casts a ray against the meshes of the panels, and the
+//! UV coordinate of the closest hit becomes the egui pointer position on that panel.
+
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::render::mesh::VertexAttributeValues;
+use bevy::window::{CursorGrabMode, PrimaryWindow};
+use bevy_egui::egui;
+
+use crate::{PanelShape, WorldspaceUi, WorldspaceUiSet};
+
+/// How many points to scroll per line, for mice that scroll in lines.
+const POINTS_PER_LINE: f32 = 24.0;
+/// How close a [`PointerSource::Poke`] has to be to a panel to hover it, in metres.
+const POKE_HOVER_DISTANCE: f32 = 0.05;
+/// How far a [`PointerSource::Poke`] can go through a panel and still press it.
+const POKE_PRESS_DEPTH: f32 = 0.03;
+
+pub struct PointerPlugin;
+
+impl Plugin for PointerPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_systems(
+			PreUpdate,
+			(mouse_pointer, raycast_panels)
+				.chain()
+				.in_set(PointerSet)
+				.after(InputSystem)
+				.before(WorldspaceUiSet::BeginFrame),
+		);
+	}
+}
+
+/// Runs in [`PreUpdate`]. Systems that set [`WorldspacePointer::pressed`] and
+/// [`WorldspacePointer::scroll`] should run before this.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PointerSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PointerSource {
+	/// Points along the forward (-Z) axis of the entity's [`GlobalTransform`], like a
+	/// laser coming out of a VR controller.
+	#[default]
+	Forward,
+	/// Points through the mouse cursor of the entity's [`Camera`], or the middle of
+	/// the window while the cursor is grabbed. The pointer is pressed with the left
+	/// mouse button and scrolled with the mouse wheel.
+	Mouse,
+	/// A fingertip pointing along its forward (-Z) axis. It hovers panels that are
+	/// just in front of it, and presses them when it touches them, so
+	/// [`WorldspacePointer::pressed`] is set for you.
+	Poke,
+}
+
+/// Something that can point at panels. For [`PointerSource::Forward`] pointers, set
+/// [`Self::pressed`] and [`Self::scroll`] from whatever input drives them, such as a
+/// controller's trigger and thumbstick.
+///
+/// egui only has one pointer per context, so if several pointers hover the same panel
+/// they will fight over it.
+#[derive(Component, Debug, Clone, Default)]
+pub struct WorldspacePointer {
+	pub source: PointerSource,
+	pub pressed: bool,
+	/// Scroll to apply this frame, in points.
+	pub scroll: Vec2,
+	/// The panel being pointed at, and where on it in egui points.
+	pub hovered: Option<(Entity, egui::Pos2)>,
+	ray: Option<Ray>,
+	was_pressed: bool,
+}
+
+impl WorldspacePointer {
+	pub fn new(source: PointerSource) -> Self {
+		Self {
+			source,
+			..default()
+		}
+	}
+}
+
+/// Gives cameras a [`PointerSource::Mouse`] pointer as soon as they get a `C`, for
+/// cameras spawned by another plugin, such as a fly camera. Add it to [`Update`] as
+/// `add_mouse_pointer::<C>`.
+pub fn add_mouse_pointer<C: Component>(
+	mut commands: Commands,
+	cameras: Query<Entity, (Added<C>, With<Camera>)>,
+) {
+	for camera in cameras.iter() {
+		commands
+			.entity(camera)
+			.insert(WorldspacePointer::new(PointerSource::Mouse));
+	}
+}
+
+fn mouse_pointer(
+	windows: Query<&Window, With<PrimaryWindow>>,
+	buttons: Res<Input<MouseButton>>,
+	mut wheel: EventReader<MouseWheel>,
+	mut pointers: Query<(&mut WorldspacePointer, &Camera, &GlobalTransform)>,
+) {
+	let scroll = wheel
+		.read()
+		.map(|event| match event.unit {
+			MouseScrollUnit::Line => Vec2::new(event.x, event.y) * POINTS_PER_LINE,
+			MouseScrollUnit::Pixel => Vec2::new(event.x, event.y),
+		})
+		.sum::<Vec2>();
+	let Ok(window) = windows.get_single() else {
+		return;
+	};
+	let cursor = match window.cursor.grab_mode {
+		CursorGrabMode::None => window.cursor_position(),
+		_ => None,
+	}
+	.unwrap_or(Vec2::new(window.width(), window.height()) / 2.0);
+
+	for (mut pointer, camera, camera_transform) in pointers.iter_mut() {
+		if pointer.source != PointerSource::Mouse {
+			continue;
+		}
+		pointer.ray = camera.viewport_to_world(camera_transform, cursor);
+		pointer.pressed = buttons.pressed(MouseButton::Left);
+		pointer.scroll = scroll;
+	}
+}
+
+fn raycast_panels(
+	mut pointers: Query<(&mut WorldspacePointer, &GlobalTransform)>,
+	mut panels: Query<(Entity, &mut WorldspaceUi, &Handle<Mesh>, &GlobalTransform)>,
+	visibility: Query<&InheritedVisibility>,
+	meshes: Res<Assets<Mesh>>,
+) {
+	for (mut pointer, pointer_transform) in pointers.iter_mut() {
+		let forward = Ray {
+			origin: pointer_transform.translation(),
+			direction: pointer_transform.forward(),
+		};
+		let (ray, max_distance) = match pointer.source {
+			PointerSource::Forward => (Some(forward), f32::INFINITY),
+			PointerSource::Mouse => (pointer.ray, f32::INFINITY),
+			// Start a little behind the fingertip, so that panels just in front of
+			// it are hit as well as ones it has gone into.
+			PointerSource::Poke => (
+				Some(Ray {
+					origin: forward.origin - forward.direction * POKE_HOVER_DISTANCE,
+					..forward
+				}),
+				POKE_HOVER_DISTANCE + POKE_PRESS_DEPTH,
+			),
+		};
+		pointer.ray = ray;
+
+		let mut closest: Option<(f32, Entity, egui::Pos2)> = None;
+		if let Some(ray) = ray {
+			for (entity, panel, mesh, transform) in panels.iter() {
+				if visibility.get(entity).is_ok_and(|visible| !visible.get()) {
+					continue;
+				}
+				let hit = match panel.shape {
+					PanelShape::Cylinder { radius } if radius > 0.0 => {
+						raycast_cylinder(ray, transform, panel, radius)
+					}
+					_ => meshes
+						.get(mesh)
+						.and_then(|mesh| raycast_mesh(ray, mesh, transform)),
+				};
+				let Some((t, uv)) = hit else {
+					continue;
+				};
+				if t > max_distance
+					|| closest.is_some_and(|(closest_t, ..)| closest_t <= t)
+				{
+					continue;
+				}
+				let pos = panel.size_in_points() * egui::vec2(uv.x, uv.y);
+				closest = Some((t, entity, pos.to_pos2()));
+			}
+		}
+		let hovered = closest.map(|(_, entity, pos)| (entity, pos));
+		if pointer.source == PointerSource::Poke {
+			pointer.pressed = closest.is_some_and(|(t, ..)| t >= POKE_HOVER_DISTANCE);
+		}
+
+		// Let the panel we stopped pointing at know, so it doesn't show hover
+		// effects or keep dragging forever.
+		if let Some((previous, previous_pos)) = pointer.hovered {
+			if hovered.map(|(entity, _)| entity) != Some(previous) {
+				if let Ok((_, mut panel, ..)) = panels.get_mut(previous) {
+					if pointer.was_pressed {
+						panel.events.push(button_event(previous_pos, false));
+					}
+					panel.events.push(egui::Event::PointerGone);
+				}
+			}
+		}
+
+		if let Some((entity, pos)) = hovered {
+			let (_, mut panel, ..) = panels.get_mut(entity).unwrap();
+			panel.events.push(egui::Event::PointerMoved(pos));
+			if pointer.pressed != pointer.was_pressed {
+				panel.events.push(button_event(pos, pointer.pressed));
+			}
+			if pointer.scroll != Vec2::ZERO {
+				panel.events.push(egui::Event::Scroll(egui::vec2(
+					pointer.scroll.x,
+					pointer.scroll.y,
+				)));
+			}
+		}
+
+		pointer.hovered = hovered;
+		pointer.was_pressed = pointer.pressed;
+	}
+}
+
+fn button_event(pos: egui::Pos2, pressed: bool) -> egui::Event {
+	egui::Event::PointerButton {
+		pos,
+		button: egui::PointerButton::Primary,
+		pressed,
+		modifiers: egui::Modifiers::default(),
+	}
+}
+
+/// Returns the distance along the ray and the UV coordinate of the closest triangle
+/// of the mesh that the ray hits, from either side.
+fn raycast_mesh(
+	ray: Ray,
+	mesh: &Mesh,
+	transform: &GlobalTransform,
+) -> Option<(f32, Vec2)> {
+	let Some(VertexAttributeValues::Float32x3(positions)) =
+		mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+	else {
+		return None;
+	};
+	let Some(VertexAttributeValues::Float32x2(uvs)) =
+		mesh.attribute(Mesh::ATTRIBUTE_UV_0)
+	else {
+		return None;
+	};
+	let indices: Vec<usize> = match mesh.indices() {
+		Some(indices) => indices.iter().collect(),
+		None => (0..positions.len()).collect(),
+	};
+
+	// Transform the ray rather than every vertex. The direction isn't normalized
+	// afterwards, so distances stay comparable with other meshes.
+	let world_to_local = transform.compute_matrix().inverse();
+	let origin = world_to_local.transform_point3(ray.origin);
+	let direction = world_to_local.transform_vector3(ray.direction);
+
+	let mut closest: Option<(f32, Vec2)> = None;
+	for triangle in indices.chunks_exact(3) {
+		let [a, b, c] = [triangle[0], triangle[1], triangle[2]];
+		let Some((t, u, v)) = intersect_triangle(
+			origin,
+			direction,
+			[
+				positions[a].into(),
+				positions[b].into(),
+				positions[c].into(),
+			],
+		) else {
+			continue;
+		};
+		if closest.is_some_and(|(closest_t, _)| closest_t <= t) {
+			continue;
+		}
+		let uv = Vec2::from(uvs[a]) * (1.0 - u - v)
+			+ Vec2::from(uvs[b]) * u
+			+ Vec2::from(uvs[c]) * v;
+		closest = Some((t, uv));
+	}
+	closest
+}
+
+/// Returns the distance along the ray and the UV coordinate where it hits a panel
+/// bent into a cylinder of `radius`, from either side. Unlike the panel's mesh,
+/// which is made of flat segments, this is exactly round, so the UV matches where the
+/// texture is drawn.
+fn raycast_cylinder(
+	ray: Ray,
+	transform: &GlobalTransform,
+	panel: &WorldspaceUi,
+	radius: f32,
+) -> Option<(f32, Vec2)> {
+	let world_to_local = transform.compute_matrix().inverse();
+	let origin = world_to_local.transform_point3(ray.origin);
+	let direction = world_to_local.transform_vector3(ray.direction);
+
+	// The cylinder's axis is vertical, `radius` in front of the middle of the panel,
+	// so only the horizontal part of the ray matters for where it meets it.
+	let flat_origin = Vec2::new(origin.x, origin.z - radius);
+	let flat_direction = Vec2::new(direction.x, direction.z);
+	let a = flat_direction.length_squared();
+	let b = 2.0 * flat_origin.dot(flat_direction);
+	let c = flat_origin.length_squared() - radius * radius;
+	let discriminant = b * b - 4.0 * a * c;
+	if a < f32::EPSILON || discriminant < 0.0 {
+		return None;
+	}
+
+	let half = panel.size / 2.0;
+	let root = discriminant.sqrt();
+	[(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)]
+		.into_iter()
+		.filter(|t| *t > 0.0)
+		.find_map(|t| {
+			let hit = origin + direction * t;
+			// How far along the curve the hit is from the middle of the panel.
+			let x = hit.x.atan2(radius - hit.z) * radius;
+			let uv = Vec2::new(x / panel.size.x + 0.5, 0.5 - hit.y / panel.size.y);
+			inside_rounded_rect(Vec2::new(x, hit.y), half, panel.corner_radius)
+				.then_some((t, uv))
+		})
+}
+
+/// Whether `point` is on a rectangle around the origin with corners rounded by
+/// `radius`.
+fn inside_rounded_rect(point: Vec2, half: Vec2, radius: f32) -> bool {
+	let radius = radius.clamp(0.0, half.min_element());
+	let corner = point.abs() - (half - Vec2::splat(radius));
+	corner.max(Vec2::ZERO).length() <= radius
+}
+
+/// Möller–Trumbore. Returns the distance along the ray and the barycentric
+/// coordinates of the hit.
+fn intersect_triangle(
+	origin: Vec3,
+	direction: Vec3,
+	[a, b, c]: [Vec3; 3],
+) -> Option<(f32, f32, f32)> {
+	let ab = b - a;
+	let ac = c - a;
+	let p = direction.cross(ac);
+	let det = ab.dot(p);
+	if det.abs() < f32::EPSILON {
+		return None;
+	}
+	let inv_det = 1.0 / det;
+	let ao = origin - a;
+	let u = ao.dot(p) * inv_det;
+	if !(0.0..=1.0).contains(&u) {
+		return None;
+	}
+	let q = ao.cross(ab);
+	let v = direction.dot(q) * inv_det;
+	if v < 0.0 || u + v > 1.0 {
+		return None;
+	}
+	let t = ac.dot(q) * inv_det;
+	(t > 0.0).then_some((t, u, v))
+}
@@ -1,17 +1,89 @@
-use bevy::{prelude::*, render::render_asset::RenderAssets};
+use bevy::{
+	prelude::*,
+	render::{render_asset::RenderAssets, renderer::RenderDevice, texture::GpuImage},
+	utils::HashMap,
+};
+use egui::epaint::Primitive;
 use std::sync::Mutex;
 
-use crate::EguiContext;
+use crate::render_systems::{ExtractedPanel, ExtractedPanels};
 
+pub const EGUI_NODE: &str = "worldspace-egui";
+
+/// Renders every panel in [`ExtractedPanels`] to its own texture. There is only one
+/// of these in the render graph, no matter how many panels there are.
+#[derive(Default)]
 pub struct EguiNode {
-	pub output_image: Handle<Image>,
-	pub egui_ctx: EguiContext,
-	pub renderer: Mutex<egui_wgpu::Renderer>,
+	panels: HashMap<Entity, Mutex<PanelRenderer>>,
+}
+
+#[derive(Default)]
+struct PanelRenderer {
+	/// Created once the output texture is on the GPU.
+	renderer: Option<egui_wgpu::Renderer>,
+	/// Texture changes that haven't been applied yet. egui only sends each change
+	/// once, so nothing can be dropped, such as the font atlas from the first frame.
+	textures_delta: egui::TexturesDelta,
+	/// Maps the panel's `egui::TextureId::User` ids to the ids the renderer gave the
+	/// images when they were registered.
+	user_textures: HashMap<u64, (Handle<Image>, egui::TextureId)>,
+	/// The last primitives the main world sent, which are only sent when they need
+	/// drawing.
+	primitives: Vec<egui::ClippedPrimitive>,
+	pixels_per_point: f32,
+	background: [f32; 4],
+	/// `primitives`, pointing at the renderer's ids for user images.
+	render_primitives: Vec<egui::ClippedPrimitive>,
+	/// Whether the texture is out of date. Otherwise it is left alone.
+	needs_render: bool,
 }
 
 impl bevy::render::render_graph::Node for EguiNode {
-	// TODO: When should I use this instead of an Extract render system?
-	fn update(&mut self, _world: &mut World) {}
+	fn update(&mut self, world: &mut World) {
+		world.resource_scope(|world, mut panels: Mut<ExtractedPanels>| {
+			// The panel's entity was despawned, so its renderer and all of its
+			// textures can go too.
+			self.panels
+				.retain(|entity, _| panels.0.contains_key(entity));
+
+			let device = world.resource::<RenderDevice>();
+			let gpu_images = world.resource::<RenderAssets<Image>>();
+			for (entity, panel) in panels.0.iter_mut() {
+				let state = self.panels.entry(*entity).or_default().get_mut().unwrap();
+				state
+					.textures_delta
+					.append(std::mem::take(&mut panel.output.textures_delta));
+				if panel.output.redraw {
+					state.primitives = std::mem::take(&mut panel.output.primitives);
+					state.pixels_per_point = panel.output.pixels_per_point;
+					state.background = panel.output.background;
+					state.needs_render = true;
+				}
+
+				if state.renderer.is_none() {
+					// The texture may not have been uploaded yet, in which case we
+					// try again next frame.
+					let Some(output_gpu_image) = gpu_images.get(&panel.output_texture)
+					else {
+						continue;
+					};
+					info!("adding egui renderer for {entity:?}");
+					state.renderer = Some(egui_wgpu::Renderer::new(
+						device.wgpu_device(),
+						output_gpu_image.texture_format,
+						None,
+						1,
+					));
+				}
+				// An image that finished loading needs to be drawn.
+				state.needs_render |=
+					sync_user_textures(state, panel, device, gpu_images);
+				if state.needs_render {
+					remap_user_textures(state);
+				}
+			}
+		});
+	}
 
 	fn run(
 		&self,
@@ -19,45 +91,125 @@ impl bevy::render::render_graph::Node for EguiNode {
 		render_context: &mut bevy::render::renderer::RenderContext,
 		world: &World,
 	) -> Result<(), bevy::render::render_graph::NodeRunError> {
-		let device = render_context.render_device().clone();
-		let device = device.wgpu_device();
-		let queue = world
-			.get_resource::<bevy::render::renderer::RenderQueue>()
-			.unwrap();
-		let encoder = render_context.command_encoder();
-		let gpu_images = world.get_resource::<RenderAssets<Image>>().unwrap();
-		let output_gpu_image = gpu_images
-			.get(&self.output_image)
-			.expect("Should have been a `GpuImage` that corresponds to the `Image`");
-		let screen_descriptor = egui_wgpu::renderer::ScreenDescriptor {
-			pixels_per_point: 1.0,
-			size_in_pixels: [
-				output_gpu_image.texture.size().width,
-				output_gpu_image.texture.size().height,
-			],
-		};
+		let panels = world.resource::<ExtractedPanels>();
+		let gpu_images = world.resource::<RenderAssets<Image>>();
+		for (entity, state) in self.panels.iter() {
+			let Some(panel) = panels.0.get(entity) else {
+				continue;
+			};
+			let mut state = state.lock().unwrap();
+			let state = &mut *state;
+			if !state.needs_render || state.renderer.is_none() {
+				continue;
+			}
+			let output_gpu_image = gpu_images.get(&panel.output_texture).expect(
+				"Should have been a `GpuImage` that corresponds to the `Image`",
+			);
+			render_panel(state, output_gpu_image, render_context, world);
+			state.needs_render = false;
+		}
 
-		let mut renderer = self.renderer.lock().unwrap();
+		Ok(())
+	}
+}
 
-		// TODO: Eventually I'll move this to a separate user defined system.
-		let egui_output = self.egui_ctx.ctx.run(egui::RawInput::default(), |ctx| {
-			egui::Window::new("Worldspace Window")
-				.show(ctx, |ui| ui.label("I am rendering to a texture on a cube"));
-		});
-		// TODO: Handle textures to delete
-		for (tid, delta) in egui_output.textures_delta.set.iter() {
-			renderer.update_texture(device, queue, *tid, delta);
+/// Points the meshes that show user images at the renderer's ids for them.
+fn remap_user_textures(state: &mut PanelRenderer) {
+	state.render_primitives.clone_from(&state.primitives);
+	for clipped in state.render_primitives.iter_mut() {
+		let Primitive::Mesh(mesh) = &mut clipped.primitive else {
+			continue;
+		};
+		let egui::TextureId::User(id) = mesh.texture_id else {
+			continue;
+		};
+		match state.user_textures.get(&id) {
+			Some((_, renderer_id)) => mesh.texture_id = *renderer_id,
+			// Not registered yet, so there is nothing to draw it with.
+			None => mesh.indices.clear(),
 		}
-		let clipped_primitives = self.egui_ctx.ctx.tessellate(egui_output.shapes);
+	}
+}
 
-		renderer.update_buffers(
-			device,
-			queue,
-			encoder,
-			&clipped_primitives,
-			&screen_descriptor,
+/// Registers the images the panel started showing, and frees the ones it stopped
+/// showing. Returns whether any images were registered.
+fn sync_user_textures(
+	state: &mut PanelRenderer,
+	panel: &ExtractedPanel,
+	device: &RenderDevice,
+	gpu_images: &RenderAssets<Image>,
+) -> bool {
+	let Some(renderer) = state.renderer.as_mut() else {
+		return false;
+	};
+	let mut registered = false;
+	state.user_textures.retain(|id, (image, renderer_id)| {
+		let keep = panel.user_textures.get(image) == Some(id);
+		if !keep {
+			renderer.free_texture(renderer_id);
+		}
+		keep
+	});
+	for (image, id) in panel.user_textures.iter() {
+		if state.user_textures.contains_key(id) {
+			continue;
+		}
+		// Not loaded yet, so try again next frame.
+		let Some(gpu_image) = gpu_images.get(image) else {
+			continue;
+		};
+		let renderer_id = renderer.register_native_texture(
+			device.wgpu_device(),
+			&gpu_image.texture_view,
+			wgpu::FilterMode::Linear,
 		);
+		state
+			.user_textures
+			.insert(*id, (image.clone(), renderer_id));
+		registered = true;
+	}
+	registered
+}
 
+fn render_panel(
+	state: &mut PanelRenderer,
+	output_gpu_image: &GpuImage,
+	render_context: &mut bevy::render::renderer::RenderContext,
+	world: &World,
+) {
+	let textures_delta = std::mem::take(&mut state.textures_delta);
+	let clipped_primitives = &state.render_primitives;
+	let [r, g, b, a] = state.background;
+	let renderer = state
+		.renderer
+		.as_mut()
+		.expect("Only panels with a renderer are rendered");
+	let device = render_context.render_device().clone();
+	let device = device.wgpu_device();
+	let queue = world
+		.get_resource::<bevy::render::renderer::RenderQueue>()
+		.unwrap();
+	let encoder = render_context.command_encoder();
+	let screen_descriptor = egui_wgpu::renderer::ScreenDescriptor {
+		pixels_per_point: state.pixels_per_point,
+		size_in_pixels: [
+			output_gpu_image.texture.size().width,
+			output_gpu_image.texture.size().height,
+		],
+	};
+
+	for (tid, delta) in textures_delta.set.iter() {
+		renderer.update_texture(device, queue, *tid, delta);
+	}
+	renderer.update_buffers(
+		device,
+		queue,
+		encoder,
+		clipped_primitives,
+		&screen_descriptor,
+	);
+
+	{
 		let mut egui_render_pass =
 			encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
 				label: Some("Egui Render Pass"),
@@ -65,11 +217,13 @@ impl bevy::render::render_graph::Node for EguiNode {
 					view: &output_gpu_image.texture_view,
 					resolve_target: None,
 					ops: wgpu::Operations {
+						// egui draws with premultiplied alpha, so the background
+						// has to be too.
 						load: wgpu::LoadOp::Clear(wgpu::Color {
-							r: 0.1,
-							g: 0.2,
-							b: 0.3,
-							a: 1.0,
+							r: r.into(),
+							g: g.into(),
+							b: b.into(),
+							a: a.into(),
 						}),
 						store: true,
 					},
@@ -79,10 +233,13 @@ impl bevy::render::render_graph::Node for EguiNode {
 
 		renderer.render(
 			&mut egui_render_pass,
-			&clipped_primitives,
+			clipped_primitives,
 			&screen_descriptor,
 		);
+	}
 
-		Ok(())
+	// wgpu keeps the textures alive until the commands using them are done.
+	for tid in textures_delta.free.iter() {
+		renderer.free_texture(tid);
 	}
 }
@@ -1,37 +1,36 @@
+use bevy::prelude::*;
 use bevy::render::Extract;
-use bevy::{prelude::*, render::render_graph::RenderGraph};
+use bevy::utils::HashMap;
 
-use crate::render_node::EguiNode;
-use crate::EguiContext;
+use crate::{PanelOutput, WorldspaceUi};
 
-pub fn add_render_node(
-	q: Extract<Query<(Entity, &EguiContext), Added<EguiContext>>>,
-	// q: Extract<Query<&EguiContext>>,
-	textures: Extract<Res<Assets<Image>>>,
-	mut render_graph: ResMut<RenderGraph>,
-	device: Res<bevy::render::renderer::RenderDevice>,
+/// Every panel that exists this frame, copied over from the main world.
+#[derive(Resource, Default)]
+pub struct ExtractedPanels(pub HashMap<Entity, ExtractedPanel>);
+
+pub struct ExtractedPanel {
+	pub output_texture: Handle<Image>,
+	pub output: PanelOutput,
+	pub user_textures: HashMap<Handle<Image>, u64>,
+}
+
+pub fn extract_panels(
+	q: Extract<Query<(Entity, &WorldspaceUi)>>,
+	mut panels: ResMut<ExtractedPanels>,
 ) {
-	for (entity, egui_ctx) in q.iter() {
-		info!("adding render node for {entity:?}");
-		let output_texture_format = textures
-			.get(&egui_ctx.output_texture)
-			.expect("Should have found the matching `Image`")
-			.texture_descriptor
-			.format;
-		let renderer = egui_wgpu::Renderer::new(
-			device.wgpu_device(),
-			output_texture_format,
-			None,
-			1,
-		);
-		let new_node = EguiNode {
-			output_image: Handle::clone(&egui_ctx.output_texture),
-			renderer: renderer.into(),
-			egui_ctx: egui_ctx.clone(),
+	// Rebuilt from scratch, so despawned panels disappear from here too.
+	panels.0.clear();
+	panels.0.extend(q.iter().map(|(entity, panel)| {
+		let extracted = ExtractedPanel {
+			output_texture: panel.output_texture.clone(),
+			// Skip copying the primitives when they won't be drawn.
+			output: if panel.output.redraw {
+				panel.output.clone()
+			} else {
+				PanelOutput::default()
+			},
+			user_textures: panel.user_textures.clone(),
 		};
-		let node_label = "egui-texture";
-		render_graph.add_node(node_label, new_node);
-		render_graph
-			.add_node_edge(bevy::render::main_graph::node::CAMERA_DRIVER, node_label);
-	}
+		(entity, extracted)
+	}));
 }
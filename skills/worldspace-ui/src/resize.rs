@@ -0,0 +1,46 @@
+//! Keeps each panel's texture in step with its size and scale.
+
+use bevy::prelude::*;
+use bevy::render::render_resource::Extent3d;
+
+use crate::WorldspaceUi;
+
+pub(crate) fn resize_panels(
+	mut panels: Query<(Entity, &mut WorldspaceUi, Option<&GlobalTransform>)>,
+	panel_materials: Query<&Handle<StandardMaterial>>,
+	mut images: ResMut<Assets<Image>>,
+	mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+	for (entity, mut panel, transform) in panels.iter_mut() {
+		// Scaling the entity makes the texture bigger or smaller, but not the layout,
+		// so the UI zooms rather than reflows.
+		let scale = transform
+			.map(|transform| transform.to_scale_rotation_translation().0.truncate())
+			.unwrap_or(Vec2::ONE);
+		let pixels_per_point = panel.pixels_per_point_for(scale);
+		let texture_size = panel.texture_size_for(pixels_per_point);
+		if texture_size == panel.texture_size {
+			if pixels_per_point != panel.pixels_per_point {
+				panel.pixels_per_point = pixels_per_point;
+				panel.dirty = true;
+			}
+			continue;
+		}
+		let Some(image) = images.get_mut(&panel.output_texture) else {
+			continue;
+		};
+		debug!("resizing panel {entity:?} to {texture_size}");
+		image.resize(Extent3d {
+			width: texture_size.x,
+			height: texture_size.y,
+			depth_or_array_layers: 1,
+		});
+		panel.texture_size = texture_size;
+		panel.pixels_per_point = pixels_per_point;
+		panel.dirty = true;
+		// The material keeps using the old texture until it's told that it changed.
+		if let Ok(material) = panel_materials.get(entity) {
+			materials.get_mut(material);
+		}
+	}
+}
@@ -1,6 +1,5 @@
 use bevy::core_pipeline::clear_color::ClearColorConfig;
 use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
-use bevy::math::vec3;
 use bevy::prelude::EulerRot::XYZ;
 use bevy::prelude::*;
 use bevy::render::camera::RenderTarget;
@@ -9,11 +8,15 @@ use bevy::render::render_resource::{
 };
 use bevy::transform::components::Transform;
 
-use bevy_oxr::input::XrInput;
-use bevy_oxr::resources::XrFrameState;
-use bevy_oxr::xr_input::oculus_touch::OculusController;
-use bevy_oxr::xr_input::{QuatConv, Vec3Conv};
+use bevy_mod_inverse_kinematics::inverse_kinematics_system;
 use bevy_oxr::DefaultXrPlugins;
+use openxr_6dof::diagnostics::{
+	XrDiagnosticsOverlay, XrDiagnosticsPlugin, XrIkTimingSet,
+};
+use openxr_6dof::poses::{XrPosePlugin, XrPoseSet, XrPoses};
+use xr_locomotion::comfort::ComfortPlugin;
+use xr_locomotion::xr::XrLocomotionPlugin;
+use xr_locomotion::{LocomotionPlugin, PlayerHead, PlayerRig};
 
 const ASSET_FOLDER: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../assets/");
 
@@ -26,10 +29,25 @@ fn main() {
 		.add_plugins(LogDiagnosticsPlugin::default())
 		.add_plugins(FrameTimeDiagnosticsPlugin)
 		.add_plugins(bevy_mod_inverse_kinematics::InverseKinematicsPlugin)
+		.add_plugins((XrPosePlugin, XrDiagnosticsPlugin))
+		.configure_sets(
+			PostUpdate,
+			(
+				XrIkTimingSet::Start.before(inverse_kinematics_system),
+				XrIkTimingSet::End.after(inverse_kinematics_system),
+			),
+		)
+		.add_plugins((LocomotionPlugin, XrLocomotionPlugin, ComfortPlugin))
 		.add_systems(Startup, setup)
 		.add_systems(
 			Update,
-			(hands, setup_ik, head_sync, body_sync, true_head_sync),
+			(setup_ik, attach_avatar_to_rig, attach_diagnostics_overlay),
+		)
+		.add_systems(
+			Update,
+			(hands, head_sync, body_sync, true_head_sync)
+				.after(setup_ik)
+				.in_set(XrPoseSet::Apply),
 		)
 		.run();
 }
@@ -191,125 +209,85 @@ pub struct Hips;
 #[derive(Component)]
 pub struct TrueHead;
 
+/// Tracked poses are relative to the rig, so the avatar has to move along with it.
+fn attach_avatar_to_rig(
+	mut commands: Commands,
+	rigs: Query<Entity, Added<PlayerRig>>,
+	avatars: Query<Entity, With<Avatar>>,
+) {
+	for rig in rigs.iter() {
+		for avatar in avatars.iter() {
+			commands.entity(rig).add_child(avatar);
+		}
+	}
+}
+
+/// Shows the XR diagnostics in front of the head, wherever locomotion took it.
+fn attach_diagnostics_overlay(
+	mut overlay: ResMut<XrDiagnosticsOverlay>,
+	heads: Query<Entity, Added<PlayerHead>>,
+) {
+	for head in heads.iter() {
+		overlay.enabled = true;
+		overlay.anchor = Some(head);
+	}
+}
+
 fn true_head_sync(
 	mut head_query: Query<(&mut Transform, &TrueHead)>,
-	frame_state: Res<XrFrameState>,
-	xr_input: Res<XrInput>,
+	poses: Res<XrPoses>,
 ) {
-	let mut func = || -> color_eyre::Result<()> {
-		let frame_state = *frame_state.lock().unwrap();
-		let a = xr_input
-			.head
-			.relate(&xr_input.stage, frame_state.predicted_display_time)?;
-		for (mut head, _) in head_query.iter_mut() {
-			head.rotation = a.0.pose.orientation.to_quat();
-		}
-		Ok(())
+	let Some(head_pose) = poses.head else {
+		return;
 	};
-	let _ = func();
+	for (mut head, _) in head_query.iter_mut() {
+		head.rotation = head_pose.rotation;
+	}
 }
 
-fn head_sync(
-	mut head_query: Query<(&mut Transform, &Head)>,
-	frame_state: Res<XrFrameState>,
-	xr_input: Res<XrInput>,
-) {
-	let mut func = || -> color_eyre::Result<()> {
-		let frame_state = *frame_state.lock().unwrap();
-		let a = xr_input
-			.head
-			.relate(&xr_input.stage, frame_state.predicted_display_time)?;
-		for (mut head, _) in head_query.iter_mut() {
-			*head = Transform {
-				translation: a.0.pose.position.to_vec3(),
-				rotation: a.0.pose.orientation.to_quat(),
-				scale: Default::default(),
-			};
-		}
-		Ok(())
+fn head_sync(mut head_query: Query<(&mut Transform, &Head)>, poses: Res<XrPoses>) {
+	let Some(head_pose) = poses.head else {
+		return;
 	};
-	let _ = func();
+	for (mut head, _) in head_query.iter_mut() {
+		*head = head_pose;
+	}
 }
 
-fn body_sync(
-	frame_state: Res<XrFrameState>,
-	xr_input: Res<XrInput>,
-	mut avatar: Query<(&mut Transform, &Hips)>,
-) {
-	let mut func = || -> color_eyre::Result<()> {
-		let frame_state = *frame_state.lock().unwrap();
-		let a = xr_input
-			.head
-			.relate(&xr_input.stage, frame_state.predicted_display_time)?;
-		for (mut avatar, _) in avatar.iter_mut() {
-			let head_pos = Transform {
-				translation: a.0.pose.position.to_vec3(),
-				rotation: Quat::IDENTITY,
-				scale: vec3(1.0, 1.0, 1.0),
-			};
-			*avatar = head_pos.with_translation(Vec3 {
-				x: head_pos.translation.x,
-				y: head_pos.translation.y - 0.6,
-				z: head_pos.translation.z,
-			})
-		}
-		Ok(())
+fn body_sync(poses: Res<XrPoses>, mut avatar: Query<(&mut Transform, &Hips)>) {
+	let Some(head_pose) = poses.head else {
+		return;
 	};
-	let _ = func();
+	for (mut avatar, _) in avatar.iter_mut() {
+		*avatar = Transform::from_translation(head_pose.translation - 0.6 * Vec3::Y);
+	}
 }
 
 fn hands(
 	mut gizmos: Gizmos,
-	oculus_controller: Res<OculusController>,
-	frame_state: Res<XrFrameState>,
-	xr_input: Res<XrInput>,
+	poses: Res<XrPoses>,
 	mut hands: Query<(&mut Transform, &Hand)>,
+	rig: Query<&GlobalTransform, With<PlayerRig>>,
 ) {
-	let mut func = || -> color_eyre::Result<()> {
-		let frame_state = *frame_state.lock().unwrap();
-
-		let right_controller = oculus_controller
-			.grip_space
-			.right
-			.relate(&xr_input.stage, frame_state.predicted_display_time)?;
-		let left_controller = oculus_controller
-			.grip_space
-			.left
-			.relate(&xr_input.stage, frame_state.predicted_display_time)?;
-		gizmos.rect(
-			right_controller.0.pose.position.to_vec3(),
-			right_controller.0.pose.orientation.to_quat(),
-			Vec2::new(0.05, 0.2),
-			Color::YELLOW_GREEN,
-		);
+	let rig = rig.get_single().copied().unwrap_or_default();
+	let (_, rig_rotation, _) = rig.to_scale_rotation_translation();
+	for grip in [poses.left_grip, poses.right_grip].into_iter().flatten() {
 		gizmos.rect(
-			left_controller.0.pose.position.to_vec3(),
-			left_controller.0.pose.orientation.to_quat(),
+			rig.transform_point(grip.translation),
+			rig_rotation * grip.rotation,
 			Vec2::new(0.05, 0.2),
 			Color::YELLOW_GREEN,
 		);
-		for (mut transform, hand) in hands.iter_mut() {
-			match hand {
-				Hand::Left => {
-					*transform = Transform {
-						translation: left_controller.0.pose.position.to_vec3(),
-						rotation: left_controller.0.pose.orientation.to_quat(),
-						scale: Default::default(),
-					};
-				}
-				Hand::Right => {
-					*transform = Transform {
-						translation: right_controller.0.pose.position.to_vec3(),
-						rotation: right_controller.0.pose.orientation.to_quat(),
-						scale: Default::default(),
-					};
-				}
-			}
+	}
+	for (mut transform, hand) in hands.iter_mut() {
+		let grip = match hand {
+			Hand::Left => poses.left_grip,
+			Hand::Right => poses.right_grip,
+		};
+		if let Some(grip) = grip {
+			*transform = grip;
 		}
-		Ok(())
-	};
-
-	let _ = func();
+	}
 }
 
 fn setup_ik(
@@ -319,6 +297,7 @@ fn setup_ik(
 	added_query: Query<(Entity, &AvatarSetup)>,
 	children: Query<&Children>,
 	names: Query<&Name>,
+	rig: Query<Entity, With<PlayerRig>>,
 ) {
 	for (entity, _thing) in added_query.iter() {
 		let mut right_hand = None;
@@ -360,6 +339,11 @@ fn setup_ik(
 			Some(e) => e,
 			None => return,
 		};
+		// The targets are set from poses relative to the rig, so wait for it to spawn
+		// before finishing the setup.
+		let Ok(rig) = rig.get_single() else {
+			return;
+		};
 		commands.entity(entity).remove::<AvatarSetup>();
 
 		commands.entity(hips).insert(Hips);
@@ -372,6 +356,11 @@ fn setup_ik(
 			.id();
 		let target_entity3 = commands.spawn((TransformBundle::default(), Head)).id();
 		let _hips_entity = commands.spawn((TransformBundle::default(), Hips)).id();
+		commands.entity(rig).push_children(&[
+			target_entity1,
+			target_entity2,
+			target_entity3,
+		]);
 		// Add an IK constraint to the right hand, using the targets that were created earlier.
 		commands
 			.entity(left_hand)
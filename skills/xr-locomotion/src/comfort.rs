@@ -0,0 +1,432 @@
+//! A tunneling vignette that narrows the view while the [`PlayerRig`] moves
+//! artificially, which helps users who get motion sick.
+//!
+//! Only the rig's own motion counts. Walking around the playspace moves the head
+//! relative to the rig, and is left alone.
+
+use bevy::pbr::NotShadowCaster;
+use bevy::prelude::*;
+use bevy::render::mesh::Indices;
+use bevy::render::render_resource::PrimitiveTopology;
+
+use crate::{LocomotionSet, PlayerHead, PlayerRig};
+
+/// How far in front of the eyes the overlay is drawn, in metres.
+const OVERLAY_DISTANCE: f32 = 0.2;
+/// Half-angle of the hole in the overlay when the vignette is off. Wide enough to be
+/// outside of the field of view of current headsets.
+const OPEN_APERTURE: f32 = 1.2;
+
+pub struct ComfortPlugin;
+
+impl Plugin for ComfortPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<ComfortSettings>()
+			.init_resource::<RigVelocity>()
+			.init_resource::<Vignette>()
+			.add_systems(
+				Update,
+				(
+					spawn_overlay,
+					measure_rig_velocity,
+					update_vignette,
+					update_overlay,
+				)
+					.chain()
+					.after(LocomotionSet),
+			);
+	}
+}
+
+/// Per-user comfort preferences. Different people tolerate artificial motion very
+/// differently, so this is meant to be exposed in a settings menu. See the presets
+/// like [`ComfortSettings::strong`].
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct ComfortSettings {
+	pub enabled: bool,
+	/// Maximum intensity of the vignette, in `[0, 1]`.
+	pub strength: f32,
+	/// Linear speed of the rig (m/s) at which the vignette starts to close.
+	pub linear_start: f32,
+	/// Linear speed of the rig (m/s) at which the vignette is fully closed.
+	pub linear_full: f32,
+	/// Angular speed of the rig (rad/s) at which the vignette starts to close.
+	pub angular_start: f32,
+	/// Angular speed of the rig (rad/s) at which the vignette is fully closed.
+	pub angular_full: f32,
+	/// How quickly the intensity can increase, per second.
+	pub fade_in: f32,
+	/// How quickly the intensity can decrease, per second.
+	pub fade_out: f32,
+	/// Half-angle (radians) of the clear part of the view at full intensity.
+	pub min_aperture: f32,
+}
+
+impl ComfortSettings {
+	/// No vignette at all.
+	pub fn off() -> Self {
+		Self {
+			enabled: false,
+			..default()
+		}
+	}
+
+	/// A subtle vignette for users who are mostly fine with artificial motion.
+	pub fn mild() -> Self {
+		Self {
+			strength: 0.6,
+			linear_full: 4.0,
+			angular_full: 180_f32.to_radians(),
+			min_aperture: 45_f32.to_radians(),
+			..default()
+		}
+	}
+
+	/// A vignette that kicks in early and closes far, for sensitive users.
+	pub fn strong() -> Self {
+		Self {
+			strength: 1.0,
+			linear_start: 0.1,
+			linear_full: 1.5,
+			angular_start: 5_f32.to_radians(),
+			angular_full: 60_f32.to_radians(),
+			min_aperture: 20_f32.to_radians(),
+			..default()
+		}
+	}
+}
+
+impl Default for ComfortSettings {
+	fn default() -> Self {
+		Self {
+			enabled: true,
+			strength: 0.85,
+			linear_start: 0.2,
+			linear_full: 2.5,
+			angular_start: 10_f32.to_radians(),
+			angular_full: 90_f32.to_radians(),
+			fade_in: 4.0,
+			fade_out: 2.0,
+			min_aperture: 30_f32.to_radians(),
+		}
+	}
+}
+
+/// Velocity of the [`PlayerRig`] over the last frame.
+///
+/// Teleports and snap turns show up as a one frame spike, which only lets the
+/// vignette close by one frame's worth of [`ComfortSettings::fade_in`] before it
+/// fades back out.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct RigVelocity {
+	/// Metres per second.
+	pub linear: Vec3,
+	/// Radians per second, ignoring the direction of rotation.
+	pub angular: f32,
+	previous: Option<Transform>,
+}
+
+/// How closed the vignette currently is, in `[0, 1]`.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub struct Vignette {
+	pub intensity: f32,
+}
+
+/// The mesh that draws the vignette. It is spawned as a child of the
+/// [`PlayerHead`].
+#[derive(Component, Debug)]
+pub struct VignetteOverlay;
+
+fn measure_rig_velocity(
+	time: Res<Time>,
+	mut velocity: ResMut<RigVelocity>,
+	rig: Query<&Transform, With<PlayerRig>>,
+) {
+	let dt = time.delta_seconds();
+	let Ok(current) = rig.get_single() else {
+		*velocity = RigVelocity::default();
+		return;
+	};
+	let (linear, angular) = match velocity.previous {
+		Some(previous) if dt > 0.0 => (
+			(current.translation - previous.translation) / dt,
+			previous.rotation.angle_between(current.rotation) / dt,
+		),
+		_ => (Vec3::ZERO, 0.0),
+	};
+	*velocity = RigVelocity {
+		linear,
+		angular,
+		previous: Some(*current),
+	};
+}
+
+fn update_vignette(
+	time: Res<Time>,
+	settings: Res<ComfortSettings>,
+	velocity: Res<RigVelocity>,
+	mut vignette: ResMut<Vignette>,
+) {
+	let target = if settings.enabled {
+		let linear = inverse_lerp(
+			settings.linear_start,
+			settings.linear_full,
+			velocity.linear.length(),
+		);
+		let angular = inverse_lerp(
+			settings.angular_start,
+			settings.angular_full,
+			velocity.angular,
+		);
+		linear.max(angular) * settings.strength.clamp(0.0, 1.0)
+	} else {
+		0.0
+	};
+
+	let dt = time.delta_seconds();
+	let current = vignette.intensity;
+	let intensity = if target > current {
+		(current + settings.fade_in * dt).min(target)
+	} else {
+		(current - settings.fade_out * dt).max(target)
+	};
+	// Avoid triggering change detection on every frame.
+	if intensity != current {
+		vignette.intensity = intensity;
+	}
+}
+
+fn spawn_overlay(
+	mut commands: Commands,
+	mut meshes: ResMut<Assets<Mesh>>,
+	mut materials: ResMut<Assets<StandardMaterial>>,
+	heads: Query<Entity, Added<PlayerHead>>,
+) {
+	for head in heads.iter() {
+		let overlay = commands
+			.spawn((
+				PbrBundle {
+					mesh: meshes.add(vignette_mesh(64, 0.5, 20.0)),
+					material: materials.add(StandardMaterial {
+						base_color: Color::rgba(0.0, 0.0, 0.0, 0.0),
+						alpha_mode: AlphaMode::Blend,
+						unlit: true,
+						cull_mode: None,
+						..default()
+					}),
+					transform: Transform::from_xyz(0.0, 0.0, -OVERLAY_DISTANCE),
+					visibility: Visibility::Hidden,
+					..default()
+				},
+				NotShadowCaster,
+				VignetteOverlay,
+			))
+			.id();
+		commands.entity(head).add_child(overlay);
+	}
+}
+
+fn update_overlay(
+	vignette: Res<Vignette>,
+	settings: Res<ComfortSettings>,
+	mut materials: ResMut<Assets<StandardMaterial>>,
+	mut overlays: Query<
+		(&mut Transform, &mut Visibility, &Handle<StandardMaterial>),
+		With<VignetteOverlay>,
+	>,
+) {
+	if !vignette.is_changed() {
+		return;
+	}
+	let intensity = vignette.intensity;
+	let aperture = OPEN_APERTURE + (settings.min_aperture - OPEN_APERTURE) * intensity;
+	let radius = OVERLAY_DISTANCE * aperture.tan();
+	for (mut transform, mut visibility, material) in overlays.iter_mut() {
+		*visibility = if intensity > 0.0 {
+			Visibility::Inherited
+		} else {
+			Visibility::Hidden
+		};
+		transform.scale = Vec3::new(radius, radius, 1.0);
+		if let Some(material) = materials.get_mut(material) {
+			material.base_color.set_a(intensity);
+		}
+	}
+}
+
+/// A flat ring facing +Z with a hole of radius 1. The vertex alpha fades from 0 at the
+/// hole to 1 at `1 + feather`, and stays opaque out to `outer_radius`.
+fn vignette_mesh(segments: u32, feather: f32, outer_radius: f32) -> Mesh {
+	let rings = [(1.0, 0.0), (1.0 + feather, 1.0), (outer_radius, 1.0)];
+	let mut positions = Vec::new();
+	let mut colors = Vec::new();
+	for (radius, alpha) in rings {
+		for i in 0..segments {
+			let angle = i as f32 / segments as f32 * std::f32::consts::TAU;
+			positions.push([radius * angle.cos(), radius * angle.sin(), 0.0]);
+			colors.push([0.0, 0.0, 0.0, alpha]);
+		}
+	}
+	let normals = vec![[0.0, 0.0, 1.0]; positions.len()];
+
+	let mut indices = Vec::new();
+	for ring in 0..rings.len() as u32 - 1 {
+		let inner = ring * segments;
+		let outer = inner + segments;
+		for i in 0..segments {
+			let next = (i + 1) % segments;
+			indices.extend([inner + i, outer + i, inner + next]);
+			indices.extend([inner + next, outer + i, outer + next]);
+		}
+	}
+
+	let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+	mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+	mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+	mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+	mesh.set_indices(Some(Indices::U32(indices)));
+	mesh
+}
+
+/// Where `value` sits between `start` and `end`, clamped to `[0, 1]`.
+fn inverse_lerp(start: f32, end: f32, value: f32) -> f32 {
+	if end <= start {
+		return if value >= end { 1.0 } else { 0.0 };
+	}
+	((value - start) / (end - start)).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+
+	use bevy::time::TimeUpdateStrategy;
+
+	use super::*;
+
+	const STEP: Duration = Duration::from_millis(100);
+
+	/// An app that only measures the rig and updates the vignette, without the
+	/// overlay that needs a renderer.
+	fn vignette_app(settings: ComfortSettings) -> (App, Entity) {
+		let mut app = App::new();
+		app.add_plugins(MinimalPlugins)
+			.insert_resource(TimeUpdateStrategy::ManualDuration(STEP))
+			.insert_resource(settings)
+			.init_resource::<RigVelocity>()
+			.init_resource::<Vignette>()
+			.add_systems(Update, (measure_rig_velocity, update_vignette).chain());
+		let rig = app.world.spawn((Transform::default(), PlayerRig)).id();
+		// The first frame has no delta, and records where the rig starts.
+		app.update();
+		(app, rig)
+	}
+
+	/// Moves the rig by `offset` and turns it by `angle` around Y over one step.
+	fn step(app: &mut App, rig: Entity, offset: Vec3, angle: f32) -> f32 {
+		let mut transform = app.world.get_mut::<Transform>(rig).unwrap();
+		transform.translation += offset;
+		transform.rotate_y(angle);
+		app.update();
+		app.world.resource::<Vignette>().intensity
+	}
+
+	fn assert_close(actual: f32, expected: f32) {
+		assert!(
+			(actual - expected).abs() < 1e-4,
+			"expected {expected}, got {actual}"
+		);
+	}
+
+	#[test]
+	fn inverse_lerp_clamps() {
+		assert_eq!(inverse_lerp(1.0, 3.0, 0.0), 0.0);
+		assert_eq!(inverse_lerp(1.0, 3.0, 2.0), 0.5);
+		assert_eq!(inverse_lerp(1.0, 3.0, 5.0), 1.0);
+		// A zero-width range is a step.
+		assert_eq!(inverse_lerp(2.0, 2.0, 1.9), 0.0);
+		assert_eq!(inverse_lerp(2.0, 2.0, 2.0), 1.0);
+	}
+
+	#[test]
+	fn standing_still_keeps_the_view_open() {
+		let (mut app, rig) = vignette_app(ComfortSettings::default());
+		for _ in 0..5 {
+			assert_eq!(step(&mut app, rig, Vec3::ZERO, 0.0), 0.0);
+		}
+	}
+
+	#[test]
+	fn fades_in_while_moving_and_out_after_stopping() {
+		let settings = ComfortSettings::default();
+		let dt = STEP.as_secs_f32();
+		// Moving at `linear_full` aims for `strength`.
+		let offset = Vec3::X * settings.linear_full * dt;
+		let (mut app, rig) = vignette_app(settings.clone());
+
+		assert_close(step(&mut app, rig, offset, 0.0), settings.fade_in * dt);
+		for _ in 0..10 {
+			step(&mut app, rig, offset, 0.0);
+		}
+		assert_close(
+			app.world.resource::<Vignette>().intensity,
+			settings.strength,
+		);
+
+		assert_close(
+			step(&mut app, rig, Vec3::ZERO, 0.0),
+			settings.strength - settings.fade_out * dt,
+		);
+		for _ in 0..10 {
+			step(&mut app, rig, Vec3::ZERO, 0.0);
+		}
+		assert_eq!(app.world.resource::<Vignette>().intensity, 0.0);
+	}
+
+	#[test]
+	fn partial_speed_partially_closes() {
+		let settings = ComfortSettings {
+			fade_in: 100.0,
+			..default()
+		};
+		let dt = STEP.as_secs_f32();
+		let speed = (settings.linear_start + settings.linear_full) / 2.0;
+		let (mut app, rig) = vignette_app(settings.clone());
+		assert_close(
+			step(&mut app, rig, Vec3::Z * speed * dt, 0.0),
+			settings.strength / 2.0,
+		);
+	}
+
+	#[test]
+	fn turning_closes_the_vignette() {
+		let settings = ComfortSettings {
+			fade_in: 100.0,
+			..default()
+		};
+		let dt = STEP.as_secs_f32();
+		let (mut app, rig) = vignette_app(settings.clone());
+		assert_close(
+			step(&mut app, rig, Vec3::ZERO, settings.angular_full * dt),
+			settings.strength,
+		);
+	}
+
+	#[test]
+	fn snap_only_closes_for_one_frame() {
+		let settings = ComfortSettings::default();
+		let dt = STEP.as_secs_f32();
+		let (mut app, rig) = vignette_app(settings.clone());
+		let peak = step(&mut app, rig, Vec3::X * 5.0, 0.0);
+		assert_close(peak, settings.fade_in * dt);
+		assert!(step(&mut app, rig, Vec3::ZERO, 0.0) < peak);
+	}
+
+	#[test]
+	fn disabled_never_closes() {
+		let (mut app, rig) = vignette_app(ComfortSettings::off());
+		for _ in 0..5 {
+			assert_eq!(step(&mut app, rig, Vec3::X * 5.0, 1.0), 0.0);
+		}
+	}
+}
@@ -0,0 +1,368 @@
+//! Artificial locomotion for a VR player rig.
+//!
+//! The plugin moves an entity tagged with [`PlayerRig`] (the root of the tracking
+//! space) based on the [`LocomotionInput`] resource. Apart from the [`xr`] module,
+//! nothing in here talks to OpenXR: [`xr::XrLocomotionPlugin`] fills in
+//! [`LocomotionInput`] from the controllers, but a test or a flatscreen skill can just
+//! as well script it.
+
+pub mod comfort;
+mod movement;
+mod teleport;
+pub mod xr;
+
+use bevy::gizmos::GizmoConfig;
+use bevy::prelude::*;
+
+pub use crate::teleport::{TeleportSurface, TeleportTarget};
+
+pub struct LocomotionPlugin;
+
+impl Plugin for LocomotionPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<LocomotionSettings>()
+			.init_resource::<LocomotionInput>()
+			.init_resource::<LocomotionState>()
+			.add_systems(
+				Update,
+				(
+					movement::turn,
+					movement::smooth_move,
+					teleport::aim_teleport,
+					teleport::teleport,
+				)
+					.chain()
+					.in_set(LocomotionSet),
+			)
+			.add_systems(
+				Update,
+				teleport::draw_teleport_arc
+					.after(LocomotionSet)
+					.run_if(resource_exists::<GizmoConfig>()),
+			);
+	}
+}
+
+/// All of the systems that move the [`PlayerRig`]. Systems that write
+/// [`LocomotionInput`] should run before this set.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LocomotionSet;
+
+/// The root of the tracking space. Locomotion moves this entity, and everything that
+/// is tracked (head, hands, cameras) should be a descendant of it.
+#[derive(Component, Debug, Default)]
+pub struct PlayerRig;
+
+/// The entity whose [`GlobalTransform`] is the user's head. Turning pivots around it
+/// and head-relative movement follows where it looks.
+#[derive(Component, Debug, Default)]
+pub struct PlayerHead;
+
+/// The hand used for hand-relative movement and for aiming the teleport arc.
+#[derive(Component, Debug, Default)]
+pub struct LocomotionHand;
+
+/// Input for this frame, in controller terms.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct LocomotionInput {
+	/// Smooth movement stick. `x` is right and `y` is forward, both in `[-1, 1]`.
+	pub move_axis: Vec2,
+	/// Turning stick, in `[-1, 1]`. Positive turns right.
+	pub turn_axis: f32,
+	/// Whether the teleport arc is being aimed. Teleport happens on release.
+	pub teleport: bool,
+}
+
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct LocomotionSettings {
+	/// Speed of smooth movement, in metres per second.
+	pub move_speed: f32,
+	/// What "forward" means for smooth movement.
+	pub move_reference: MoveReference,
+	pub turn: TurnMode,
+	/// Stick values with a magnitude below this are ignored.
+	pub deadzone: f32,
+	pub teleport: TeleportSettings,
+}
+
+impl Default for LocomotionSettings {
+	fn default() -> Self {
+		Self {
+			move_speed: 2.0,
+			move_reference: MoveReference::Head,
+			turn: TurnMode::default(),
+			deadzone: 0.15,
+			teleport: TeleportSettings::default(),
+		}
+	}
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MoveReference {
+	/// Move in the direction the head is looking.
+	#[default]
+	Head,
+	/// Move in the direction the [`LocomotionHand`] is pointing.
+	Hand,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TurnMode {
+	/// Turn by a fixed `angle` (radians) each time the stick is pushed past
+	/// `threshold`. The stick has to come back below half of `threshold` before it
+	/// can snap again.
+	Snap { angle: f32, threshold: f32 },
+	/// Turn continuously at up to `speed` radians per second.
+	Smooth { speed: f32 },
+}
+
+impl Default for TurnMode {
+	fn default() -> Self {
+		Self::Snap {
+			angle: 30_f32.to_radians(),
+			threshold: 0.7,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TeleportSettings {
+	pub enabled: bool,
+	/// Speed the arc leaves the hand at, in metres per second.
+	pub launch_speed: f32,
+	/// Downwards acceleration of the arc, in metres per second squared.
+	pub gravity: f32,
+	/// Simulated time between two points of the arc, in seconds.
+	pub time_step: f32,
+	/// The arc gives up after this many segments.
+	pub max_segments: usize,
+	/// Surfaces steeper than this (radians) are not valid teleport targets.
+	pub max_slope: f32,
+}
+
+impl Default for TeleportSettings {
+	fn default() -> Self {
+		Self {
+			enabled: true,
+			launch_speed: 8.0,
+			gravity: 9.81,
+			time_step: 0.05,
+			max_segments: 60,
+			max_slope: 30_f32.to_radians(),
+		}
+	}
+}
+
+/// Locomotion state carried between frames.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct LocomotionState {
+	/// Whether the turn stick went back to the centre since the last snap turn.
+	pub snap_armed: bool,
+	/// Points of the teleport arc, empty while not aiming.
+	pub arc: Vec<Vec3>,
+	/// Where the arc landed, if it hit a [`TeleportSurface`].
+	pub teleport_target: Option<TeleportTarget>,
+	was_aiming: bool,
+}
+
+/// Position of the head relative to the rig, projected onto the rig's floor.
+fn head_offset(rig: &GlobalTransform, head: &GlobalTransform) -> Vec3 {
+	let local = rig.affine().inverse().transform_point3(head.translation());
+	Vec3::new(local.x, 0.0, local.z)
+}
+
+/// Removes the part of `v` that is below `deadzone`, rescaling the rest to `[0, 1]`.
+fn apply_deadzone(v: Vec2, deadzone: f32) -> Vec2 {
+	let len = v.length();
+	if len <= deadzone {
+		return Vec2::ZERO;
+	}
+	let scaled = ((len - deadzone) / (1.0 - deadzone)).min(1.0);
+	v / len * scaled
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+
+	use bevy::time::TimeUpdateStrategy;
+
+	use super::*;
+
+	const STEP: Duration = Duration::from_millis(100);
+
+	/// A rig at the origin, with a head and a hand as its children.
+	fn rig_app(head: Transform, hand: Transform) -> (App, Entity, Entity) {
+		let mut app = App::new();
+		app.add_plugins((MinimalPlugins, TransformPlugin, HierarchyPlugin))
+			.add_plugins(LocomotionPlugin)
+			.insert_resource(TimeUpdateStrategy::ManualDuration(STEP));
+		let head = app
+			.world
+			.spawn((TransformBundle::from_transform(head), PlayerHead))
+			.id();
+		let hand = app
+			.world
+			.spawn((TransformBundle::from_transform(hand), LocomotionHand))
+			.id();
+		let rig = app
+			.world
+			.spawn((TransformBundle::default(), PlayerRig))
+			.push_children(&[head, hand])
+			.id();
+		// Propagate the transforms, which locomotion reads.
+		app.update();
+		(app, rig, head)
+	}
+
+	fn step(app: &mut App, input: LocomotionInput) {
+		app.insert_resource(input);
+		app.update();
+	}
+
+	fn rig_transform(app: &App, rig: Entity) -> Transform {
+		*app.world.get::<Transform>(rig).unwrap()
+	}
+
+	fn assert_close(actual: Vec3, expected: Vec3) {
+		assert!(
+			actual.abs_diff_eq(expected, 1e-4),
+			"expected {expected}, got {actual}"
+		);
+	}
+
+	#[test]
+	fn snap_turn_pivots_around_head() {
+		let head = Transform::from_xyz(1.0, 1.7, 0.5);
+		let (mut app, rig, head) = rig_app(head, Transform::default());
+		let before = app
+			.world
+			.get::<GlobalTransform>(head)
+			.unwrap()
+			.translation();
+
+		let turn = LocomotionInput {
+			turn_axis: 1.0,
+			..default()
+		};
+		step(&mut app, turn.clone());
+		let after = app
+			.world
+			.get::<GlobalTransform>(head)
+			.unwrap()
+			.translation();
+		assert_close(after, before);
+		let (_, angle) = rig_transform(&app, rig).rotation.to_axis_angle();
+		assert!((angle - 30_f32.to_radians()).abs() < 1e-4);
+
+		// Holding the stick doesn't snap again until it comes back to the centre.
+		step(&mut app, turn.clone());
+		let (_, held) = rig_transform(&app, rig).rotation.to_axis_angle();
+		assert!((held - angle).abs() < 1e-4);
+		step(&mut app, LocomotionInput::default());
+		step(&mut app, turn);
+		let (_, again) = rig_transform(&app, rig).rotation.to_axis_angle();
+		assert!((again - 60_f32.to_radians()).abs() < 1e-4);
+	}
+
+	#[test]
+	fn smooth_move_follows_head() {
+		// Looking to the left, along -X.
+		let head = Transform::from_xyz(0.0, 1.7, 0.0)
+			.with_rotation(Quat::from_rotation_y(90_f32.to_radians()));
+		let (mut app, rig, _) = rig_app(head, Transform::default());
+		let forward = LocomotionInput {
+			move_axis: Vec2::Y,
+			..default()
+		};
+		step(&mut app, forward);
+		let distance = LocomotionSettings::default().move_speed * STEP.as_secs_f32();
+		assert_close(rig_transform(&app, rig).translation, Vec3::NEG_X * distance);
+	}
+
+	#[test]
+	fn smooth_move_follows_hand() {
+		let head = Transform::from_xyz(0.0, 1.7, 0.0);
+		// Pointing to the right, along +X, while looking ahead.
+		let hand = Transform::from_xyz(0.3, 1.2, -0.2)
+			.with_rotation(Quat::from_rotation_y(-90_f32.to_radians()));
+		let (mut app, rig, _) = rig_app(head, hand);
+		app.world
+			.resource_mut::<LocomotionSettings>()
+			.move_reference = MoveReference::Hand;
+		let forward = LocomotionInput {
+			move_axis: Vec2::Y,
+			..default()
+		};
+		step(&mut app, forward);
+		let distance = LocomotionSettings::default().move_speed * STEP.as_secs_f32();
+		assert_close(rig_transform(&app, rig).translation, Vec3::X * distance);
+	}
+
+	/// Aims the teleport arc for a frame, then releases it, returning where it
+	/// landed.
+	fn aim_and_release(app: &mut App) -> Option<TeleportTarget> {
+		step(
+			app,
+			LocomotionInput {
+				teleport: true,
+				..default()
+			},
+		);
+		let target = app.world.resource::<LocomotionState>().teleport_target;
+		step(app, LocomotionInput::default());
+		target
+	}
+
+	#[test]
+	fn teleports_onto_valid_surface() {
+		let head = Transform::from_xyz(0.2, 1.7, 0.1);
+		// Pointing ahead and a little up.
+		let hand = Transform::from_xyz(0.0, 1.5, 0.0)
+			.with_rotation(Quat::from_rotation_x(20_f32.to_radians()));
+		let (mut app, rig, head) = rig_app(head, hand);
+		app.world.spawn((
+			TransformBundle::default(),
+			TeleportSurface {
+				half_size: Vec2::splat(20.0),
+			},
+		));
+		app.update();
+
+		let target = aim_and_release(&mut app).expect("the arc should land");
+		assert!(target.valid);
+		assert!(target.position.z < -1.0);
+		app.update();
+		// The floor under the head is moved onto the target.
+		let head = app
+			.world
+			.get::<GlobalTransform>(head)
+			.unwrap()
+			.translation();
+		assert_close(Vec3::new(head.x, 0.0, head.z), target.position);
+		assert!(app.world.resource::<LocomotionState>().arc.is_empty());
+		assert_ne!(rig_transform(&app, rig).translation, Vec3::ZERO);
+	}
+
+	#[test]
+	fn rejects_steep_surface() {
+		let head = Transform::from_xyz(0.0, 1.7, 0.0);
+		let hand = Transform::from_xyz(0.0, 1.5, 0.0);
+		let (mut app, rig, _) = rig_app(head, hand);
+		// A wall-like slope ahead, leaning back by 30 degrees.
+		app.world.spawn((
+			TransformBundle::from_transform(
+				Transform::from_xyz(0.0, 0.0, -3.0)
+					.with_rotation(Quat::from_rotation_x(60_f32.to_radians())),
+			),
+			TeleportSurface {
+				half_size: Vec2::splat(20.0),
+			},
+		));
+		app.update();
+
+		let target = aim_and_release(&mut app).expect("the arc should hit the slope");
+		assert!(!target.valid);
+		assert_eq!(rig_transform(&app, rig), Transform::default());
+	}
+}
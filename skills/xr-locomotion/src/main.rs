@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+use bevy_oxr::DefaultXrPlugins;
+use openxr_6dof::poses::XrPosePlugin;
+use xr_locomotion::comfort::ComfortPlugin;
+use xr_locomotion::xr::XrLocomotionPlugin;
+use xr_locomotion::{LocomotionPlugin, TeleportSurface};
+
+fn main() {
+	color_eyre::install().unwrap();
+
+	info!("Running `xr-locomotion` skill");
+	App::new()
+		.add_plugins(DefaultXrPlugins)
+		.add_plugins(XrPosePlugin)
+		.add_plugins((LocomotionPlugin, XrLocomotionPlugin, ComfortPlugin))
+		.add_systems(Startup, setup)
+		.run();
+}
+
+/// set up a simple 3D scene with a few places to teleport to
+fn setup(
+	mut commands: Commands,
+	mut meshes: ResMut<Assets<Mesh>>,
+	mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+	// floor
+	commands.spawn((
+		PbrBundle {
+			mesh: meshes.add(shape::Plane::from_size(20.0).into()),
+			material: materials.add(Color::rgb(0.3, 0.5, 0.3).into()),
+			..default()
+		},
+		TeleportSurface {
+			half_size: Vec2::splat(10.0),
+		},
+	));
+	// raised platform, the teleport surface sits on its top face
+	commands
+		.spawn(PbrBundle {
+			mesh: meshes.add(shape::Box::new(3.0, 1.0, 3.0).into()),
+			material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
+			transform: Transform::from_xyz(4.0, 0.5, -4.0),
+			..default()
+		})
+		.with_children(|parent| {
+			parent.spawn((
+				SpatialBundle::from_transform(Transform::from_xyz(0.0, 0.5, 0.0)),
+				TeleportSurface {
+					half_size: Vec2::splat(1.5),
+				},
+			));
+		});
+	// ramp that is too steep to teleport onto
+	commands.spawn((
+		PbrBundle {
+			mesh: meshes.add(shape::Box::new(3.0, 0.1, 3.0).into()),
+			material: materials.add(Color::rgb(0.6, 0.3, 0.3).into()),
+			transform: Transform::from_xyz(-4.0, 1.0, -4.0)
+				.with_rotation(Quat::from_rotation_x(50_f32.to_radians())),
+			..default()
+		},
+		TeleportSurface {
+			half_size: Vec2::splat(1.5),
+		},
+	));
+	// light
+	commands.spawn(PointLightBundle {
+		point_light: PointLight {
+			intensity: 1500.0,
+			shadows_enabled: true,
+			..default()
+		},
+		transform: Transform::from_xyz(4.0, 8.0, 4.0),
+		..default()
+	});
+}
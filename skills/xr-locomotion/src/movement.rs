@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+
+use crate::{
+	apply_deadzone, head_offset, LocomotionHand, LocomotionInput, LocomotionSettings,
+	LocomotionState, MoveReference, PlayerHead, PlayerRig, TurnMode,
+};
+
+pub(crate) fn turn(
+	time: Res<Time>,
+	settings: Res<LocomotionSettings>,
+	input: Res<LocomotionInput>,
+	mut state: ResMut<LocomotionState>,
+	mut rig: Query<(&mut Transform, &GlobalTransform), With<PlayerRig>>,
+	head: Query<&GlobalTransform, With<PlayerHead>>,
+) {
+	let Ok((mut rig_transform, rig_global)) = rig.get_single_mut() else {
+		return;
+	};
+	let stick = input.turn_axis;
+	let angle = match settings.turn {
+		TurnMode::Snap { angle, threshold } => {
+			if stick.abs() < threshold * 0.5 {
+				state.snap_armed = true;
+				0.0
+			} else if state.snap_armed && stick.abs() >= threshold {
+				state.snap_armed = false;
+				angle * stick.signum()
+			} else {
+				0.0
+			}
+		}
+		TurnMode::Smooth { speed } => {
+			apply_deadzone(Vec2::new(stick, 0.0), settings.deadzone).x
+				* speed * time.delta_seconds()
+		}
+	};
+	if angle == 0.0 {
+		return;
+	}
+
+	// Pivot around the head, otherwise a user standing away from the centre of their
+	// playspace gets swung around the room.
+	let pivot = match head.get_single() {
+		Ok(head) => rig_transform.transform_point(head_offset(rig_global, head)),
+		Err(_) => rig_transform.translation,
+	};
+	// A positive stick value turns right, which is clockwise seen from above.
+	rig_transform.rotate_around(pivot, Quat::from_rotation_y(-angle));
+}
+
+pub(crate) fn smooth_move(
+	time: Res<Time>,
+	settings: Res<LocomotionSettings>,
+	input: Res<LocomotionInput>,
+	mut rig: Query<&mut Transform, With<PlayerRig>>,
+	head: Query<&GlobalTransform, With<PlayerHead>>,
+	hand: Query<&GlobalTransform, With<LocomotionHand>>,
+) {
+	let axis = apply_deadzone(input.move_axis, settings.deadzone);
+	if axis == Vec2::ZERO {
+		return;
+	}
+	let Ok(mut rig_transform) = rig.get_single_mut() else {
+		return;
+	};
+	let reference = match settings.move_reference {
+		MoveReference::Head => head.get_single(),
+		MoveReference::Hand => hand.get_single(),
+	};
+	let right = match reference {
+		Ok(reference) => reference.right(),
+		Err(_) => rig_transform.right(),
+	};
+	// Derive forward from right rather than flattening the forward vector, so that
+	// looking (or pointing) straight down still moves somewhere sensible.
+	let right = Vec3::new(right.x, 0.0, right.z).normalize_or_zero();
+	let forward = Vec3::Y.cross(right);
+
+	let velocity = (forward * axis.y + right * axis.x) * settings.move_speed;
+	rig_transform.translation += velocity * time.delta_seconds();
+}
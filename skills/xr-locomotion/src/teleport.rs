@@ -0,0 +1,157 @@
+use bevy::prelude::*;
+
+use crate::{
+	head_offset, LocomotionHand, LocomotionInput, LocomotionSettings, LocomotionState,
+	PlayerHead, PlayerRig, TeleportSettings,
+};
+
+/// Something the teleport arc can land on: a rectangle in the entity's local XZ
+/// plane, facing its local +Y.
+///
+/// The arc only collides with these, so it will happily pass through walls that
+/// aren't tagged.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct TeleportSurface {
+	/// Half of the width (x) and depth (z) of the rectangle, in local units.
+	pub half_size: Vec2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TeleportTarget {
+	pub position: Vec3,
+	pub normal: Vec3,
+	/// False if the surface was too steep to stand on.
+	pub valid: bool,
+}
+
+pub(crate) fn aim_teleport(
+	settings: Res<LocomotionSettings>,
+	input: Res<LocomotionInput>,
+	mut state: ResMut<LocomotionState>,
+	hand: Query<&GlobalTransform, With<LocomotionHand>>,
+	surfaces: Query<(&GlobalTransform, &TeleportSurface)>,
+) {
+	if !input.teleport || !settings.teleport.enabled {
+		return;
+	}
+	let Ok(hand) = hand.get_single() else {
+		return;
+	};
+	let state = &mut *state;
+	state.arc.clear();
+	state.teleport_target = trace_arc(
+		hand.translation(),
+		hand.forward(),
+		&settings.teleport,
+		&surfaces,
+		&mut state.arc,
+	);
+}
+
+pub(crate) fn teleport(
+	input: Res<LocomotionInput>,
+	mut state: ResMut<LocomotionState>,
+	mut rig: Query<(&mut Transform, &GlobalTransform), With<PlayerRig>>,
+	head: Query<&GlobalTransform, With<PlayerHead>>,
+) {
+	let released = state.was_aiming && !input.teleport;
+	state.was_aiming = input.teleport;
+	if input.teleport {
+		return;
+	}
+	let target = state.teleport_target.take();
+	state.arc.clear();
+	if !released {
+		return;
+	}
+	let Some(target) = target.filter(|t| t.valid) else {
+		return;
+	};
+	let Ok((mut rig_transform, rig_global)) = rig.get_single_mut() else {
+		return;
+	};
+
+	// Put the point on the floor under the head onto the target, rather than the
+	// rig's origin, which could be anywhere in the playspace.
+	let feet = match head.get_single() {
+		Ok(head) => rig_transform.transform_point(head_offset(rig_global, head)),
+		Err(_) => rig_transform.translation,
+	};
+	rig_transform.translation += target.position - feet;
+}
+
+pub(crate) fn draw_teleport_arc(state: Res<LocomotionState>, mut gizmos: Gizmos) {
+	if state.arc.is_empty() {
+		return;
+	}
+	let color = match state.teleport_target {
+		Some(TeleportTarget { valid: true, .. }) => Color::LIME_GREEN,
+		_ => Color::RED,
+	};
+	gizmos.linestrip(state.arc.iter().copied(), color);
+	if let Some(target) = state.teleport_target {
+		gizmos.circle(target.position, target.normal, 0.3, color);
+	}
+}
+
+/// Simulates a projectile thrown from `origin`, pushing the points it passes through
+/// into `arc`, and returns where it hit a [`TeleportSurface`].
+fn trace_arc(
+	origin: Vec3,
+	direction: Vec3,
+	settings: &TeleportSettings,
+	surfaces: &Query<(&GlobalTransform, &TeleportSurface)>,
+	arc: &mut Vec<Vec3>,
+) -> Option<TeleportTarget> {
+	let dt = settings.time_step;
+	let gravity = Vec3::NEG_Y * settings.gravity;
+	let mut position = origin;
+	let mut velocity = direction * settings.launch_speed;
+	arc.push(position);
+	for _ in 0..settings.max_segments {
+		let next = position + velocity * dt + 0.5 * gravity * dt * dt;
+		velocity += gravity * dt;
+
+		let hit = surfaces
+			.iter()
+			.filter_map(|(transform, surface)| {
+				intersect_surface(position, next, transform, surface)
+			})
+			.min_by(|a, b| a.0.total_cmp(&b.0));
+		if let Some((_, position, normal)) = hit {
+			arc.push(position);
+			return Some(TeleportTarget {
+				position,
+				normal,
+				valid: normal.angle_between(Vec3::Y) <= settings.max_slope,
+			});
+		}
+
+		arc.push(next);
+		position = next;
+	}
+	None
+}
+
+/// Intersects the segment from `start` to `end` with the top side of a surface,
+/// returning the fraction along the segment, the point of intersection and the
+/// surface normal.
+fn intersect_surface(
+	start: Vec3,
+	end: Vec3,
+	transform: &GlobalTransform,
+	surface: &TeleportSurface,
+) -> Option<(f32, Vec3, Vec3)> {
+	let to_local = transform.affine().inverse();
+	let start_local = to_local.transform_point3(start);
+	let end_local = to_local.transform_point3(end);
+	if start_local.y < 0.0 || end_local.y >= 0.0 {
+		return None;
+	}
+	let t = start_local.y / (start_local.y - end_local.y);
+	let hit = start_local.lerp(end_local, t);
+	if hit.x.abs() > surface.half_size.x || hit.z.abs() > surface.half_size.y {
+		return None;
+	}
+	Some((t, start.lerp(end, t), transform.up()))
+}
@@ -0,0 +1,87 @@
+//! Drives locomotion from OpenXR. This is the only part of the crate that needs a
+//! headset.
+
+use bevy::prelude::*;
+use bevy_oxr::input::XrInput;
+use bevy_oxr::resources::{XrFrameState, XrInstance, XrSession};
+use bevy_oxr::xr_input::oculus_touch::OculusController;
+use bevy_oxr::xr_input::trackers::OpenXRTrackingRoot;
+use bevy_oxr::xr_input::Hand;
+use openxr_6dof::poses::{XrPoseSet, XrPoses};
+
+use crate::{LocomotionHand, LocomotionInput, LocomotionSet, PlayerHead, PlayerRig};
+
+/// Turns the OpenXR tracking root into the [`PlayerRig`], tracks the head and right
+/// hand, and maps the Oculus Touch thumbsticks to [`LocomotionInput`]. Needs the
+/// [`XrPosePlugin`](openxr_6dof::poses::XrPosePlugin).
+///
+/// - Left thumbstick: move
+/// - Right thumbstick left/right: turn
+/// - Right thumbstick forward: aim teleport, release to teleport
+pub struct XrLocomotionPlugin;
+
+impl Plugin for XrLocomotionPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_systems(
+			Update,
+			(
+				setup_rig,
+				track_poses.in_set(XrPoseSet::Apply),
+				controller_input,
+			)
+				.chain()
+				.before(LocomotionSet),
+		);
+	}
+}
+
+/// The XR cameras are children of the tracking root, so that is what we move around.
+fn setup_rig(mut commands: Commands, roots: Query<Entity, Added<OpenXRTrackingRoot>>) {
+	for root in roots.iter() {
+		let head = commands.spawn((SpatialBundle::default(), PlayerHead)).id();
+		let hand = commands
+			.spawn((SpatialBundle::default(), LocomotionHand))
+			.id();
+		commands
+			.entity(root)
+			.insert(PlayerRig)
+			.push_children(&[head, hand]);
+	}
+}
+
+/// Poses are relative to the stage, which is the local space of the tracking root.
+fn track_poses(
+	poses: Res<XrPoses>,
+	mut head: Query<&mut Transform, With<PlayerHead>>,
+	mut hand: Query<&mut Transform, (With<LocomotionHand>, Without<PlayerHead>)>,
+) {
+	if let Some(head_pose) = poses.head {
+		for mut transform in head.iter_mut() {
+			*transform = head_pose;
+		}
+	}
+	if let Some(hand_pose) = poses.right_aim {
+		for mut transform in hand.iter_mut() {
+			*transform = hand_pose;
+		}
+	}
+}
+
+fn controller_input(
+	oculus_controller: Res<OculusController>,
+	frame_state: Res<XrFrameState>,
+	xr_input: Res<XrInput>,
+	instance: Res<XrInstance>,
+	session: Res<XrSession>,
+	mut input: ResMut<LocomotionInput>,
+) {
+	let frame_state = *frame_state.lock().unwrap();
+	let controller =
+		oculus_controller.get_ref(&instance, &session, &frame_state, &xr_input);
+	let left = controller.thumbstick(Hand::Left);
+	let right = controller.thumbstick(Hand::Right);
+
+	input.move_axis = Vec2::new(left.x, left.y);
+	input.teleport = right.y > 0.7;
+	input.turn_axis = if input.teleport { 0.0 } else { right.x };
+}